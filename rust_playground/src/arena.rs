@@ -0,0 +1,293 @@
+// ============================================
+// Arena - 用下标代替指针的对象池
+// ============================================
+//
+// `Rc<RefCell<T>>` 组成的图，每条边都是一个引用计数指针：
+// 成环就泄漏，反向边要换成 `Weak`，每次访问都要 `borrow()` / `upgrade()`。
+//
+// Arena 换了一种思路：所有节点都放在一个 `Vec` 里，由 Arena 统一拥有，
+// 节点之间用下标（`NodeId`）互相引用。下标只是个数字，想怎么连就怎么连，
+// 成环也不会泄漏——Arena drop 时所有节点一起释放。
+//
+// 下标的问题是"悬空"：节点删掉以后，它的槽位会被新节点重用，旧下标就悄悄指向了别人。
+// 所以每个槽位带一个代数（generation），每重用一次加一；`NodeId` 同时记住下标和代数，
+// 代数对不上就说明这个 id 已经过期，`get` 返回 `None`。
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
+/// Arena 里某个值的句柄：下标 + 代数
+///
+/// 类型参数只用来区分不同 Arena 的 id，`NodeId<A>` 不能拿去查 `Arena<B>`。
+pub struct NodeId<T> {
+    index: u32,
+    generation: u32,
+    _type: PhantomData<fn() -> T>,
+}
+
+struct Slot<T> {
+    generation: u32,
+    /// `None` 表示空槽，它的下标在空闲列表里
+    value: Option<T>,
+}
+
+/// 槽位里的值，代数对不上时返回 `None`
+fn live_value<T>(slot: Option<&mut Slot<T>>, id: NodeId<T>) -> Option<&mut T> {
+    match slot {
+        Some(slot) if slot.generation == id.generation => slot.value.as_mut(),
+        _ => None,
+    }
+}
+
+/// 按下标存放值的对象池，删除后的槽位会被重用
+pub struct Arena<T> {
+    slots: Vec<Slot<T>>,
+    free: Vec<u32>,
+    len: usize,
+}
+
+impl<T> NodeId<T> {
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+
+    pub fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+// 手写而不是 derive：derive 会要求 T 也实现这些 trait，而 id 本身和 T 无关
+impl<T> Clone for NodeId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodeId<T> {}
+
+impl<T> PartialEq for NodeId<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.index, self.generation) == (other.index, other.generation)
+    }
+}
+
+impl<T> Eq for NodeId<T> {}
+
+impl<T> Hash for NodeId<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.index, self.generation).hash(state);
+    }
+}
+
+/// 写成 `#下标v代数`，例如 `#3v1`
+impl<T> fmt::Debug for NodeId<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}v{}", self.index, self.generation)
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Arena<T> {
+        Arena {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// 放入一个值，优先重用空槽
+    pub fn insert(&mut self, value: T) -> NodeId<T> {
+        self.len += 1;
+        let index = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.generation += 1;
+                slot.value = Some(value);
+                index
+            }
+            None => {
+                let index = u32::try_from(self.slots.len()).expect("Arena 最多放 u32::MAX 个值");
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                index
+            }
+        };
+        NodeId {
+            index,
+            generation: self.slots[index as usize].generation,
+            _type: PhantomData,
+        }
+    }
+
+    /// 取出并删除；id 已经过期时返回 `None`
+    pub fn remove(&mut self, id: NodeId<T>) -> Option<T> {
+        let slot = self.slots.get_mut(id.index())?;
+        if slot.generation != id.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        self.free.push(id.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    pub fn get(&self, id: NodeId<T>) -> Option<&T> {
+        match self.slots.get(id.index()) {
+            Some(slot) if slot.generation == id.generation => slot.value.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, id: NodeId<T>) -> Option<&mut T> {
+        live_value(self.slots.get_mut(id.index()), id)
+    }
+
+    /// 同时可变地借出两个不同的值
+    ///
+    /// # Panics
+    ///
+    /// `a` 和 `b` 是同一个 id 时 panic。
+    pub fn get2_mut(&mut self, a: NodeId<T>, b: NodeId<T>) -> (Option<&mut T>, Option<&mut T>) {
+        assert!(a.index != b.index, "get2_mut 的两个 id 不能指向同一个槽位");
+        let (first, second) = if a.index < b.index { (a, b) } else { (b, a) };
+        let (head, tail) = self.slots.split_at_mut(second.index());
+        let first_value = live_value(head.get_mut(first.index()), first);
+        let second_value = live_value(tail.first_mut(), second);
+        if a.index < b.index {
+            (first_value, second_value)
+        } else {
+            (second_value, first_value)
+        }
+    }
+
+    pub fn contains(&self, id: NodeId<T>) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 按下标顺序遍历所有活着的值
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId<T>, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let value = slot.value.as_ref()?;
+            let id = NodeId {
+                index: index as u32,
+                generation: slot.generation,
+                _type: PhantomData,
+            };
+            Some((id, value))
+        })
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = NodeId<T>> + '_ {
+        self.iter().map(|(id, _)| id)
+    }
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<T> std::ops::Index<NodeId<T>> for Arena<T> {
+    type Output = T;
+
+    /// # Panics
+    ///
+    /// id 已经过期时 panic；不确定时用 [`Arena::get`]。
+    fn index(&self, id: NodeId<T>) -> &T {
+        match self.get(id) {
+            Some(value) => value,
+            None => panic!("过期的 NodeId {:?}", id),
+        }
+    }
+}
+
+impl<T> std::ops::IndexMut<NodeId<T>> for Arena<T> {
+    fn index_mut(&mut self, id: NodeId<T>) -> &mut T {
+        match self.get_mut(id) {
+            Some(value) => value,
+            None => panic!("过期的 NodeId {:?}", id),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Arena<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut arena = Arena::new();
+        let a = arena.insert("a");
+        let b = arena.insert("b");
+        assert_eq!((arena[a], arena[b]), ("a", "b"));
+        assert_eq!(arena.len(), 2);
+
+        arena[b] = "B";
+        assert_eq!(arena.remove(b), Some("B"));
+        assert_eq!(arena.remove(b), None);
+        assert_eq!(arena.len(), 1);
+        assert!(!arena.contains(b));
+        assert_eq!(format!("{:?}", arena), "{#0v0: \"a\"}");
+    }
+
+    #[test]
+    fn test_reused_slot_makes_old_id_stale() {
+        let mut arena = Arena::new();
+        let old = arena.insert(1);
+        arena.remove(old);
+        let new = arena.insert(2);
+
+        // 同一个槽位，代数不同
+        assert_eq!(old.index(), new.index());
+        assert_eq!((old.generation(), new.generation()), (0, 1));
+        assert_eq!(arena.get(old), None);
+        assert_eq!(arena.get_mut(old), None);
+        assert_eq!(arena.remove(old), None);
+        assert_eq!(arena.get(new), Some(&2));
+    }
+
+    #[test]
+    #[should_panic(expected = "过期的 NodeId #0v0")]
+    fn test_indexing_with_stale_id_panics() {
+        let mut arena = Arena::new();
+        let id = arena.insert(1);
+        arena.remove(id);
+        arena.insert(2);
+        let _ = arena[id];
+    }
+
+    #[test]
+    fn test_get2_mut_and_iter() {
+        let mut arena = Arena::new();
+        let ids: Vec<_> = (0..4).map(|i| arena.insert(i)).collect();
+        arena.remove(ids[1]);
+
+        let (x, y) = arena.get2_mut(ids[3], ids[0]);
+        std::mem::swap(x.unwrap(), y.unwrap());
+        let (stale, live) = arena.get2_mut(ids[1], ids[2]);
+        assert!(stale.is_none());
+        *live.unwrap() *= 10;
+
+        let values: Vec<_> = arena.iter().map(|(_, &v)| v).collect();
+        assert_eq!(values, [3, 20, 0]);
+        assert_eq!(arena.ids().collect::<Vec<_>>(), [ids[0], ids[2], ids[3]]);
+    }
+}
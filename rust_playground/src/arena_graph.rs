@@ -0,0 +1,385 @@
+// ============================================
+// Arena 有向图 - BFS、DFS、找环、拓扑排序
+// ============================================
+//
+// 用 `Rc<RefCell<Node>>` 表示一般的有向图很别扭：边可能成环，
+// 哪些边该用 `Rc`、哪些该用 `Weak` 根本说不清，写错了就泄漏。
+//
+// 放进 Arena 以后，边只是一个 `VertexId`，成环完全没问题，图 drop 时所有节点一起释放。
+// 每个节点同时记着出边和入边，删除节点时可以顺着入边把指向它的边也删掉，
+// 不会留下指向已删除节点的边。
+//
+// 所有遍历都按"节点创建顺序、出边添加顺序"进行，结果是确定的。
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::arena::{Arena, NodeId};
+
+/// 图的顶点，存放在 [`Graph`] 里
+pub struct Vertex<T> {
+    pub value: T,
+    out: Vec<VertexId<T>>,
+    incoming: Vec<VertexId<T>>,
+}
+
+/// 顶点的句柄
+pub type VertexId<T> = NodeId<Vertex<T>>;
+
+/// 有向图，顶点放在 Arena 里，边用 id 表示
+pub struct Graph<T> {
+    vertices: Arena<Vertex<T>>,
+    edges: usize,
+}
+
+/// `topo_sort` 失败：图里有环，附带其中一个环
+#[derive(Debug, PartialEq, Eq)]
+pub struct CycleError<T> {
+    pub cycle: Vec<VertexId<T>>,
+}
+
+impl<T> Graph<T> {
+    pub fn new() -> Graph<T> {
+        Graph {
+            vertices: Arena::new(),
+            edges: 0,
+        }
+    }
+
+    pub fn add_vertex(&mut self, value: T) -> VertexId<T> {
+        self.vertices.insert(Vertex {
+            value,
+            out: vec![],
+            incoming: vec![],
+        })
+    }
+
+    /// 删除顶点和所有与它相连的边；id 过期时返回 `None`
+    pub fn remove_vertex(&mut self, id: VertexId<T>) -> Option<T> {
+        let vertex = self.vertices.remove(id)?;
+        for &to in &vertex.out {
+            // 自环的另一端就是它自己，已经删掉了
+            if let Some(to) = self.vertices.get_mut(to) {
+                to.incoming.retain(|&from| from != id);
+            }
+        }
+        for &from in &vertex.incoming {
+            if let Some(from) = self.vertices.get_mut(from) {
+                from.out.retain(|&to| to != id);
+            }
+        }
+        let self_loops = vertex.out.iter().filter(|&&to| to == id).count();
+        self.edges -= vertex.out.len() + vertex.incoming.len() - self_loops;
+        Some(vertex.value)
+    }
+
+    /// 添加一条边 `from → to`；已经有这条边时什么也不做，返回 `false`
+    ///
+    /// # Panics
+    ///
+    /// 任意一端的 id 过期时 panic。
+    pub fn add_edge(&mut self, from: VertexId<T>, to: VertexId<T>) -> bool {
+        assert!(self.vertices.contains(to), "过期的 VertexId {:?}", to);
+        if self.vertices[from].out.contains(&to) {
+            return false;
+        }
+        self.vertices[from].out.push(to);
+        self.vertices[to].incoming.push(from);
+        self.edges += 1;
+        true
+    }
+
+    /// 删除边 `from → to`，没有这条边时返回 `false`
+    pub fn remove_edge(&mut self, from: VertexId<T>, to: VertexId<T>) -> bool {
+        let Some(vertex) = self.vertices.get_mut(from) else {
+            return false;
+        };
+        let before = vertex.out.len();
+        vertex.out.retain(|&id| id != to);
+        if vertex.out.len() == before {
+            return false;
+        }
+        self.vertices[to].incoming.retain(|&id| id != from);
+        self.edges -= 1;
+        true
+    }
+
+    pub fn contains(&self, id: VertexId<T>) -> bool {
+        self.vertices.contains(id)
+    }
+
+    pub fn get(&self, id: VertexId<T>) -> Option<&T> {
+        self.vertices.get(id).map(|vertex| &vertex.value)
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges
+    }
+
+    /// 所有顶点，按创建顺序（重用的槽位排在原来的位置）
+    pub fn vertices(&self) -> impl Iterator<Item = VertexId<T>> + '_ {
+        self.vertices.ids()
+    }
+
+    /// 出边指向的顶点
+    pub fn neighbors(&self, id: VertexId<T>) -> &[VertexId<T>] {
+        &self.vertices[id].out
+    }
+
+    /// 有边指向 `id` 的顶点
+    pub fn predecessors(&self, id: VertexId<T>) -> &[VertexId<T>] {
+        &self.vertices[id].incoming
+    }
+
+    /// 广度优先：从 `start` 出发能到达的顶点，按距离由近到远
+    pub fn bfs(&self, start: VertexId<T>) -> Vec<VertexId<T>> {
+        let mut seen = HashSet::from([start]);
+        let mut queue = VecDeque::from([start]);
+        let mut order = vec![];
+        while let Some(id) = queue.pop_front() {
+            order.push(id);
+            for &next in self.neighbors(id) {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        order
+    }
+
+    /// 深度优先（先序）：从 `start` 出发能到达的顶点
+    pub fn dfs(&self, start: VertexId<T>) -> Vec<VertexId<T>> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![start];
+        let mut order = vec![];
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id) {
+                continue;
+            }
+            order.push(id);
+            // 倒着压栈，先访问先添加的边
+            stack.extend(
+                self.neighbors(id)
+                    .iter()
+                    .rev()
+                    .filter(|next| !seen.contains(next)),
+            );
+        }
+        order
+    }
+
+    /// 找出一个环，按边的方向排列：`[a, b, c]` 表示 `a → b → c → a`
+    ///
+    /// 深度优先搜索时记住当前路径，遇到指回路径上顶点的边就找到了环。
+    pub fn find_cycle(&self) -> Option<Vec<VertexId<T>>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            OnPath,
+            Done,
+        }
+
+        let mut state: HashMap<VertexId<T>, State> = HashMap::new();
+        for start in self.vertices() {
+            if state.contains_key(&start) {
+                continue;
+            }
+            // 栈里存 (顶点, 下一条要看的出边)，和递归时的调用栈一一对应
+            let mut path: Vec<(VertexId<T>, usize)> = vec![(start, 0)];
+            state.insert(start, State::OnPath);
+            while let Some((id, edge)) = path.last_mut() {
+                let id = *id;
+                let Some(&next) = self.neighbors(id).get(*edge) else {
+                    state.insert(id, State::Done);
+                    path.pop();
+                    continue;
+                };
+                *edge += 1;
+                match state.get(&next) {
+                    Some(State::OnPath) => {
+                        let begin = path.iter().position(|&(v, _)| v == next).unwrap();
+                        return Some(path[begin..].iter().map(|&(v, _)| v).collect());
+                    }
+                    Some(State::Done) => {}
+                    None => {
+                        state.insert(next, State::OnPath);
+                        path.push((next, 0));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    pub fn has_cycle(&self) -> bool {
+        self.find_cycle().is_some()
+    }
+
+    /// 拓扑排序（Kahn 算法）：每条边 `a → b` 里 `a` 都排在 `b` 前面
+    ///
+    /// 同时可以排的顶点按创建顺序排列。有环时返回其中一个环。
+    pub fn topo_sort(&self) -> Result<Vec<VertexId<T>>, CycleError<T>> {
+        let mut in_degree: HashMap<VertexId<T>, usize> = self
+            .vertices()
+            .map(|id| (id, self.predecessors(id).len()))
+            .collect();
+        let mut ready: VecDeque<VertexId<T>> =
+            self.vertices().filter(|id| in_degree[id] == 0).collect();
+        let mut order = Vec::with_capacity(self.vertex_count());
+        while let Some(id) = ready.pop_front() {
+            order.push(id);
+            for next in self.neighbors(id) {
+                let degree = in_degree.get_mut(next).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push_back(*next);
+                }
+            }
+        }
+        if order.len() == self.vertex_count() {
+            Ok(order)
+        } else {
+            Err(CycleError {
+                cycle: self.find_cycle().unwrap(),
+            })
+        }
+    }
+
+    /// 把一串 id 换成对应的值，方便打印和断言
+    pub fn values<'a>(&'a self, ids: impl IntoIterator<Item = VertexId<T>> + 'a) -> Vec<&'a T> {
+        ids.into_iter().map(|id| &self[id]).collect()
+    }
+}
+
+impl<T> Default for Graph<T> {
+    fn default() -> Self {
+        Graph::new()
+    }
+}
+
+impl<T> Index<VertexId<T>> for Graph<T> {
+    type Output = T;
+
+    fn index(&self, id: VertexId<T>) -> &T {
+        &self.vertices[id].value
+    }
+}
+
+impl<T> IndexMut<VertexId<T>> for Graph<T> {
+    fn index_mut(&mut self, id: VertexId<T>) -> &mut T {
+        &mut self.vertices[id].value
+    }
+}
+
+/// 每行一个顶点和它的出边：`a → [b, c]`
+impl<T: fmt::Display> fmt::Display for Graph<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (_, vertex) in self.vertices.iter() {
+            let targets: Vec<String> = vertex.out.iter().map(|&to| self[to].to_string()).collect();
+            writeln!(f, "{} → [{}]", vertex.value, targets.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 一个构建依赖图：`a → b` 表示 a 要先于 b 完成
+    ///
+    /// ```text
+    /// fetch → compile → test → release
+    ///            ↑        ↑
+    ///          config ────┘
+    /// ```
+    fn build_graph() -> (Graph<&'static str>, Vec<VertexId<&'static str>>) {
+        let mut graph = Graph::new();
+        let ids: Vec<_> = ["fetch", "compile", "test", "release", "config"]
+            .into_iter()
+            .map(|name| graph.add_vertex(name))
+            .collect();
+        for (from, to) in [(0, 1), (1, 2), (2, 3), (4, 1), (4, 2)] {
+            graph.add_edge(ids[from], ids[to]);
+        }
+        (graph, ids)
+    }
+
+    #[test]
+    fn test_bfs_and_dfs_order() {
+        let (graph, ids) = build_graph();
+        assert_eq!(
+            graph.values(graph.bfs(ids[4])),
+            [&"config", &"compile", &"test", &"release"]
+        );
+        assert_eq!(
+            graph.values(graph.dfs(ids[0])),
+            [&"fetch", &"compile", &"test", &"release"]
+        );
+        assert_eq!(graph.values(graph.dfs(ids[3])), [&"release"]);
+    }
+
+    #[test]
+    fn test_topo_sort_respects_every_edge() {
+        let (graph, _) = build_graph();
+        let order = graph.topo_sort().unwrap();
+        assert_eq!(
+            graph.values(order.iter().copied()),
+            [&"fetch", &"config", &"compile", &"test", &"release"]
+        );
+        let position: HashMap<_, _> = order.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+        for from in graph.vertices() {
+            for to in graph.neighbors(from) {
+                assert!(position[&from] < position[to]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cycle_is_found_and_blocks_topo_sort() {
+        let (mut graph, ids) = build_graph();
+        assert_eq!(graph.find_cycle(), None);
+
+        graph.add_edge(ids[3], ids[1]);
+        let cycle = graph.find_cycle().unwrap();
+        assert_eq!(graph.values(cycle), [&"compile", &"test", &"release"]);
+        let err = graph.topo_sort().unwrap_err();
+        assert_eq!(err.cycle.len(), 3);
+
+        assert!(graph.remove_edge(ids[3], ids[1]));
+        assert!(!graph.remove_edge(ids[3], ids[1]));
+        assert!(!graph.has_cycle());
+
+        // 自环也是环
+        graph.add_edge(ids[0], ids[0]);
+        assert_eq!(graph.find_cycle(), Some(vec![ids[0]]));
+    }
+
+    #[test]
+    fn test_remove_vertex_drops_its_edges() {
+        let (mut graph, ids) = build_graph();
+        assert!(!graph.add_edge(ids[0], ids[1]));
+        assert_eq!(graph.edge_count(), 5);
+        graph.add_edge(ids[1], ids[1]);
+
+        assert_eq!(graph.remove_vertex(ids[1]), Some("compile"));
+        assert_eq!(graph.remove_vertex(ids[1]), None);
+        assert_eq!((graph.vertex_count(), graph.edge_count()), (4, 2));
+        assert!(graph.neighbors(ids[0]).is_empty());
+        assert_eq!(graph.neighbors(ids[4]), [ids[2]]);
+
+        // 新顶点重用了 compile 的槽位，但旧 id 不会指向它
+        let lint = graph.add_vertex("lint");
+        assert_eq!(lint.index(), ids[1].index());
+        assert!(!graph.contains(ids[1]));
+        assert_eq!(graph.get(lint), Some(&"lint"));
+        assert_eq!(
+            graph.to_string(),
+            "fetch → []\nlint → []\ntest → [release]\nrelease → []\nconfig → [test]\n"
+        );
+    }
+}
@@ -0,0 +1,405 @@
+// ============================================
+// Arena 树 - 用 NodeId 代替 Rc/Weak 的树
+// ============================================
+//
+// 和 `tree.rs` 是同一棵树，换成 Arena 来存：
+// - 父节点、子节点都只是 `TreeId`（下标 + 代数），没有 `Rc`、`Weak`、`RefCell`
+// - 所有节点由 `ArenaTree` 拥有，树 drop 时一起释放
+// - 修改通过 `&mut ArenaTree` 进行，借用检查在编译期完成，不会在运行时 panic
+//
+// 代价是节点不会"没人引用就自动释放"：摘下来的子树还留在 Arena 里，
+// 不要了得自己调用 `remove_subtree`。
+//
+// 传入过期的 id（节点已经被删掉）会 panic；不确定时先用 `contains` 或 `get` 检查。
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+use crate::arena::{Arena, NodeId};
+
+/// 树节点，存放在 [`ArenaTree`] 里
+pub struct Node<T> {
+    pub value: T,
+    parent: Option<TreeId<T>>,
+    children: Vec<TreeId<T>>,
+}
+
+/// 树节点的句柄
+pub type TreeId<T> = NodeId<Node<T>>;
+
+/// 所有节点放在同一个 Arena 里的树（准确地说是森林：可以有多个根）
+pub struct ArenaTree<T> {
+    nodes: Arena<Node<T>>,
+}
+
+impl<T> ArenaTree<T> {
+    pub fn new() -> ArenaTree<T> {
+        ArenaTree {
+            nodes: Arena::new(),
+        }
+    }
+
+    /// 新建一个还没有父节点的节点
+    pub fn add_node(&mut self, value: T) -> TreeId<T> {
+        self.nodes.insert(Node {
+            value,
+            parent: None,
+            children: vec![],
+        })
+    }
+
+    /// 把 `child` 挂到 `parent` 下面（放在最后），已有父节点时先摘下来
+    ///
+    /// # Panics
+    ///
+    /// `child` 是 `parent` 自己或者它的祖先时 panic：那样树就成环了。
+    pub fn add_child(&mut self, parent: TreeId<T>, child: TreeId<T>) {
+        assert!(
+            !self.is_ancestor_of(child, parent),
+            "不能把节点挂到它自己的子树下面"
+        );
+        self.detach(child);
+        // 上面的检查排除了 child == parent，可以同时可变地借出两个节点
+        match self.nodes.get2_mut(parent, child) {
+            (Some(parent_node), Some(child_node)) => {
+                child_node.parent = Some(parent);
+                parent_node.children.push(child);
+            }
+            _ => unreachable!("is_ancestor_of 已经检查过两个 id 都没有过期"),
+        }
+    }
+
+    /// 从父节点上摘下来，成为一棵独立的树；原来没有父节点时返回 `false`
+    pub fn detach(&mut self, node: TreeId<T>) -> bool {
+        match self.nodes[node].parent.take() {
+            Some(parent) => {
+                self.nodes[parent].children.retain(|&child| child != node);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 删除整棵子树，按先序返回被删掉的值；之后这些节点的 id 都会过期
+    pub fn remove_subtree(&mut self, node: TreeId<T>) -> Vec<T> {
+        self.detach(node);
+        let ids: Vec<_> = self.pre_order(node).collect();
+        ids.into_iter()
+            .map(|id| self.nodes.remove(id).unwrap().value)
+            .collect()
+    }
+
+    pub fn contains(&self, node: TreeId<T>) -> bool {
+        self.nodes.contains(node)
+    }
+
+    /// 节点的值；id 过期时返回 `None`
+    pub fn get(&self, node: TreeId<T>) -> Option<&T> {
+        self.nodes.get(node).map(|node| &node.value)
+    }
+
+    pub fn get_mut(&mut self, node: TreeId<T>) -> Option<&mut T> {
+        self.nodes.get_mut(node).map(|node| &mut node.value)
+    }
+
+    /// 所有树加起来的节点数
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn parent(&self, node: TreeId<T>) -> Option<TreeId<T>> {
+        self.nodes[node].parent
+    }
+
+    /// 不用克隆：直接借出子节点列表
+    pub fn children(&self, node: TreeId<T>) -> &[TreeId<T>] {
+        &self.nodes[node].children
+    }
+
+    pub fn is_root(&self, node: TreeId<T>) -> bool {
+        self.parent(node).is_none()
+    }
+
+    pub fn is_leaf(&self, node: TreeId<T>) -> bool {
+        self.children(node).is_empty()
+    }
+
+    /// 到根节点的边数，根节点是 0
+    pub fn depth(&self, node: TreeId<T>) -> usize {
+        self.path_to_root(node).len() - 1
+    }
+
+    /// 子树的高度：叶子是 0
+    pub fn height(&self, node: TreeId<T>) -> usize {
+        self.children(node)
+            .iter()
+            .map(|&child| self.height(child) + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 从 `node` 一路往上到根节点，第一个元素是 `node` 自己
+    pub fn path_to_root(&self, node: TreeId<T>) -> Vec<TreeId<T>> {
+        let mut path = vec![node];
+        while let Some(parent) = self.parent(*path.last().unwrap()) {
+            path.push(parent);
+        }
+        path
+    }
+
+    /// 所在树的根节点
+    pub fn root(&self, node: TreeId<T>) -> TreeId<T> {
+        *self.path_to_root(node).last().unwrap()
+    }
+
+    /// `ancestor` 是不是 `node` 自己或者它的祖先
+    pub fn is_ancestor_of(&self, ancestor: TreeId<T>, node: TreeId<T>) -> bool {
+        self.path_to_root(node).contains(&ancestor)
+    }
+
+    /// 子树里的节点总数（包括自己）
+    pub fn size(&self, node: TreeId<T>) -> usize {
+        self.pre_order(node).count()
+    }
+
+    /// 先序遍历：先自己，再从左到右访问子树
+    pub fn pre_order(&self, node: TreeId<T>) -> PreOrder<'_, T> {
+        PreOrder {
+            tree: self,
+            stack: vec![node],
+        }
+    }
+
+    /// 后序遍历：先从左到右访问子树，最后才是自己
+    pub fn post_order(&self, node: TreeId<T>) -> PostOrder<'_, T> {
+        PostOrder {
+            tree: self,
+            stack: vec![(node, false)],
+        }
+    }
+
+    /// 层序遍历：按深度一层一层地访问
+    pub fn level_order(&self, node: TreeId<T>) -> LevelOrder<'_, T> {
+        LevelOrder {
+            tree: self,
+            queue: VecDeque::from([node]),
+        }
+    }
+
+    /// 以 `node` 为根画出子树，格式和 `TreeNode` 的 `Display` 一样
+    pub fn display(&self, node: TreeId<T>) -> Subtree<'_, T> {
+        Subtree { tree: self, node }
+    }
+}
+
+impl<T> Default for ArenaTree<T> {
+    fn default() -> Self {
+        ArenaTree::new()
+    }
+}
+
+/// `tree[id]` 直接访问节点的值
+impl<T> Index<TreeId<T>> for ArenaTree<T> {
+    type Output = T;
+
+    fn index(&self, node: TreeId<T>) -> &T {
+        &self.nodes[node].value
+    }
+}
+
+impl<T> IndexMut<TreeId<T>> for ArenaTree<T> {
+    fn index_mut(&mut self, node: TreeId<T>) -> &mut T {
+        &mut self.nodes[node].value
+    }
+}
+
+/// 先序遍历器，见 [`ArenaTree::pre_order`]
+///
+/// 遍历器借着整棵树，所以遍历期间不能修改树——这由编译器保证，
+/// 而 `tree.rs` 只能靠每一步都克隆子节点列表来避免 `RefCell` 冲突。
+pub struct PreOrder<'a, T> {
+    tree: &'a ArenaTree<T>,
+    stack: Vec<TreeId<T>>,
+}
+
+impl<T> Iterator for PreOrder<'_, T> {
+    type Item = TreeId<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // 倒着压栈，弹出时才是从左到右
+        self.stack
+            .extend(self.tree.children(node).iter().rev().copied());
+        Some(node)
+    }
+}
+
+/// 后序遍历器，见 [`ArenaTree::post_order`]
+pub struct PostOrder<'a, T> {
+    tree: &'a ArenaTree<T>,
+    /// 第二个字段表示子节点是否已经压过栈
+    stack: Vec<(TreeId<T>, bool)>,
+}
+
+impl<T> Iterator for PostOrder<'_, T> {
+    type Item = TreeId<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(node);
+            }
+            self.stack.push((node, true));
+            self.stack.extend(
+                self.tree
+                    .children(node)
+                    .iter()
+                    .rev()
+                    .map(|&child| (child, false)),
+            );
+        }
+    }
+}
+
+/// 层序遍历器，见 [`ArenaTree::level_order`]
+pub struct LevelOrder<'a, T> {
+    tree: &'a ArenaTree<T>,
+    queue: VecDeque<TreeId<T>>,
+}
+
+impl<T> Iterator for LevelOrder<'_, T> {
+    type Item = TreeId<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(self.tree.children(node));
+        Some(node)
+    }
+}
+
+/// [`ArenaTree::display`] 的返回值
+pub struct Subtree<'a, T> {
+    tree: &'a ArenaTree<T>,
+    node: TreeId<T>,
+}
+
+impl<T: fmt::Display> fmt::Display for Subtree<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.tree[self.node])?;
+        self.write_children(self.node, "", f)
+    }
+}
+
+impl<T: fmt::Display> Subtree<'_, T> {
+    fn write_children(&self, node: TreeId<T>, prefix: &str, f: &mut fmt::Formatter) -> fmt::Result {
+        let children = self.tree.children(node);
+        for (i, &child) in children.iter().enumerate() {
+            let last = i + 1 == children.len();
+            let (branch, indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            writeln!(f, "{}{}{}", prefix, branch, self.tree[child])?;
+            self.write_children(child, &format!("{}{}", prefix, indent), f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 和 `tree.rs` 的测试用同一棵树：
+    ///
+    /// ```text
+    /// 1
+    /// ├── 2
+    /// │   ├── 4
+    /// │   └── 5
+    /// └── 3
+    ///     └── 6
+    /// ```
+    fn sample() -> (ArenaTree<i32>, Vec<TreeId<i32>>) {
+        let mut tree = ArenaTree::new();
+        let ids: Vec<_> = (1..=6).map(|v| tree.add_node(v)).collect();
+        tree.add_child(ids[0], ids[1]);
+        tree.add_child(ids[0], ids[2]);
+        tree.add_child(ids[1], ids[3]);
+        tree.add_child(ids[1], ids[4]);
+        tree.add_child(ids[2], ids[5]);
+        (tree, ids)
+    }
+
+    fn values(tree: &ArenaTree<i32>, ids: impl IntoIterator<Item = TreeId<i32>>) -> Vec<i32> {
+        ids.into_iter().map(|id| tree[id]).collect()
+    }
+
+    #[test]
+    fn test_traversals_match_the_rc_tree() {
+        let (tree, ids) = sample();
+        let root = ids[0];
+        assert_eq!(values(&tree, tree.pre_order(root)), [1, 2, 4, 5, 3, 6]);
+        assert_eq!(values(&tree, tree.post_order(root)), [4, 5, 2, 6, 3, 1]);
+        assert_eq!(values(&tree, tree.level_order(root)), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(tree.size(root), 6);
+        assert_eq!(tree.height(root), 2);
+        assert_eq!(tree.depth(ids[4]), 2);
+        assert_eq!(values(&tree, tree.path_to_root(ids[4])), [5, 2, 1]);
+        assert_eq!(tree.root(ids[5]), root);
+        assert!(tree.is_leaf(ids[3]) && tree.is_root(root));
+    }
+
+    #[test]
+    fn test_reparent_and_display() {
+        let (mut tree, ids) = sample();
+        tree.add_child(ids[5], ids[1]);
+        assert_eq!(tree.parent(ids[1]), Some(ids[5]));
+        assert_eq!(tree.children(ids[0]), [ids[2]]);
+        let expected = "\
+1
+└── 3
+    └── 6
+        └── 2
+            ├── 4
+            └── 5
+";
+        assert_eq!(tree.display(ids[0]).to_string(), expected);
+    }
+
+    #[test]
+    #[should_panic(expected = "不能把节点挂到它自己的子树下面")]
+    fn test_adding_an_ancestor_as_child_panics() {
+        let (mut tree, ids) = sample();
+        tree.add_child(ids[3], ids[0]);
+    }
+
+    #[test]
+    fn test_detach_keeps_nodes_until_removed() {
+        let (mut tree, ids) = sample();
+        assert!(tree.detach(ids[1]));
+        assert!(!tree.detach(ids[1]));
+        // 摘下来的子树还在 Arena 里，成了另一棵树的根
+        assert_eq!(tree.len(), 6);
+        assert!(tree.is_root(ids[1]));
+        assert_eq!(tree.size(ids[0]), 3);
+
+        assert_eq!(tree.remove_subtree(ids[1]), [2, 4, 5]);
+        assert_eq!(tree.len(), 3);
+        assert!(!tree.contains(ids[4]));
+        assert_eq!(tree.get(ids[4]), None);
+
+        // 新节点重用了空槽，旧 id 仍然是过期的
+        let new = tree.add_node(7);
+        assert_eq!(tree.get(ids[4]), None);
+        *tree.get_mut(new).unwrap() += 1;
+        assert_eq!(tree[new], 8);
+    }
+}
@@ -0,0 +1,216 @@
+// ============================================
+// Rust 异步编程 - 深度示例
+// ============================================
+//
+// 所有示例都运行在 `executor.rs` 里的最小执行器上，不依赖 tokio 等外部 crate。
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::executor::{self, block_on, sleep, spawn, yield_now};
+use crate::lesson::{self, Difficulty, Registry, UNORDERED};
+
+/// 手写的 Future：每次被 poll 倒数一次，数到 0 才完成
+struct Countdown {
+    remaining: u32,
+}
+
+impl Future for Countdown {
+    type Output = &'static str;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.remaining == 0 {
+            outln!("poll: 完成");
+            return Poll::Ready("发射！");
+        }
+        outln!("poll: 还剩 {}", self.remaining);
+        self.remaining -= 1;
+        // 返回 Pending 之前必须安排好唤醒，否则执行器再也不会 poll 它
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+async fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+/// Future 与 poll
+pub fn future_basics() {
+    outln!("\n=== 1. Future 与 poll ===");
+
+    // Future 是惰性的：创建出来什么都不会发生，要交给执行器去 poll
+    let countdown = Countdown { remaining: 3 };
+    outln!("创建了 Countdown，还没有被 poll");
+    let result = block_on(countdown);
+    outln!("结果: {}", result);
+
+    // async fn 返回的也是一个 Future，编译器会生成对应的状态机
+    let future = add(1, 2);
+    outln!("add(1, 2) 返回 Future，.await 或 block_on 之后才得到: {}", block_on(future));
+
+    // async 块里可以 .await 其他 Future
+    let total = block_on(async {
+        let a = add(1, 2).await;
+        let b = add(a, 10).await;
+        a + b
+    });
+    outln!("async 块: {}", total);
+
+    outln!("\n💡 Poll::Pending 表示\"还没好，准备好了会用 Waker 通知你\"");
+}
+
+/// spawn：在同一个线程上交替运行多个任务
+pub fn spawn_tasks() {
+    outln!("\n=== 2. spawn 与任务队列 ===");
+
+    block_on(async {
+        let handles: Vec<_> = ["A", "B", "C"]
+            .into_iter()
+            .map(|name| {
+                spawn(async move {
+                    for step in 1..=2 {
+                        outln!("任务 {} 第 {} 步", name, step);
+                        // 让出执行权：当前任务排到队尾，其他任务先运行
+                        yield_now().await;
+                    }
+                    format!("{} 完成", name)
+                })
+            })
+            .collect();
+
+        // JoinHandle 也是 Future，await 它拿到任务的返回值
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await);
+        }
+        outln!("所有任务完成，返回值: {:?}", results);
+    });
+
+    outln!("\n💡 只有一个线程：任务在 .await 处主动让出，执行器按队列顺序轮流 poll");
+}
+
+/// 定时器 Future：sleep 不占用线程
+pub fn timer_future() {
+    outln!("\n=== 3. 定时器 Future ===");
+
+    block_on(async {
+        let (tx, mut rx) = executor::channel();
+        for ms in [300, 100, 200] {
+            let tx = tx.clone();
+            spawn(async move {
+                // 把 Waker 交给后台定时器线程，到期后由它唤醒这个任务
+                sleep(Duration::from_millis(ms)).await;
+                tx.send(ms);
+            });
+        }
+        drop(tx);
+
+        while let Some(ms) = rx.recv().await {
+            outln!("睡了 {}ms 的任务醒来", ms);
+        }
+    });
+
+    outln!("💡 三个 sleep 同时进行，总耗时接近最长的 300ms，而不是 600ms");
+    outln!("💡 换成 thread::sleep 会阻塞整个执行器，所有任务都得排队等");
+}
+
+/// 异步版本的消息传递
+pub fn async_message_passing() {
+    outln!("\n=== 4. 异步消息传递 ===");
+
+    block_on(async {
+        let (tx, mut rx) = executor::channel();
+
+        spawn(async move {
+            let val = String::from("hi");
+            tx.send(val);
+            // outln!("{}", val); // ❌ val 已被移动，和线程版本一样
+        });
+
+        // recv().await 在没有消息时让出执行权，而不是阻塞线程
+        let received = rx.recv().await.unwrap();
+        outln!("Got: {}", received);
+    });
+}
+
+/// 异步版本的生产者-消费者
+pub fn async_producer_consumer() {
+    outln!("\n=== 5. 异步生产者-消费者 ===");
+
+    block_on(async {
+        let (tx, mut rx) = executor::channel();
+
+        // 生产者
+        let producer = spawn(async move {
+            for i in 0..10 {
+                outln!("生产者: 生产 {}", i);
+                tx.send(i);
+                sleep(Duration::from_millis(10)).await;
+            }
+        });
+
+        // 消费者
+        let consumer = spawn(async move {
+            while let Some(received) = rx.recv().await {
+                outln!("消费者: 消费 {}", received);
+                sleep(Duration::from_millis(20)).await;
+            }
+        });
+
+        producer.await;
+        consumer.await;
+    });
+
+    outln!("💡 两个任务共用一个线程，sleep 期间执行器去运行另一个任务");
+}
+
+/// 登记本模块的示例
+pub fn register(registry: &mut Registry) {
+    let mut topic = registry.topic("async", "异步编程", run_all_async_examples);
+
+    topic
+        .lesson("future_basics", "Future 与 poll", future_basics)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["future", "poll", "waker"])
+        .requires(&["trait_generic::trait_basics"]);
+    topic
+        .lesson("spawn_tasks", "spawn 与任务队列", spawn_tasks)
+        .level(Difficulty::Advanced)
+        .minutes(8)
+        .tags(&["executor", "spawn"])
+        .requires(&["async::future_basics"]);
+    topic
+        .lesson("timer_future", "定时器 Future", timer_future)
+        .level(Difficulty::Advanced)
+        .minutes(8)
+        .tags(&["executor", "timer", "waker"])
+        .requires(&["async::spawn_tasks"]);
+    topic
+        .lesson("async_message_passing", "异步消息传递", async_message_passing)
+        .level(Difficulty::Advanced)
+        .minutes(5)
+        .tags(&["channel"])
+        .requires(&["async::spawn_tasks", "concurrency::message_passing"]);
+    topic
+        .lesson("async_producer_consumer", "异步生产者-消费者", async_producer_consumer)
+        .level(Difficulty::Advanced)
+        .minutes(8)
+        .tags(&["channel", "pattern", "timer", UNORDERED])
+        .requires(&["async::timer_future", "concurrency::producer_consumer"]);
+}
+
+/// 运行所有示例
+pub fn run_all_async_examples() {
+    outln!("\n");
+    outln!("╔════════════════════════════════════════╗");
+    outln!("║   Rust 异步编程 - 完整示例            ║");
+    outln!("╚════════════════════════════════════════╝");
+
+    lesson::registry().run_topic("async");
+
+    outln!("\n=== 异步编程示例完成 ===\n");
+}
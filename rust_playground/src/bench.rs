@@ -0,0 +1,90 @@
+// ============================================
+// 简易计时工具 - 不依赖外部 crate 的基准测试
+// ============================================
+//
+// 只用来在课堂上比较几种写法的相对快慢：先预热一次，再重复运行若干次取平均。
+// 数字会受机器、负载和编译模式影响，想看真实差距请用
+// `cargo run --release -- run <示例>`。
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// 一组计时结果
+pub struct Measurement {
+    pub name: String,
+    pub iterations: u32,
+    pub total: Duration,
+    /// 每次运行处理的元素 / 操作数量，用来换算吞吐量
+    pub items: u64,
+}
+
+impl Measurement {
+    /// 平均每次运行的耗时
+    pub fn per_iter(&self) -> Duration {
+        self.total / self.iterations
+    }
+
+    /// 每秒处理的元素 / 操作数量
+    pub fn throughput(&self) -> f64 {
+        let secs = self.total.as_secs_f64();
+        if secs == 0.0 {
+            return f64::INFINITY;
+        }
+        self.items as f64 * self.iterations as f64 / secs
+    }
+}
+
+/// 运行 `f` 共 `iterations` 次（另有一次不计时的预热）并计时
+///
+/// `items` 是每次运行处理的元素数量。返回值经过 `black_box`，
+/// 防止编译器把整个计算优化掉。
+pub fn measure<F, R>(name: &str, iterations: u32, items: u64, mut f: F) -> Measurement
+where
+    F: FnMut() -> R,
+{
+    let iterations = iterations.max(1);
+    black_box(f());
+
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+
+    Measurement {
+        name: name.to_string(),
+        iterations,
+        total: start.elapsed(),
+        items,
+    }
+}
+
+/// 打印对比表，第一行作为基准计算加速比
+pub fn report(results: &[Measurement]) {
+    let baseline = match results.first() {
+        Some(first) => first.per_iter().as_secs_f64(),
+        None => return,
+    };
+
+    outln!(
+        "{:<28} {:>12} {:>16} {:>8}",
+        "方案",
+        "每次耗时",
+        "吞吐量(个/秒)",
+        "加速比"
+    );
+    for result in results {
+        let per_iter = result.per_iter();
+        let speedup = if per_iter.is_zero() {
+            f64::INFINITY
+        } else {
+            baseline / per_iter.as_secs_f64()
+        };
+        outln!(
+            "{:<28} {:>12} {:>16.0} {:>7.2}x",
+            result.name,
+            format!("{:.2?}", per_iter),
+            result.throughput(),
+            speedup
+        );
+    }
+}
@@ -0,0 +1,264 @@
+// ============================================
+// 通道工具 - 手写 select 和广播通道
+// ============================================
+//
+// 标准库的 `mpsc` 只提供一对一的接收端，这里用 std 原语补上两个常用工具：
+// - `Select`：同时等待多个接收端，哪个先有消息就返回哪个。
+//   std 的 `Receiver` 无法注册唤醒回调，所以只能轮流 `try_recv` 加退避；
+//   crossbeam-channel 之类的库会把等待的线程挂在每个通道上，不需要轮询
+// - `Broadcast`：每条消息复制给所有订阅者（发布-订阅），
+//   订阅者的接收端 drop 之后会在下一次发送时被自动移除
+
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// `Select` 接收失败的原因
+#[derive(Debug, PartialEq)]
+pub enum SelectError {
+    /// 等待超时，还有接收端没有断开
+    Timeout,
+    /// 所有接收端的发送端都已经 drop，不会再有消息了
+    Disconnected,
+}
+
+impl fmt::Display for SelectError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SelectError::Timeout => f.write_str("等待超时"),
+            SelectError::Disconnected => f.write_str("所有通道都已断开"),
+        }
+    }
+}
+
+/// 同时等待多个接收端
+///
+/// 每次从上一次成功的下一个接收端开始检查，避免某个繁忙的通道饿死其他通道。
+pub struct Select<'a, T> {
+    receivers: Vec<&'a Receiver<T>>,
+    next: usize,
+}
+
+impl<'a, T> Select<'a, T> {
+    pub fn new() -> Select<'a, T> {
+        Select {
+            receivers: Vec::new(),
+            next: 0,
+        }
+    }
+
+    /// 加入一个接收端，返回它的编号（`recv` 的结果里用这个编号区分来源）
+    pub fn add(&mut self, receiver: &'a Receiver<T>) -> usize {
+        self.receivers.push(receiver);
+        self.receivers.len() - 1
+    }
+
+    /// 不等待：轮一圈，有消息就返回
+    fn poll(&mut self) -> Result<(usize, T), TryRecvError> {
+        let count = self.receivers.len();
+        let mut disconnected = 0;
+        for offset in 0..count {
+            let index = (self.next + offset) % count;
+            match self.receivers[index].try_recv() {
+                Ok(message) => {
+                    self.next = (index + 1) % count;
+                    return Ok((index, message));
+                }
+                Err(TryRecvError::Empty) => {}
+                Err(TryRecvError::Disconnected) => disconnected += 1,
+            }
+        }
+        if disconnected == count {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// 阻塞直到任意一个接收端收到消息，返回 `(编号, 消息)`
+    pub fn recv(&mut self) -> Result<(usize, T), SelectError> {
+        self.wait(None)
+    }
+
+    /// 和 `recv` 一样，但最多等待 `timeout`
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<(usize, T), SelectError> {
+        self.wait(Some(Instant::now() + timeout))
+    }
+
+    fn wait(&mut self, deadline: Option<Instant>) -> Result<(usize, T), SelectError> {
+        let mut rounds = 0u32;
+        loop {
+            match self.poll() {
+                Ok(received) => return Ok(received),
+                Err(TryRecvError::Disconnected) => return Err(SelectError::Disconnected),
+                Err(TryRecvError::Empty) => {}
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Err(SelectError::Timeout);
+            }
+            // 退避：先让出几次 CPU，还没有消息就短暂 sleep
+            rounds += 1;
+            if rounds < 16 {
+                thread::yield_now();
+            } else {
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+}
+
+impl<T> Default for Select<'_, T> {
+    fn default() -> Self {
+        Select::new()
+    }
+}
+
+/// 广播通道：每条消息都会复制给每个订阅者
+///
+/// 克隆出来的 `Broadcast` 共享同一组订阅者；最后一个 `Broadcast` drop 后，
+/// 所有订阅者的 `recv` 都会返回 `RecvError`。
+pub struct Broadcast<T> {
+    subscribers: Arc<Mutex<Vec<Sender<T>>>>,
+}
+
+impl<T> Clone for Broadcast<T> {
+    fn clone(&self) -> Self {
+        Broadcast {
+            subscribers: Arc::clone(&self.subscribers),
+        }
+    }
+}
+
+impl<T: Clone> Broadcast<T> {
+    pub fn new() -> Broadcast<T> {
+        Broadcast {
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// 订阅：只会收到订阅之后发送的消息
+    pub fn subscribe(&self) -> Receiver<T> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// 发给所有订阅者，返回送达的数量；接收端已经 drop 的订阅者会被移除
+    pub fn send(&self, message: T) -> usize {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|subscriber| subscriber.send(message.clone()).is_ok());
+        subscribers.len()
+    }
+
+    /// 当前的订阅者数量（已经断开但还没被 `send` 清理的也算在内）
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+}
+
+impl<T: Clone> Default for Broadcast<T> {
+    fn default() -> Self {
+        Broadcast::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::{RecvError, RecvTimeoutError, TrySendError};
+
+    #[test]
+    fn test_select_alternates_between_ready_receivers() {
+        let (tx_a, rx_a) = mpsc::channel();
+        let (tx_b, rx_b) = mpsc::channel();
+        for i in 0..3 {
+            tx_a.send(format!("a{}", i)).unwrap();
+            tx_b.send(format!("b{}", i)).unwrap();
+        }
+        drop((tx_a, tx_b));
+
+        let mut select = Select::new();
+        select.add(&rx_a);
+        select.add(&rx_b);
+        let mut received = Vec::new();
+        while let Ok((_, message)) = select.recv() {
+            received.push(message);
+        }
+        assert_eq!(received, ["a0", "b0", "a1", "b1", "a2", "b2"]);
+    }
+
+    #[test]
+    fn test_select_keeps_going_while_one_receiver_is_alive() {
+        let (tx_a, rx_a) = mpsc::channel::<i32>();
+        let (tx_b, rx_b) = mpsc::channel();
+        drop(tx_a);
+
+        let mut select = Select::new();
+        select.add(&rx_a);
+        let b = select.add(&rx_b);
+
+        assert_eq!(
+            select.recv_timeout(Duration::from_millis(10)),
+            Err(SelectError::Timeout)
+        );
+
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx_b.send(7).unwrap();
+        });
+        assert_eq!(select.recv(), Ok((b, 7)));
+        sender.join().unwrap();
+        assert_eq!(select.recv(), Err(SelectError::Disconnected));
+    }
+
+    #[test]
+    fn test_broadcast_prunes_dropped_subscribers() {
+        let broadcast = Broadcast::new();
+        let first = broadcast.subscribe();
+        let second = broadcast.subscribe();
+
+        assert_eq!(broadcast.send(1), 2);
+        drop(second);
+        assert_eq!(broadcast.subscriber_count(), 2);
+        assert_eq!(broadcast.send(2), 1);
+        assert_eq!(broadcast.subscriber_count(), 1);
+
+        assert_eq!(first.try_iter().collect::<Vec<_>>(), [1, 2]);
+    }
+
+    #[test]
+    fn test_broadcast_disconnects_subscribers_when_dropped() {
+        let broadcast = Broadcast::new();
+        let clone = broadcast.clone();
+        let subscriber = broadcast.subscribe();
+
+        drop(broadcast);
+        clone.send("still open");
+        assert_eq!(subscriber.recv(), Ok("still open"));
+
+        drop(clone);
+        assert_eq!(subscriber.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn test_std_disconnection_errors() {
+        // 发送端全部 drop：接收端先收完缓冲里的消息，然后报告断开
+        let (tx, rx) = mpsc::sync_channel(1);
+        tx.send(1).unwrap();
+        assert_eq!(tx.try_send(2), Err(TrySendError::Full(2)));
+        drop(tx);
+        assert_eq!(rx.recv_timeout(Duration::from_millis(10)), Ok(1));
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+
+        // 接收端 drop：发送端立刻报错，消息原样退回
+        let (tx, rx) = mpsc::sync_channel(1);
+        drop(rx);
+        assert_eq!(tx.try_send(3), Err(TrySendError::Disconnected(3)));
+        assert_eq!(tx.send(4).unwrap_err().0, 4);
+    }
+}
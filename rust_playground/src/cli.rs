@@ -0,0 +1,265 @@
+// ============================================
+// 命令行入口 - 按主题 / 函数名运行示例
+// ============================================
+//
+// 用法：
+//   rust_playground                         运行 main.rs 里的基础语法演练
+//   rust_playground list                    列出所有主题和示例函数
+//   rust_playground run ownership           运行某个主题的全部示例
+//   rust_playground run concurrency::barrier_example
+//                                           只运行某一个示例函数
+
+use crate::lesson::{self, Lesson, Topic};
+use crate::scheduler;
+
+/// 解析后的命令
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// 不带参数：运行 main.rs 里原有的基础语法演练
+    Playground,
+    /// `list [--ids] [--tag <标签>]`：列出所有主题和示例
+    List(ListOptions),
+    /// `path`：按前置课程排出的学习顺序
+    Path,
+    /// `run <目标>... [--seed <n>]`：目标可以是主题名，也可以是 `主题::函数名`
+    Run(RunOptions),
+    /// `help` / `-h` / `--help`
+    Help,
+}
+
+/// `list` 的选项
+#[derive(Debug, Default, PartialEq)]
+pub struct ListOptions {
+    /// 只输出课程 id，每行一个，方便脚本和测试使用
+    pub ids_only: bool,
+    /// 只列出带有这个标签的课程
+    pub tag: Option<String>,
+}
+
+/// `run` 的选项
+#[derive(Debug, Default, PartialEq)]
+pub struct RunOptions {
+    pub targets: Vec<String>,
+    /// 并发示例使用确定性调度器，并用这个种子决定线程交错
+    pub seed: Option<u64>,
+}
+
+/// 把命令行参数（不含程序名）解析为 [`Command`]
+pub fn parse_args<I>(args: I) -> Result<Command, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter();
+
+    let command = match args.next() {
+        None => return Ok(Command::Playground),
+        Some(command) => command,
+    };
+
+    match command.as_str() {
+        "list" | "ls" => {
+            let mut options = ListOptions::default();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--ids" => options.ids_only = true,
+                    "--tag" => {
+                        let tag = args.next().ok_or("--tag 需要一个标签名")?;
+                        options.tag = Some(tag);
+                    }
+                    other => return Err(format!("list 不支持的参数: {}", other)),
+                }
+            }
+            Ok(Command::List(options))
+        }
+        "path" => Ok(Command::Path),
+        "help" | "-h" | "--help" => Ok(Command::Help),
+        "run" => {
+            let mut options = RunOptions::default();
+            while let Some(arg) = args.next() {
+                if arg == "--seed" {
+                    let seed = args.next().ok_or("--seed 需要一个整数种子")?;
+                    let seed = seed.parse().map_err(|_| format!("无效的种子: {}", seed))?;
+                    options.seed = Some(seed);
+                } else {
+                    options.targets.push(arg);
+                }
+            }
+            if options.targets.is_empty() {
+                Err(String::from("run 需要至少一个目标，例如: run ownership"))
+            } else {
+                Ok(Command::Run(options))
+            }
+        }
+        other => Err(format!("未知命令: {}", other)),
+    }
+}
+
+/// 一个解析好的运行目标
+pub enum Target {
+    Topic(&'static Topic),
+    Lesson(&'static Lesson),
+}
+
+/// 按名字查找目标：`ownership` 或 `concurrency::barrier_example`
+pub fn resolve(target: &str) -> Result<Target, String> {
+    let registry = lesson::registry();
+
+    match target.split_once("::") {
+        None => registry
+            .find_topic(target)
+            .map(Target::Topic)
+            .ok_or_else(|| format!("未知主题: {}（用 `list` 查看所有主题）", target)),
+        Some((topic, demo)) => {
+            registry
+                .find_topic(topic)
+                .ok_or_else(|| format!("未知主题: {}（用 `list` 查看所有主题）", topic))?;
+            registry
+                .find(target)
+                .map(Target::Lesson)
+                .ok_or_else(|| format!("主题 {} 中没有示例: {}", topic, demo))
+        }
+    }
+}
+
+/// 打印所有主题和示例函数
+pub fn print_list(options: &ListOptions) {
+    let registry = lesson::registry();
+    let selected = |lesson: &&Lesson| match &options.tag {
+        Some(tag) => lesson.has_tag(tag),
+        None => true,
+    };
+
+    if options.ids_only {
+        for lesson in registry.lessons().iter().filter(selected) {
+            println!("{}", lesson.id);
+        }
+        return;
+    }
+
+    println!("可用的主题和示例：\n");
+    for topic in registry.topics() {
+        let lessons: Vec<&Lesson> = registry.lessons_in(topic.name).filter(selected).collect();
+        if lessons.is_empty() {
+            continue;
+        }
+        let minutes: u32 = lessons.iter().map(|lesson| lesson.minutes).sum();
+        println!(
+            "{:<15} {}（{} 节，约 {} 分钟）",
+            topic.name,
+            topic.title,
+            lessons.len(),
+            minutes
+        );
+        for lesson in lessons {
+            println!(
+                "    {:<45} {:<12} {:>3} 分钟  {}",
+                lesson.id, lesson.difficulty, lesson.minutes, lesson.title
+            );
+        }
+        println!();
+    }
+}
+
+/// 打印按前置课程排好的学习顺序
+pub fn print_learning_path() -> Result<(), String> {
+    let path = lesson::registry().learning_path()?;
+    let mut total = 0;
+
+    println!("推荐学习顺序：\n");
+    for (i, lesson) in path.iter().enumerate() {
+        total += lesson.minutes;
+        println!("{:>3}. {:<45} {}", i + 1, lesson.id, lesson.title);
+    }
+    println!("\n共 {} 节，约 {} 分钟", path.len(), total);
+    Ok(())
+}
+
+/// 打印用法说明
+pub fn print_help() {
+    println!("用法:");
+    println!("  rust_playground                      运行基础语法演练");
+    println!("  rust_playground list                 列出所有主题和示例");
+    println!("  rust_playground list --tag <标签>    只列出带某个标签的示例");
+    println!("  rust_playground path                 按前置课程排出学习顺序");
+    println!("  rust_playground run <主题>           运行某个主题的全部示例");
+    println!("  rust_playground run <主题>::<函数>   只运行一个示例函数");
+    println!("  rust_playground run <目标> --seed <n>");
+    println!("                                       并发示例使用确定性调度，同一个种子输出相同");
+    println!();
+    println!("例如:");
+    println!("  cargo run -- run ownership");
+    println!("  cargo run -- run concurrency::barrier_example");
+    println!("  cargo run -- run concurrency::barrier_example --seed 42");
+}
+
+/// 先解析所有目标再依次运行，避免跑到一半才发现拼写错误
+pub fn run_targets(options: &RunOptions) -> Result<(), String> {
+    let resolved = options
+        .targets
+        .iter()
+        .map(|target| resolve(target))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    scheduler::set_seed(options.seed);
+    for target in resolved {
+        match target {
+            Target::Topic(topic) => (topic.run_all)(),
+            Target::Lesson(lesson) => (lesson.run)(),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse_args(args(&[])), Ok(Command::Playground));
+        assert_eq!(
+            parse_args(args(&["list"])),
+            Ok(Command::List(ListOptions::default()))
+        );
+        assert_eq!(
+            parse_args(args(&["list", "--ids", "--tag", "slow"])),
+            Ok(Command::List(ListOptions {
+                ids_only: true,
+                tag: Some(String::from("slow")),
+            }))
+        );
+        assert!(parse_args(args(&["list", "--tag"])).is_err());
+        assert_eq!(
+            parse_args(args(&["run", "ownership", "lifetime"])),
+            Ok(Command::Run(RunOptions {
+                targets: args(&["ownership", "lifetime"]),
+                seed: None,
+            }))
+        );
+        assert_eq!(
+            parse_args(args(&["run", "concurrency", "--seed", "42"])),
+            Ok(Command::Run(RunOptions {
+                targets: args(&["concurrency"]),
+                seed: Some(42),
+            }))
+        );
+        assert!(parse_args(args(&["run", "concurrency", "--seed", "x"])).is_err());
+        assert!(parse_args(args(&["run"])).is_err());
+        assert!(parse_args(args(&["fly"])).is_err());
+    }
+
+    #[test]
+    fn test_resolve() {
+        assert!(matches!(resolve("ownership"), Ok(Target::Topic(t)) if t.name == "ownership"));
+        assert!(matches!(
+            resolve("concurrency::barrier_example"),
+            Ok(Target::Lesson(l)) if l.id == "concurrency::barrier_example"
+        ));
+        assert!(resolve("nope").is_err());
+        assert!(resolve("ownership::nope").is_err());
+    }
+}
@@ -4,66 +4,106 @@
 
 use std::thread;
 use std::time::Duration;
-use std::sync::{Arc, Mutex, mpsc};
+use std::sync::{Arc, Barrier, Condvar, Mutex, mpsc};
+use std::sync::mpsc::{RecvTimeoutError, TryRecvError, TrySendError};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::bench;
+use crate::channels::{Broadcast, Select};
+use crate::counters;
+use crate::deadlock_detector::{LockDetector, TrackedMutex};
+use crate::lesson::{self, Difficulty, Registry, NONDETERMINISTIC, SEEDED, SLOW, UNORDERED};
+use crate::parallel::{self, Par};
+use crate::scheduler::{self, Rng, Scheduler};
+use crate::sync_primitives::{BlockingQueue, CountDownLatch, ReusableBarrier, Semaphore};
+use crate::thread_pool::{self, ThreadPool};
+use crate::utils::math_utils;
+use crate::work_stealing::{self, Mode, WorkerStats};
 
 /// 创建线程基础
 pub fn basic_threads() {
-    println!("\n=== 1. 创建线程基础 ===");
+    outln!("\n=== 1. 创建线程基础 ===");
+
+    if let Some(seed) = scheduler::seed() {
+        return basic_threads_deterministic(seed);
+    }
 
     // 创建新线程
     let handle = thread::spawn(|| {
         for i in 1..10 {
-            println!("hi number {} from the spawned thread!", i);
+            outln!("hi number {} from the spawned thread!", i);
             thread::sleep(Duration::from_millis(1));
         }
     });
 
     // 主线程
     for i in 1..5 {
-        println!("hi number {} from the main thread!", i);
+        outln!("hi number {} from the main thread!", i);
         thread::sleep(Duration::from_millis(1));
     }
 
     // 等待线程完成
     handle.join().unwrap();
-    println!("所有线程完成");
+    outln!("所有线程完成");
+}
+
+/// `basic_threads` 的确定性版本：sleep 变成逻辑时钟上的让出点
+fn basic_threads_deterministic(seed: u64) {
+    outln!("[确定性调度 seed = {}]", seed);
+
+    Scheduler::run(seed, |ctx| {
+        let handle = ctx.spawn(|ctx| {
+            for i in 1..10 {
+                outln!("hi number {} from the spawned thread!", i);
+                ctx.sleep(Duration::from_millis(1));
+            }
+        });
+
+        for i in 1..5 {
+            outln!("hi number {} from the main thread!", i);
+            ctx.sleep(Duration::from_millis(1));
+        }
+
+        ctx.join(handle).unwrap();
+        outln!("所有线程完成");
+    });
 }
 
 /// 使用 move 闭包
 pub fn move_closures() {
-    println!("\n=== 2. 使用 move 闭包 ===");
+    outln!("\n=== 2. 使用 move 闭包 ===");
 
     let v = vec![1, 2, 3];
 
     // move 关键字强制闭包获取所有权
     let handle = thread::spawn(move || {
-        println!("vector from thread: {:?}", v);
+        outln!("vector from thread: {:?}", v);
     });
 
-    // println!("v: {:?}", v); // ❌ v 已被移动
+    // outln!("v: {:?}", v); // ❌ v 已被移动
 
     handle.join().unwrap();
 }
 
 /// 消息传递 - Channel
 pub fn message_passing() {
-    println!("\n=== 3. 消息传递 - Channel ===");
+    outln!("\n=== 3. 消息传递 - Channel ===");
 
     let (tx, rx) = mpsc::channel();
 
     thread::spawn(move || {
         let val = String::from("hi");
         tx.send(val).unwrap();
-        // println!("{}", val); // ❌ val 已被移动
+        // outln!("{}", val); // ❌ val 已被移动
     });
 
     let received = rx.recv().unwrap();
-    println!("Got: {}", received);
+    outln!("Got: {}", received);
 }
 
 /// 发送多个值
 pub fn multiple_messages() {
-    println!("\n=== 4. 发送多个值 ===");
+    outln!("\n=== 4. 发送多个值 ===");
 
     let (tx, rx) = mpsc::channel();
 
@@ -83,13 +123,17 @@ pub fn multiple_messages() {
 
     // rx 作为迭代器
     for received in rx {
-        println!("Got: {}", received);
+        outln!("Got: {}", received);
     }
 }
 
 /// 多个生产者
 pub fn multiple_producers() {
-    println!("\n=== 5. 多个生产者 ===");
+    outln!("\n=== 5. 多个生产者 ===");
+
+    if let Some(seed) = scheduler::seed() {
+        return multiple_producers_deterministic(seed);
+    }
 
     let (tx, rx) = mpsc::channel();
 
@@ -123,13 +167,42 @@ pub fn multiple_producers() {
     });
 
     for received in rx {
-        println!("Got: {}", received);
+        outln!("Got: {}", received);
     }
 }
 
+/// `multiple_producers` 的确定性版本：两个生产者的消息按种子决定的顺序交错
+fn multiple_producers_deterministic(seed: u64) {
+    outln!("[确定性调度 seed = {}]", seed);
+
+    Scheduler::run(seed, |ctx| {
+        let (tx, rx) = mpsc::channel();
+
+        let tx1 = tx.clone();
+        ctx.spawn(move |ctx| {
+            for val in ["hi", "from", "the", "thread"] {
+                tx1.send(String::from(val)).unwrap();
+                ctx.sleep(Duration::from_millis(100));
+            }
+        });
+
+        ctx.spawn(move |ctx| {
+            for val in ["more", "messages", "for", "you"] {
+                tx.send(String::from(val)).unwrap();
+                ctx.sleep(Duration::from_millis(100));
+            }
+        });
+
+        // 相当于 `for received in rx`：两个发送端都释放后结束
+        while let Ok(received) = ctx.recv(&rx) {
+            outln!("Got: {}", received);
+        }
+    });
+}
+
 /// 共享状态 - Mutex
 pub fn mutex_basics() {
-    println!("\n=== 6. Mutex 互斥锁基础 ===");
+    outln!("\n=== 6. Mutex 互斥锁基础 ===");
 
     let m = Mutex::new(5);
 
@@ -138,12 +211,12 @@ pub fn mutex_basics() {
         *num = 6;
     } // 锁在这里自动释放
 
-    println!("m = {:?}", m);
+    outln!("m = {:?}", m);
 }
 
 /// 多线程共享 Mutex - Arc
 pub fn arc_mutex() {
-    println!("\n=== 7. Arc<Mutex<T>> 多线程共享 ===");
+    outln!("\n=== 7. Arc<Mutex<T>> 多线程共享 ===");
 
     let counter = Arc::new(Mutex::new(0));
     let mut handles = vec![];
@@ -161,123 +234,201 @@ pub fn arc_mutex() {
         handle.join().unwrap();
     }
 
-    println!("Result: {}", *counter.lock().unwrap());
+    outln!("Result: {}", *counter.lock().unwrap());
 }
 
 /// RefCell 与 Mutex 的相似性
 pub fn refcell_vs_mutex() {
-    println!("\n=== 8. RefCell vs Mutex ===");
+    outln!("\n=== 8. RefCell vs Mutex ===");
 
-    println!("RefCell<T>:");
-    println!("  - 单线程");
-    println!("  - 运行时借用检查");
-    println!("  - Panic 如果违反借用规则");
+    outln!("RefCell<T>:");
+    outln!("  - 单线程");
+    outln!("  - 运行时借用检查");
+    outln!("  - Panic 如果违反借用规则");
 
-    println!("\nMutex<T>:");
-    println!("  - 多线程");
-    println!("  - 运行时获取锁");
-    println!("  - 可能死锁");
+    outln!("\nMutex<T>:");
+    outln!("  - 多线程");
+    outln!("  - 运行时获取锁");
+    outln!("  - 可能死锁");
 }
 
 /// 死锁示例（注释掉以防真的死锁）
 pub fn deadlock_example() {
-    println!("\n=== 9. 死锁警告 ===");
+    outln!("\n=== 9. 死锁警告 ===");
 
-    println!("⚠️  常见死锁场景：");
-    println!("1. 多个锁的获取顺序不一致");
-    println!("2. 持有锁时等待另一个锁");
-    println!("3. 忘记释放锁");
+    outln!("⚠️  常见死锁场景：");
+    outln!("1. 多个锁的获取顺序不一致");
+    outln!("2. 持有锁时等待另一个锁");
+    outln!("3. 忘记释放锁");
 
-    // 演示代码（不会真的死锁）
-    let lock1 = Arc::new(Mutex::new(0));
-    let lock2 = Arc::new(Mutex::new(0));
+    // 用带检测的锁把"危险版本"真的跑一遍：检测器报告问题，而不是让程序卡死
+    outln!("\n第一次：两个线程先后运行，加锁顺序相反");
+    let detector = LockDetector::new();
+    let lock1 = Arc::new(TrackedMutex::new(&detector, "lock1", 0));
+    let lock2 = Arc::new(TrackedMutex::new(&detector, "lock2", 0));
 
-    let l1 = Arc::clone(&lock1);
-    let l2 = Arc::clone(&lock2);
+    let (l1, l2) = (Arc::clone(&lock1), Arc::clone(&lock2));
+    spawn_named("线程 A", move || {
+        let _g1 = l1.lock().unwrap();
+        let _g2 = l2.lock().unwrap();
+        outln!("线程 A: lock1 → lock2");
+    })
+    .join()
+    .unwrap();
 
-    let handle1 = thread::spawn(move || {
+    let (l1, l2) = (Arc::clone(&lock1), Arc::clone(&lock2));
+    spawn_named("线程 B", move || {
+        let _g2 = l2.lock().unwrap();
         let _g1 = l1.lock().unwrap();
-        thread::sleep(Duration::from_millis(10));
-        // 如果取消注释下面这行会导致死锁
-        // let _g2 = l2.lock().unwrap();
-    });
+        outln!("线程 B: lock2 → lock1");
+    })
+    .join()
+    .unwrap();
+
+    outln!("这次没有卡住，但检测器已经发现了隐患:");
+    for report in detector.reports() {
+        outln!("  {}", report);
+    }
 
-    let l1 = Arc::clone(&lock1);
-    let l2 = Arc::clone(&lock2);
+    outln!("\n第二次：两个线程同时运行，各拿一把锁再去拿另一把");
+    let detector = LockDetector::new();
+    let lock1 = Arc::new(TrackedMutex::new(&detector, "lock1", 0));
+    let lock2 = Arc::new(TrackedMutex::new(&detector, "lock2", 0));
+    let barrier = Arc::new(Barrier::new(2));
 
-    let handle2 = thread::spawn(move || {
-        let _g2 = l2.lock().unwrap();
-        thread::sleep(Duration::from_millis(10));
-        // 如果取消注释下面这行会导致死锁
-        // let _g1 = l1.lock().unwrap();
+    let (l1, l2, b) = (Arc::clone(&lock1), Arc::clone(&lock2), Arc::clone(&barrier));
+    let handle1 = spawn_named("线程 A", move || {
+        let _g1 = l1.lock().unwrap();
+        b.wait();
+        let _g2 = l2.lock().unwrap(); // 等到线程 B 放弃并释放 lock2
+        outln!("线程 A: 拿到了 lock2，顺利完成");
+    });
+
+    let (l1, l2, d) = (
+        Arc::clone(&lock1),
+        Arc::clone(&lock2),
+        Arc::clone(&detector),
+    );
+    let handle2 = spawn_named("线程 B", move || {
+        let g2 = l2.lock().unwrap();
+        barrier.wait();
+        // 等线程 A 确实开始等待 lock2，让输出每次都一样
+        while d.waiting_threads() == 0 {
+            thread::yield_now();
+        }
+        match l1.lock() {
+            Ok(_) => outln!("线程 B: 拿到了 lock1"),
+            Err(_) => outln!("线程 B: lock1 返回错误，放弃并释放 lock2"),
+        }
+        drop(g2);
     });
 
-    handle1.join().unwrap();
     handle2.join().unwrap();
+    handle1.join().unwrap();
 
-    println!("✅ 避免了死锁");
+    outln!("检测器的报告:");
+    for report in detector.reports() {
+        outln!("  {}", report);
+    }
+
+    outln!("✅ 解决办法：所有线程按同样的顺序加锁（先 lock1 再 lock2）");
+}
+
+/// 创建带名字的线程，检测器的报告里会显示线程名
+fn spawn_named<F>(name: &str, f: F) -> thread::JoinHandle<()>
+where
+    F: FnOnce() + Send + 'static,
+{
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(f)
+        .unwrap()
 }
 
 /// Send 和 Sync trait
 pub fn send_sync_traits() {
-    println!("\n=== 10. Send 和 Sync Trait ===");
+    outln!("\n=== 10. Send 和 Sync Trait ===");
 
-    println!("\n📤 Send Trait:");
-    println!("  - 允许在线程间转移所有权");
-    println!("  - 大多数类型都实现了 Send");
-    println!("  - Rc<T> 没有实现 Send");
+    outln!("\n📤 Send Trait:");
+    outln!("  - 允许在线程间转移所有权");
+    outln!("  - 大多数类型都实现了 Send");
+    outln!("  - Rc<T> 没有实现 Send");
 
-    println!("\n🔄 Sync Trait:");
-    println!("  - 允许多线程同时访问");
-    println!("  - &T 是 Sync 的，如果 T 是 Sync");
-    println!("  - RefCell<T> 和 Cell<T> 不是 Sync");
-    println!("  - Mutex<T> 是 Sync");
+    outln!("\n🔄 Sync Trait:");
+    outln!("  - 允许多线程同时访问");
+    outln!("  - &T 是 Sync 的，如果 T 是 Sync");
+    outln!("  - RefCell<T> 和 Cell<T> 不是 Sync");
+    outln!("  - Mutex<T> 是 Sync");
 
-    println!("\n完全由 Send 和 Sync 类型组成的类型也自动实现 Send 和 Sync");
+    outln!("\n完全由 Send 和 Sync 类型组成的类型也自动实现 Send 和 Sync");
 }
 
 /// 实用示例：并行计算
 pub fn parallel_computation() {
-    println!("\n=== 11. 实用示例：并行计算 ===");
-
-    let data = Arc::new(vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
-    let result = Arc::new(Mutex::new(0));
-    let mut handles = vec![];
-
-    let chunk_size = 2;
-    for i in 0..(data.len() / chunk_size) {
-        let data = Arc::clone(&data);
-        let result = Arc::clone(&result);
-
-        let handle = thread::spawn(move || {
-            let start = i * chunk_size;
-            let end = start + chunk_size;
-            let sum: i32 = data[start..end].iter().sum();
-
-            let mut total = result.lock().unwrap();
-            *total += sum;
-        });
-
-        handles.push(handle);
-    }
-
-    for handle in handles {
-        handle.join().unwrap();
-    }
+    outln!("\n=== 11. 实用示例：并行计算 ===");
+
+    // 11 个元素：4 个线程分不均匀，最后一块只有 2 个元素
+    let data = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+    // 作用域线程保证在 scope 结束前全部 join，所以可以直接借用 data，
+    // 不需要 Arc；每个线程只返回自己那块的和，也就不需要 Mutex
+    let chunk_size = data.len().div_ceil(4);
+    let total: i32 = thread::scope(|s| {
+        let handles: Vec<_> = data
+            .chunks(chunk_size) // 最后一块可能更短，但不会丢元素
+            .map(|chunk| s.spawn(move || chunk.iter().sum::<i32>()))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    });
+    outln!("总和: {}", total);
+
+    // 同样的分块逻辑封装在 parallel 模块里
+    let par = Par::new(4);
+    outln!(
+        "{} 个元素分给 {} 个线程，每块 {} 个",
+        data.len(),
+        par.threads(),
+        par.chunk_size(data.len())
+    );
+    outln!(
+        "par.reduce 求和: {}",
+        par.reduce(&data, 0, |&x| x, |a, b| a + b)
+    );
+    outln!("par.map 求平方: {:?}", par.map(&data, |&x| x * x));
+
+    let evens = AtomicUsize::new(0);
+    par.for_each(&data, |&x| {
+        if x % 2 == 0 {
+            evens.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    outln!("par.for_each 统计偶数: {} 个", evens.into_inner());
 
-    println!("总和: {}", *result.lock().unwrap());
+    // 不想自己定线程数时用 par_* 函数，按可用并行度分块
+    let odds = AtomicUsize::new(0);
+    parallel::par_for_each(&data, |&x| {
+        if x % 2 == 1 {
+            odds.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+    outln!("par_for_each 统计奇数: {} 个", odds.into_inner());
+    outln!("par_average: {:.2}", parallel::par_average(&data));
 }
 
 /// 实用示例：生产者-消费者模式
 pub fn producer_consumer() {
-    println!("\n=== 12. 实用示例：生产者-消费者 ===");
+    outln!("\n=== 12. 实用示例：生产者-消费者 ===");
+
+    if let Some(seed) = scheduler::seed() {
+        return producer_consumer_deterministic(seed);
+    }
 
     let (tx, rx) = mpsc::channel();
 
     // 生产者
     let producer = thread::spawn(move || {
         for i in 0..10 {
-            println!("生产者: 生产 {}", i);
+            outln!("生产者: 生产 {}", i);
             tx.send(i).unwrap();
             thread::sleep(Duration::from_millis(100));
         }
@@ -286,7 +437,7 @@ pub fn producer_consumer() {
     // 消费者
     let consumer = thread::spawn(move || {
         for received in rx {
-            println!("消费者: 消费 {}", received);
+            outln!("消费者: 消费 {}", received);
             thread::sleep(Duration::from_millis(200));
         }
     });
@@ -295,49 +446,99 @@ pub fn producer_consumer() {
     consumer.join().unwrap();
 }
 
-/// 实用示例：线程池概念
-pub fn thread_pool_concept() {
-    println!("\n=== 13. 线程池概念 ===");
+/// `producer_consumer` 的确定性版本：消费者比生产者慢，可以看到消息逐渐积压
+fn producer_consumer_deterministic(seed: u64) {
+    outln!("[确定性调度 seed = {}]", seed);
 
-    println!("线程池的优势：");
-    println!("  ✓ 避免频繁创建/销毁线程的开销");
-    println!("  ✓ 限制并发线程数量");
-    println!("  ✓ 任务队列管理");
+    Scheduler::run(seed, |ctx| {
+        let (tx, rx) = mpsc::channel();
 
-    println!("\n简单的工作线程示例：");
+        let producer = ctx.spawn(move |ctx| {
+            for i in 0..10 {
+                outln!("生产者: 生产 {}", i);
+                tx.send(i).unwrap();
+                ctx.sleep(Duration::from_millis(100));
+            }
+        });
 
-    let (tx, rx) = mpsc::channel();
-    let rx = Arc::new(Mutex::new(rx));
-
-    // 创建 4 个工作线程
-    for id in 0..4 {
-        let rx = Arc::clone(&rx);
-        thread::spawn(move || {
-            loop {
-                let job = rx.lock().unwrap().recv();
-                match job {
-                    Ok(job) => {
-                        println!("Worker {} got job: {}", id, job);
-                        thread::sleep(Duration::from_millis(100));
-                    }
-                    Err(_) => break,
-                }
+        let consumer = ctx.spawn(move |ctx| {
+            while let Ok(received) = ctx.recv(&rx) {
+                outln!("消费者: 消费 {}", received);
+                ctx.sleep(Duration::from_millis(200));
             }
         });
-    }
 
-    // 发送任务
+        ctx.join(producer).unwrap();
+        ctx.join(consumer).unwrap();
+    });
+}
+
+/// 实用示例：线程池
+pub fn thread_pool_concept() {
+    outln!("\n=== 13. 线程池 ===");
+
+    outln!("线程池的优势：");
+    outln!("  ✓ 避免频繁创建/销毁线程的开销");
+    outln!("  ✓ 限制并发线程数量");
+    outln!("  ✓ 任务队列管理");
+
+    outln!("\n使用 ThreadPool（实现见 thread_pool.rs）：");
+
+    // 4 个 worker 共享一个任务队列
+    let pool = ThreadPool::new(4);
     for i in 0..10 {
-        tx.send(i).unwrap();
+        pool.execute(move || {
+            let id = thread_pool::current_worker().unwrap();
+            outln!("Worker {} got job: {}", id, i);
+            thread::sleep(Duration::from_millis(100));
+        });
     }
 
-    thread::sleep(Duration::from_secs(2));
-    println!("所有任务完成");
+    // panic 的任务只会被记录下来（panic 信息打印在 stderr），worker 继续工作
+    pool.execute(|| panic!("任务 10 执行失败"));
+
+    // shutdown（或者 drop）会关闭队列并 join 所有 worker，不再需要 sleep 猜时间
+    let stats = pool.shutdown();
+    outln!("所有任务完成: 成功 {} 个, panic {} 个", stats.completed, stats.panicked);
+
+    outln!("\n有界队列（背压）：");
+    let pool = ThreadPool::bounded(2, 2);
+    for i in 0..6 {
+        // 两个 worker 都在忙、队列里也有 2 个任务时，execute 会阻塞等待空位
+        pool.execute(|| thread::sleep(Duration::from_millis(50)));
+        outln!("提交任务 {}", i);
+    }
+    let stats = pool.shutdown();
+    outln!("有界线程池完成 {} 个任务", stats.completed);
+
+    outln!("\ntry_execute：队列满了立即返回错误，而不是阻塞");
+    let pool = ThreadPool::bounded(1, 1);
+    let (started_tx, started_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+    pool.execute(move || {
+        started_tx.send(()).unwrap();
+        let _ = release_rx.recv();
+    });
+    started_rx.recv().unwrap(); // 唯一的 worker 已经在忙
+    for i in 0..3 {
+        match pool.try_execute(|| {}) {
+            Ok(()) => outln!("任务 {}: 进入队列", i),
+            Err(e) => outln!("任务 {}: {}", i, e),
+        }
+    }
+    drop(release_tx);
+    let size = pool.size();
+    let stats = pool.shutdown();
+    outln!("{} 个 worker 完成 {} 个任务", size, stats.completed);
 }
 
 /// Barrier - 同步屏障
 pub fn barrier_example() {
-    println!("\n=== 14. Barrier 同步屏障 ===");
+    outln!("\n=== 14. Barrier 同步屏障 ===");
+
+    if let Some(seed) = scheduler::seed() {
+        return barrier_example_deterministic(seed);
+    }
 
     use std::sync::Barrier;
 
@@ -347,11 +548,11 @@ pub fn barrier_example() {
     for i in 0..5 {
         let c = Arc::clone(&barrier);
         handles.push(thread::spawn(move || {
-            println!("线程 {} 开始工作", i);
+            outln!("线程 {} 开始工作", i);
             thread::sleep(Duration::from_millis(100 * i as u64));
-            println!("线程 {} 到达屏障", i);
+            outln!("线程 {} 到达屏障", i);
             c.wait();
-            println!("线程 {} 继续执行", i);
+            outln!("线程 {} 继续执行", i);
         }));
     }
 
@@ -360,63 +561,675 @@ pub fn barrier_example() {
     }
 }
 
+/// `barrier_example` 的确定性版本
+///
+/// `std::sync::Barrier::wait` 会真正阻塞线程，这里用"到达计数 + 等待计数到齐"
+/// 表达同样的语义，这也正是屏障的实现原理。
+fn barrier_example_deterministic(seed: u64) {
+    outln!("[确定性调度 seed = {}]", seed);
+
+    Scheduler::run(seed, |ctx| {
+        let mut handles = vec![];
+        let arrived = Arc::new(Mutex::new(0));
+
+        for i in 0..5 {
+            let arrived = Arc::clone(&arrived);
+            handles.push(ctx.spawn(move |ctx| {
+                outln!("线程 {} 开始工作", i);
+                ctx.sleep(Duration::from_millis(100 * i as u64));
+                outln!("线程 {} 到达屏障", i);
+                *arrived.lock().unwrap() += 1;
+                ctx.wait_until(|| *arrived.lock().unwrap() == 5);
+                outln!("线程 {} 继续执行", i);
+            }));
+        }
+
+        for handle in handles {
+            ctx.join(handle).unwrap();
+        }
+    });
+}
+
 /// 并发最佳实践
 pub fn concurrency_best_practices() {
-    println!("\n=== 15. 并发编程最佳实践 ===");
+    outln!("\n=== 15. 并发编程最佳实践 ===");
+
+    outln!("\n✅ 推荐做法:");
+    outln!("1. 优先使用消息传递而非共享内存");
+    outln!("2. 使用 Arc<Mutex<T>> 共享可变状态");
+    outln!("3. 保持临界区尽可能小");
+    outln!("4. 避免嵌套锁以防死锁");
+    outln!("5. 使用 RAII 确保锁被释放");
+
+    outln!("\n❌ 避免:");
+    outln!("1. 在持有锁时执行耗时操作");
+    outln!("2. 使用 Rc<T> 跨线程（用 Arc<T>）");
+    outln!("3. 忘记 join 重要的线程");
+    outln!("4. 过度使用共享状态");
+
+    outln!("\n📦 工具选择:");
+    outln!("  - 所有权转移: 直接 move");
+    outln!("  - 消息传递: mpsc::channel");
+    outln!("  - 共享只读: Arc<T>");
+    outln!("  - 共享可变: Arc<Mutex<T>>");
+    outln!("  - 原子操作: std::sync::atomic（见 atomic_counter）");
+    outln!("  - 等待条件: Condvar（见 condvar_basics）");
+}
+
+/// 并行 map/reduce 与顺序版本的性能对比
+pub fn parallel_benchmark() {
+    outln!("\n=== 16. 并行 vs 顺序：性能对比 ===");
+
+    let par = Par::auto();
+    outln!("可用并行度: {}", par.threads());
+
+    // 数值保持很小，顺序版 `math_utils::average` 的 i32 求和不会溢出
+    let numbers: Vec<i32> = (0..2_000_000).map(|i| i % 100).collect();
+    let len = numbers.len() as u64;
+    outln!("\n求平均值（{} 个元素，每个元素的计算量很小）:", len);
+    bench::report(&[
+        bench::measure("顺序 math_utils::average", 10, len, || {
+            math_utils::average(&numbers)
+        }),
+        bench::measure("并行 par_average", 10, len, || {
+            parallel::par_average(&numbers)
+        }),
+    ]);
+
+    // 每个元素的计算量大时，线程创建的开销就显得微不足道了
+    let candidates: Vec<u64> = (0..50_000).collect();
+    let len = candidates.len() as u64;
+    outln!("\n判断素数（{} 个元素，每个元素的计算量较大）:", len);
+    bench::report(&[
+        bench::measure("顺序 iter().map()", 3, len, || {
+            candidates.iter().map(|&n| is_prime(n)).collect::<Vec<_>>()
+        }),
+        bench::measure("并行 par_map", 3, len, || {
+            parallel::par_map(&candidates, |&n| is_prime(n))
+        }),
+    ]);
+
+    outln!("\n💡 只有每个元素的工作量足够大时，并行才划算；");
+    outln!("   用 `cargo run --release -- run concurrency::parallel_benchmark` 看优化后的数字");
+}
+
+/// 试除法判断素数，故意写得朴素一点来制造计算量
+fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0)
+}
+
+/// 原子类型：不加锁的计数器
+pub fn atomic_counter() {
+    outln!("\n=== 17. 原子类型与无锁计数器 ===");
+
+    // 和 arc_mutex 一样是 10 个线程各加一次，但不需要 Mutex
+    let counter = Arc::new(AtomicUsize::new(0));
+    let mut handles = vec![];
 
-    println!("\n✅ 推荐做法:");
-    println!("1. 优先使用消息传递而非共享内存");
-    println!("2. 使用 Arc<Mutex<T>> 共享可变状态");
-    println!("3. 保持临界区尽可能小");
-    println!("4. 避免嵌套锁以防死锁");
-    println!("5. 使用 RAII 确保锁被释放");
+    for _ in 0..10 {
+        let counter = Arc::clone(&counter);
+        let handle = thread::spawn(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        handles.push(handle);
+    }
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    outln!("AtomicUsize Result: {}", counter.load(Ordering::SeqCst));
+
+    // 同一个计数器的其他实现，结果都一样，区别在于性能
+    outln!("\n同样的计数器，不同的同步方式（10 个线程 × 1000 次）:");
+    for counter in counters::all_counters(10) {
+        counters::hammer(counter.as_ref(), 10, 1000);
+        outln!("  {:<24} → {}", counter.name(), counter.get());
+    }
 
-    println!("\n❌ 避免:");
-    println!("1. 在持有锁时执行耗时操作");
-    println!("2. 使用 Rc<T> 跨线程（用 Arc<T>）");
-    println!("3. 忘记 join 重要的线程");
-    println!("4. 过度使用共享状态");
+    outln!("\n内存顺序（Ordering）:");
+    outln!("  Relaxed - 只保证这个变量本身的操作是原子的，计数器用它就够了");
+    outln!("  Release / Acquire - 成对使用：Release 之前的写，对 Acquire 读到它的线程可见");
+    outln!("  AcqRel - 读-改-写操作同时具有 Acquire 和 Release");
+    outln!("  SeqCst - 所有线程看到同一个全局顺序，最严格，也是最安全的默认选择");
 
-    println!("\n📦 工具选择:");
-    println!("  - 所有权转移: 直接 move");
-    println!("  - 消息传递: mpsc::channel");
-    println!("  - 共享只读: Arc<T>");
-    println!("  - 共享可变: Arc<Mutex<T>>");
-    println!("  - 原子操作: std::sync::atomic");
+    // Release / Acquire 用来"发布"数据：先写数据，再用 Release 置标志
+    let data = Arc::new(AtomicUsize::new(0));
+    let ready = Arc::new(AtomicBool::new(false));
+
+    let (d, r) = (Arc::clone(&data), Arc::clone(&ready));
+    let producer = thread::spawn(move || {
+        d.store(42, Ordering::Relaxed);
+        r.store(true, Ordering::Release); // 之前的写入随标志一起"发布"
+    });
+
+    // Acquire 读到 true 之后，一定能看到 producer 在 Release 之前写的 42
+    while !ready.load(Ordering::Acquire) {
+        std::hint::spin_loop();
+    }
+    outln!("\n用 Release/Acquire 发布的数据: {}", data.load(Ordering::Relaxed));
+    producer.join().unwrap();
 }
 
-/// 运行所有示例
-pub fn run_all_concurrency_examples() {
-    println!("\n");
-    println!("╔════════════════════════════════════════╗");
-    println!("║   Rust 并发编程 - 完整示例            ║");
-    println!("╚════════════════════════════════════════╝");
+/// 各种计数器的吞吐量对比
+pub fn counter_benchmark() {
+    outln!("\n=== 18. 计数器性能对比：Mutex vs 原子操作 ===");
+
+    // 和第 7 节 Arc<Mutex<T>> 的计数器一样用 10 个线程
+    let threads = 10;
+    let per_thread = 20_000;
+    outln!("{} 个线程，每个线程加 {} 次:", threads, per_thread);
+    bench::report(&counters::benchmark(threads, per_thread, 5));
+
+    outln!("\n💡 竞争越激烈，Mutex 的等待开销越明显；");
+    outln!("   分片计数器让每个线程写自己的缓存行，几乎没有竞争");
+}
+
+/// 有界通道：缓冲区满了发送端就要等（背压）
+pub fn bounded_channel() {
+    outln!("\n=== 19. 有界通道与背压 ===");
+
+    // 容量为 2：最多缓存 2 条消息，再发就要等接收端取走
+    let (tx, rx) = mpsc::sync_channel(2);
+
+    for i in 1..=3 {
+        match tx.try_send(i) {
+            Ok(()) => outln!("try_send({}) 成功", i),
+            Err(TrySendError::Full(v)) => outln!("try_send({}) 失败: 缓冲区已满", v),
+            Err(TrySendError::Disconnected(v)) => outln!("try_send({}) 失败: 接收端已断开", v),
+        }
+    }
+
+    // send 会阻塞到有空位为止：消费者取走一条后，生产者才能继续
+    let (done_tx, done_rx) = mpsc::channel();
+    let producer = thread::spawn(move || {
+        tx.send(3).unwrap();
+        done_tx.send(()).unwrap();
+    });
+
+    outln!("生产者阻塞中? {}", done_rx.recv_timeout(Duration::from_millis(50)).is_err());
+    outln!("消费者取走: {}", rx.recv().unwrap());
+    done_rx.recv().unwrap();
+    outln!("生产者的 send(3) 返回了");
+    producer.join().unwrap();
+
+    outln!("剩余消息: {:?}", rx.iter().collect::<Vec<_>>());
+
+    // 容量为 0 是"会合通道"：send 要等到接收端真正取走才返回
+    outln!("💡 sync_channel(0) 没有缓冲，每次 send 都要等 recv 接手");
+}
+
+/// 不阻塞的接收：try_recv 和 recv_timeout
+pub fn channel_timeouts() {
+    outln!("\n=== 20. try_recv 与 recv_timeout ===");
+
+    let (tx, rx) = mpsc::channel();
+
+    // try_recv 立即返回，适合在循环里顺便检查一下有没有消息
+    match rx.try_recv() {
+        Ok(v) => outln!("收到: {}", v),
+        Err(TryRecvError::Empty) => outln!("try_recv: 暂时没有消息"),
+        Err(TryRecvError::Disconnected) => outln!("try_recv: 发送端已断开"),
+    }
+
+    // recv_timeout 最多等一段时间
+    match rx.recv_timeout(Duration::from_millis(20)) {
+        Ok(v) => outln!("收到: {}", v),
+        Err(RecvTimeoutError::Timeout) => outln!("recv_timeout: 20ms 内没有消息"),
+        Err(RecvTimeoutError::Disconnected) => outln!("recv_timeout: 发送端已断开"),
+    }
+
+    let worker = thread::spawn(move || {
+        for i in 1..=3 {
+            thread::sleep(Duration::from_millis(10));
+            tx.send(i).unwrap();
+        }
+        // tx 在这里 drop，接收端会看到 Disconnected
+    });
+
+    // 轮询：等消息的同时还可以做别的事
+    let mut received = Vec::new();
+    loop {
+        match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(v) => received.push(v),
+            Err(RecvTimeoutError::Timeout) => outln!("还在等……"),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    worker.join().unwrap();
+    outln!("收到 {:?} 后发送端断开，退出循环", received);
+}
+
+/// 同时等待多个通道
+pub fn channel_select() {
+    outln!("\n=== 21. 手写 select：同时等待多个通道 ===");
+
+    let (orders_tx, orders) = mpsc::channel();
+    let (alerts_tx, alerts) = mpsc::channel();
+
+    for i in 1..=3 {
+        orders_tx.send(format!("订单 #{}", i)).unwrap();
+    }
+    alerts_tx.send("库存不足".to_string()).unwrap();
+
+    let mut select = Select::new();
+    let order_index = select.add(&orders);
+    let alert_index = select.add(&alerts);
+
+    // 两个通道轮流检查，哪个有消息就处理哪个
+    for _ in 0..4 {
+        match select.recv_timeout(Duration::from_millis(100)) {
+            Ok((i, msg)) if i == order_index => outln!("[orders] {}", msg),
+            Ok((_, msg)) => outln!("[alerts] {}", msg),
+            Err(e) => outln!("select: {}", e),
+        }
+    }
+
+    outln!(
+        "两个通道都空了: {}",
+        select.recv_timeout(Duration::from_millis(10)).unwrap_err()
+    );
+
+    drop(orders_tx);
+    drop(alerts_tx);
+    outln!("发送端全部 drop 之后: {}", select.recv().unwrap_err());
+}
+
+/// 广播 / 发布-订阅
+pub fn broadcast_channel() {
+    outln!("\n=== 22. 广播通道（发布-订阅） ===");
+
+    let news = Broadcast::new();
+    let alice = news.subscribe();
+    let bob = news.subscribe();
+
+    outln!("发送给 {} 个订阅者", news.send("Rust 1.0 发布"));
 
-    basic_threads();
-    thread::sleep(Duration::from_millis(100));
+    // bob 退订：只要 drop 接收端，下次发送时会被自动移除
+    drop(bob);
+    outln!("bob 退订后、下次发送前的订阅者: {} 个", news.subscriber_count());
+    outln!("bob 退订后发送给 {} 个订阅者", news.send("新版本发布"));
 
-    move_closures();
-    message_passing();
-    multiple_messages();
+    let carol = news.subscribe();
+    news.send("carol 只能收到订阅之后的消息");
 
-    println!("\n[跳过多个生产者示例以节省时间]");
-    // multiple_producers(); // 会花较长时间
+    // 发布者 drop 后，订阅者收完剩余消息，迭代自然结束
+    drop(news);
+    let alice_thread = thread::spawn(move || alice.iter().collect::<Vec<_>>());
+    outln!("alice 收到: {:?}", alice_thread.join().unwrap());
+    outln!("carol 收到: {:?}", carol.iter().collect::<Vec<_>>());
+}
 
-    mutex_basics();
-    arc_mutex();
-    refcell_vs_mutex();
-    deadlock_example();
-    send_sync_traits();
-    parallel_computation();
+/// 工作窃取：每个 worker 一个队列，空闲时去偷
+pub fn work_stealing() {
+    outln!("\n=== 23. 工作窃取调度 ===");
+
+    // 递归任务：每个任务又会提交两个子任务
+    let (fib, stats) = work_stealing::parallel_fib(Mode::WorkStealing, 4, 25);
+    outln!("并行 fib(25) = {}", fib);
+    print_worker_stats(&stats);
+
+    let mut rng = Rng::new(42);
+    let mut data: Vec<u32> = (0..100_000).map(|_| rng.below(1_000_000) as u32).collect();
+    let stats = work_stealing::parallel_quicksort(Mode::WorkStealing, 4, &mut data);
+    let sorted = data.windows(2).all(|w| w[0] <= w[1]);
+    outln!("\n并行快速排序 {} 个数，结果有序: {}", data.len(), sorted);
+    print_worker_stats(&stats);
+
+    outln!("\n💡 根任务只在 worker 0 的队列里，其他 worker 一开始只能去偷；");
+    outln!("   偷的是队头（最早提交、规模最大）的任务，拆出的子任务进了自己的队列，");
+    outln!("   所以偷的次数远少于执行的任务数");
+}
 
-    println!("\n[跳过生产者-消费者示例以节省时间]");
-    // producer_consumer(); // 会花较长时间
+fn print_worker_stats(stats: &[WorkerStats]) {
+    for (id, s) in stats.iter().enumerate() {
+        outln!(
+            "  worker {}: 执行 {:>5} 个任务，其中偷来 {:>4} 个，锁竞争 {:>4} 次",
+            id,
+            s.executed,
+            s.stolen,
+            s.contended
+        );
+    }
+}
 
-    println!("\n[跳过线程池示例以节省时间]");
-    // thread_pool_concept(); // 会花较长时间
+/// 工作窃取 vs 共享队列
+pub fn work_stealing_benchmark() {
+    outln!("\n=== 24. 工作窃取 vs 共享队列：性能对比 ===");
+
+    let workers = Par::auto().threads().max(4);
+    let n = 30;
+    outln!("{} 个 worker，递归计算 fib({}):", workers, n);
+    let jobs = work_stealing::parallel_fib(Mode::WorkStealing, 1, n).1[0].executed as u64;
+    bench::report(&[
+        bench::measure("共享队列 (ThreadPool 的做法)", 3, jobs, || {
+            work_stealing::parallel_fib(Mode::SharedQueue, workers, n)
+        }),
+        bench::measure("工作窃取", 3, jobs, || {
+            work_stealing::parallel_fib(Mode::WorkStealing, workers, n)
+        }),
+    ]);
+
+    let contended = |mode| {
+        let (_, stats) = work_stealing::parallel_fib(mode, workers, n);
+        stats.iter().map(|s| s.contended).sum::<usize>()
+    };
+    outln!("\n锁竞争次数（一次运行）:");
+    outln!("  共享队列: {}", contended(Mode::SharedQueue));
+    outln!("  工作窃取: {}", contended(Mode::WorkStealing));
+
+    outln!("\n💡 共享队列每次取任务、提交任务都抢同一把锁；");
+    outln!("   工作窃取只在偷的时候才碰别人的锁");
+}
+
+/// Condvar 条件变量：等待某个条件成立
+pub fn condvar_basics() {
+    outln!("\n=== 25. Condvar 条件变量 ===");
+
+    // Mutex 保护"条件"本身，Condvar 负责"条件变了叫醒我"
+    let pair = Arc::new((Mutex::new(None), Condvar::new()));
+
+    let worker_pair = Arc::clone(&pair);
+    let worker = thread::spawn(move || {
+        let (data, ready) = &*worker_pair;
+        let mut data = data.lock().unwrap();
+        // wait 会原子地释放锁并睡眠，醒来时重新拿到锁
+        // 必须用 while：wait 可能在没人 notify 时返回（虚假唤醒）
+        while data.is_none() {
+            data = ready.wait(data).unwrap();
+        }
+        outln!("worker: 收到数据 {:?}", data.take().unwrap());
+    });
+
+    outln!("主线程: 准备数据");
+    thread::sleep(Duration::from_millis(20));
+    {
+        let (data, ready) = &*pair;
+        *data.lock().unwrap() = Some(vec![1, 2, 3]);
+        ready.notify_one();
+    }
+    worker.join().unwrap();
+
+    // wait_while 把 while 循环封装好了：闭包返回 true 时继续等
+    let (data, ready) = &*pair;
+    let producer_pair = Arc::clone(&pair);
+    let producer = thread::spawn(move || {
+        let (data, ready) = &*producer_pair;
+        *data.lock().unwrap() = Some(vec![4, 5]);
+        ready.notify_all();
+    });
+    let mut guard = ready.wait_while(data.lock().unwrap(), |data| data.is_none()).unwrap();
+    outln!("主线程: wait_while 等到数据 {:?}", guard.take().unwrap());
+    drop(guard);
+    producer.join().unwrap();
+
+    outln!("\n💡 为什么一定要在循环里 wait:");
+    outln!("   1. 虚假唤醒：操作系统允许 wait 在没有 notify 的情况下返回");
+    outln!("   2. 被唤醒到重新拿到锁之间，别的线程可能已经把条件改回去了");
+    outln!("   3. notify_all 叫醒所有人，但可能只有一个能满足条件");
+    outln!("   写成 if 的话，这三种情况都会在条件不成立时往下执行");
+    outln!("💡 先改状态再 notify，而且改状态要在锁里；否则通知可能在对方 wait 之前发出而丢失");
+}
+
+/// 用 Mutex + Condvar 搭出常用的同步原语
+pub fn condvar_primitives() {
+    outln!("\n=== 26. 用 Condvar 实现同步原语 ===");
+
+    // 信号量：最多 2 个线程同时进入
+    outln!("\n--- Semaphore：限制并发数 ---");
+    let semaphore = Semaphore::new(2);
+    let running = AtomicUsize::new(0);
+    let max_running = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..6 {
+            s.spawn(|| {
+                let _permit = semaphore.acquire();
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(10));
+                running.fetch_sub(1, Ordering::SeqCst);
+                // _permit 在这里 drop，归还许可并唤醒一个等待者
+            });
+        }
+    });
+    outln!("6 个线程抢 2 个许可，同时运行的线程数不超过 2: {}", max_running.load(Ordering::SeqCst) <= 2);
+    outln!("全部结束后剩余许可: {}", semaphore.available());
+    let held = [semaphore.try_acquire(), semaphore.try_acquire()];
+    outln!("已经拿走 {} 个许可时 try_acquire 不阻塞: {:?}", held.len(), semaphore.try_acquire().map(|_| ()));
+
+    // 倒计时门闩：等所有 worker 初始化完
+    outln!("\n--- CountDownLatch：等待一组线程就绪 ---");
+    let latch = CountDownLatch::new(3);
+    thread::scope(|s| {
+        for i in 0..3u64 {
+            let latch = &latch;
+            s.spawn(move || {
+                thread::sleep(Duration::from_millis(10 * i));
+                latch.count_down();
+            });
+        }
+        latch.wait();
+        outln!("3 个 worker 都调用了 count_down，主线程继续（剩余计数 {}）", latch.count());
+    });
+    let never = CountDownLatch::new(1);
+    outln!("没有人 count_down 时 wait_timeout(10ms): {}", never.wait_timeout(Duration::from_millis(10)));
+
+    // 有界阻塞队列：满了生产者等，空了消费者等
+    outln!("\n--- BlockingQueue：容量为 2 的阻塞队列 ---");
+    let queue = BlockingQueue::new(2);
+    thread::scope(|s| {
+        let consumer = s.spawn(|| {
+            let mut sum = 0;
+            while let Some(item) = queue.pop() {
+                sum += item;
+                thread::sleep(Duration::from_millis(1));
+            }
+            sum
+        });
+        for i in 1..=10 {
+            queue.push(i).unwrap();
+        }
+        // close 之后消费者取完剩余元素，pop 返回 None，循环结束
+        queue.close();
+        outln!("生产者放入 1..=10，消费者取出的总和: {}", consumer.join().unwrap());
+    });
+    outln!("关闭后再 push: {:?}", queue.push(11));
+
+    // try_push / try_pop 不等待：满了或空了立即返回
+    let queue = BlockingQueue::new(2);
+    let pushed: Vec<_> = (1..=3).map(|i| queue.try_push(i)).collect();
+    outln!("容量 2 时 try_push 1..=3: {:?}，队列长度 {}", pushed, queue.len());
+    while let Some(item) = queue.try_pop() {
+        outln!("try_pop: {}", item);
+    }
+    outln!("取完之后 is_empty: {}", queue.is_empty());
+
+    // 可重用屏障：每一轮由最后到达的线程报告
+    outln!("\n--- ReusableBarrier：多轮同步 ---");
+    let barrier = ReusableBarrier::new(3);
+    thread::scope(|s| {
+        for _ in 0..3 {
+            s.spawn(|| {
+                for round in 1..=3 {
+                    if barrier.wait() {
+                        outln!("第 {} 轮: 3 个线程全部到达", round);
+                    }
+                }
+            });
+        }
+    });
+
+    outln!("\n💡 std::sync::Barrier（见 barrier_example）就是这样实现的：");
+    outln!("   等待的条件是\"轮次变了\"而不是\"人数到齐\"，");
+    outln!("   否则跑得快的线程进入下一轮后，计数又不满了，还在等的线程会一直睡下去");
+}
+
+/// 登记本模块的示例
+pub fn register(registry: &mut Registry) {
+    let mut topic = registry.topic("concurrency", "并发编程", run_all_concurrency_examples);
+
+    topic
+        .lesson("basic_threads", "创建线程基础", basic_threads)
+        .minutes(5)
+        .tags(&["thread", UNORDERED, SEEDED]);
+    topic
+        .lesson("move_closures", "使用 move 闭包", move_closures)
+        .minutes(5)
+        .tags(&["thread", "move", "closure"])
+        .requires(&["concurrency::basic_threads", "ownership::basic_ownership"]);
+    topic
+        .lesson("message_passing", "消息传递 - Channel", message_passing)
+        .minutes(5)
+        .tags(&["channel"])
+        .requires(&["concurrency::move_closures"]);
+    topic
+        .lesson("multiple_messages", "发送多个值", multiple_messages)
+        .minutes(5)
+        .tags(&["channel"])
+        .requires(&["concurrency::message_passing"]);
+    topic
+        .lesson("multiple_producers", "多个生产者", multiple_producers)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["channel", SLOW, UNORDERED, SEEDED])
+        .requires(&["concurrency::multiple_messages"]);
+    topic
+        .lesson("mutex_basics", "Mutex 互斥锁基础", mutex_basics)
+        .minutes(5)
+        .tags(&["mutex"]);
+    topic
+        .lesson("arc_mutex", "Arc<Mutex<T>> 多线程共享", arc_mutex)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["mutex", "arc"])
+        .requires(&["concurrency::mutex_basics", "smart_pointer::rc_basics"]);
+    topic
+        .lesson("refcell_vs_mutex", "RefCell vs Mutex", refcell_vs_mutex)
+        .level(Difficulty::Intermediate)
+        .minutes(3)
+        .tags(&["mutex", "refcell"])
+        .requires(&["concurrency::mutex_basics", "smart_pointer::refcell_basics"]);
+    topic
+        .lesson("deadlock_example", "死锁警告", deadlock_example)
+        .level(Difficulty::Advanced)
+        .minutes(8)
+        .tags(&["mutex", "deadlock"])
+        .requires(&["concurrency::arc_mutex"]);
+    topic
+        .lesson("send_sync_traits", "Send 和 Sync Trait", send_sync_traits)
+        .level(Difficulty::Advanced)
+        .minutes(5)
+        .tags(&["send", "sync", "trait"])
+        .requires(&["concurrency::arc_mutex"]);
+    topic
+        .lesson("parallel_computation", "并行计算", parallel_computation)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["thread", "scope", "parallel"])
+        .requires(&["concurrency::arc_mutex"]);
+    topic
+        .lesson("producer_consumer", "生产者-消费者", producer_consumer)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["channel", "pattern", SLOW, UNORDERED, SEEDED])
+        .requires(&["concurrency::multiple_messages"]);
+    topic
+        .lesson("thread_pool_concept", "线程池", thread_pool_concept)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["thread", "channel", "mutex", NONDETERMINISTIC])
+        .requires(&["concurrency::producer_consumer", "concurrency::arc_mutex"]);
+    topic
+        .lesson("barrier_example", "Barrier 同步屏障", barrier_example)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["barrier", "sync", UNORDERED, SEEDED])
+        .requires(&["concurrency::arc_mutex"]);
+    topic
+        .lesson("concurrency_best_practices", "并发编程最佳实践", concurrency_best_practices)
+        .minutes(3)
+        .tags(&["guide"]);
+    topic
+        .lesson("parallel_benchmark", "并行 vs 顺序：性能对比", parallel_benchmark)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["parallel", "bench", SLOW, NONDETERMINISTIC])
+        .requires(&["concurrency::parallel_computation", "utils::demo_utils"]);
+    topic
+        .lesson("atomic_counter", "原子类型与无锁计数器", atomic_counter)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["atomic", "rwlock", "ordering"])
+        .requires(&["concurrency::arc_mutex"]);
+    topic
+        .lesson("counter_benchmark", "计数器性能对比", counter_benchmark)
+        .level(Difficulty::Advanced)
+        .minutes(5)
+        .tags(&["atomic", "bench", SLOW, NONDETERMINISTIC])
+        .requires(&["concurrency::atomic_counter"]);
+    topic
+        .lesson("bounded_channel", "有界通道与背压", bounded_channel)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["channel", "backpressure"])
+        .requires(&["concurrency::multiple_messages"]);
+    topic
+        .lesson("channel_timeouts", "try_recv 与 recv_timeout", channel_timeouts)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["channel", "timeout"])
+        .requires(&["concurrency::multiple_messages"]);
+    topic
+        .lesson("channel_select", "手写 select", channel_select)
+        .level(Difficulty::Advanced)
+        .minutes(8)
+        .tags(&["channel", "select"])
+        .requires(&["concurrency::channel_timeouts"]);
+    topic
+        .lesson("broadcast_channel", "广播通道（发布-订阅）", broadcast_channel)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["channel", "pattern"])
+        .requires(&["concurrency::multiple_producers"]);
+    topic
+        .lesson("work_stealing", "工作窃取调度", work_stealing)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["thread", "scheduler", "recursion", NONDETERMINISTIC])
+        .requires(&["concurrency::thread_pool_concept", "concurrency::parallel_computation"]);
+    topic
+        .lesson("work_stealing_benchmark", "工作窃取 vs 共享队列", work_stealing_benchmark)
+        .level(Difficulty::Advanced)
+        .minutes(5)
+        .tags(&["scheduler", "bench", SLOW, NONDETERMINISTIC])
+        .requires(&["concurrency::work_stealing"]);
+    topic
+        .lesson("condvar_basics", "Condvar 条件变量", condvar_basics)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["condvar", "mutex", "sync"])
+        .requires(&["concurrency::arc_mutex"]);
+    topic
+        .lesson("condvar_primitives", "用 Condvar 实现同步原语", condvar_primitives)
+        .level(Difficulty::Advanced)
+        .minutes(12)
+        .tags(&["condvar", "semaphore", "barrier", "sync"])
+        .requires(&["concurrency::condvar_basics", "concurrency::barrier_example"]);
+}
+
+/// 运行所有示例
+pub fn run_all_concurrency_examples() {
+    outln!("\n");
+    outln!("╔════════════════════════════════════════╗");
+    outln!("║   Rust 并发编程 - 完整示例            ║");
+    outln!("╚════════════════════════════════════════╝");
 
-    barrier_example();
-    concurrency_best_practices();
+    // 标记为 slow 的示例（多个生产者、生产者-消费者）会花较长时间，
+    // 这里跳过，可以用 `run concurrency::<函数名>` 单独运行
+    lesson::registry().run_topic("concurrency");
 
-    println!("\n=== 并发编程示例完成 ===\n");
+    outln!("\n=== 并发编程示例完成 ===\n");
 }
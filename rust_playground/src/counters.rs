@@ -0,0 +1,275 @@
+// ============================================
+// 计数器的几种实现 - Mutex / RwLock / 原子操作 / 分片
+// ============================================
+//
+// `arc_mutex` 用 `Arc<Mutex<i32>>` 让 10 个线程各加一次。这里把同一个计数器
+// 换成不同的同步方式，接口统一成 `Counter` trait，方便放在一起计时：
+// - `MutexCounter`：和 `arc_mutex` 一样，每次加一都要加锁
+// - `RwLockCounter`：读多写少时才有优势，计数器每次都是写，反而更慢
+// - `AtomicCounter`：一条 `fetch_add` 指令，可以选择内存顺序
+// - `CasCounter`：用 `compare_exchange` 循环手写的无锁计数
+// - `ShardedCounter`：每个线程写自己的分片，读的时候再求和，几乎没有竞争
+
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::thread;
+
+use crate::bench::{self, Measurement};
+
+/// 可以被多个线程同时加一的计数器
+pub trait Counter: Sync {
+    fn name(&self) -> String;
+    fn increment(&self);
+    fn get(&self) -> usize;
+}
+
+#[derive(Default)]
+pub struct MutexCounter(Mutex<usize>);
+
+impl Counter for MutexCounter {
+    fn name(&self) -> String {
+        "Mutex<usize>".to_string()
+    }
+
+    fn increment(&self) {
+        *self.0.lock().unwrap() += 1;
+    }
+
+    fn get(&self) -> usize {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[derive(Default)]
+pub struct RwLockCounter(RwLock<usize>);
+
+impl Counter for RwLockCounter {
+    fn name(&self) -> String {
+        "RwLock<usize>".to_string()
+    }
+
+    fn increment(&self) {
+        *self.0.write().unwrap() += 1;
+    }
+
+    fn get(&self) -> usize {
+        *self.0.read().unwrap()
+    }
+}
+
+/// 用指定内存顺序做 `fetch_add` 的原子计数器
+pub struct AtomicCounter {
+    value: AtomicUsize,
+    ordering: Ordering,
+}
+
+impl AtomicCounter {
+    pub fn new(ordering: Ordering) -> AtomicCounter {
+        AtomicCounter {
+            value: AtomicUsize::new(0),
+            ordering,
+        }
+    }
+}
+
+/// `load` 不接受 `Release` / `AcqRel`，换成对应的读顺序
+pub fn load_ordering(ordering: Ordering) -> Ordering {
+    match ordering {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        other => other,
+    }
+}
+
+impl Counter for AtomicCounter {
+    fn name(&self) -> String {
+        format!("AtomicUsize ({:?})", self.ordering)
+    }
+
+    fn increment(&self) {
+        self.value.fetch_add(1, self.ordering);
+    }
+
+    fn get(&self) -> usize {
+        self.value.load(load_ordering(self.ordering))
+    }
+}
+
+/// 手写 compare-and-swap 循环：读出旧值，只有没被别人改过时才写入新值
+#[derive(Default)]
+pub struct CasCounter(AtomicUsize);
+
+impl Counter for CasCounter {
+    fn name(&self) -> String {
+        "CAS 循环".to_string()
+    }
+
+    fn increment(&self) {
+        let mut current = self.0.load(Ordering::Relaxed);
+        // 失败说明别的线程抢先改了，拿到最新值重试
+        loop {
+            let next = current + 1;
+            match self
+                .0
+                .compare_exchange_weak(current, next, Ordering::Relaxed, Ordering::Relaxed)
+            {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 对齐到 64 字节（常见的缓存行大小），避免相邻分片的伪共享（false sharing）
+#[repr(align(64))]
+#[derive(Default)]
+struct Shard(AtomicUsize);
+
+/// 每个线程固定写一个分片，`get` 时把所有分片加起来
+pub struct ShardedCounter {
+    shards: Vec<Shard>,
+}
+
+/// 给每个线程分配一个编号，用来挑选分片
+static NEXT_THREAD_INDEX: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static THREAD_INDEX: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+fn thread_index() -> usize {
+    THREAD_INDEX.with(|index| match index.get() {
+        Some(i) => i,
+        None => {
+            let i = NEXT_THREAD_INDEX.fetch_add(1, Ordering::Relaxed);
+            index.set(Some(i));
+            i
+        }
+    })
+}
+
+impl ShardedCounter {
+    /// # Panics
+    ///
+    /// `shards` 为 0 时 panic。
+    pub fn new(shards: usize) -> ShardedCounter {
+        assert!(shards > 0, "至少需要一个分片");
+        ShardedCounter {
+            shards: (0..shards).map(|_| Shard::default()).collect(),
+        }
+    }
+}
+
+impl Counter for ShardedCounter {
+    fn name(&self) -> String {
+        format!("分片计数器 ({} 片)", self.shards.len())
+    }
+
+    fn increment(&self) {
+        let shard = &self.shards[thread_index() % self.shards.len()];
+        shard.0.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// 只在所有写线程结束后（例如 join 之后）读才是精确值
+    fn get(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|s| s.0.load(Ordering::Relaxed))
+            .sum()
+    }
+}
+
+/// 用 `threads` 个线程各加 `per_thread` 次
+pub fn hammer(counter: &dyn Counter, threads: usize, per_thread: usize) {
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                for _ in 0..per_thread {
+                    counter.increment();
+                }
+            });
+        }
+    });
+}
+
+/// 根据线程数创建计数器
+type MakeCounter = fn(usize) -> Box<dyn Counter>;
+
+/// 参与对比的所有计数器，`Mutex` 排第一作为基准
+const FACTORIES: &[MakeCounter] = &[
+    |_| Box::new(MutexCounter::default()),
+    |_| Box::new(RwLockCounter::default()),
+    |_| Box::new(AtomicCounter::new(Ordering::SeqCst)),
+    |_| Box::new(AtomicCounter::new(Ordering::AcqRel)),
+    |_| Box::new(AtomicCounter::new(Ordering::Relaxed)),
+    |_| Box::new(CasCounter::default()),
+    |threads| Box::new(ShardedCounter::new(threads)),
+];
+
+/// 每种实现各创建一个计数器
+pub fn all_counters(threads: usize) -> Vec<Box<dyn Counter>> {
+    FACTORIES.iter().map(|make| make(threads)).collect()
+}
+
+/// 计时：每种计数器都由 `threads` 个线程各加 `per_thread` 次
+pub fn benchmark(threads: usize, per_thread: usize, iterations: u32) -> Vec<Measurement> {
+    let items = (threads * per_thread) as u64;
+    FACTORIES
+        .iter()
+        .map(|make| {
+            let name = make(threads).name();
+            // 每次计时都换一个新计数器，避免数值越加越大
+            bench::measure(&name, iterations, items, || {
+                let counter = make(threads);
+                hammer(counter.as_ref(), threads, per_thread);
+                counter.get()
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_counter_counts_exactly() {
+        for counter in all_counters(4) {
+            hammer(counter.as_ref(), 8, 1000);
+            assert_eq!(counter.get(), 8000, "{}", counter.name());
+        }
+    }
+
+    #[test]
+    fn test_sharded_counter_spreads_threads_over_shards() {
+        let counter = ShardedCounter::new(4);
+        hammer(&counter, 4, 100);
+        assert_eq!(counter.get(), 400);
+        let used = counter
+            .shards
+            .iter()
+            .filter(|s| s.0.load(Ordering::Relaxed) > 0)
+            .count();
+        assert!(used > 1, "线程应该分散到不同分片");
+        assert_eq!(std::mem::align_of::<Shard>(), 64);
+    }
+
+    #[test]
+    fn test_load_ordering_is_valid_for_every_store_ordering() {
+        let value = AtomicUsize::new(1);
+        for ordering in [
+            Ordering::Relaxed,
+            Ordering::Release,
+            Ordering::Acquire,
+            Ordering::AcqRel,
+            Ordering::SeqCst,
+        ] {
+            assert_eq!(value.load(load_ordering(ordering)), 1);
+        }
+    }
+}
@@ -0,0 +1,498 @@
+// ============================================
+// 死锁检测 - 带记录的 Mutex 包装
+// ============================================
+//
+// `TrackedMutex` 在加锁时把信息报告给共享的 `LockDetector`：
+// - 加锁顺序图：线程持有 A 时再去拿 B，就记下一条 A → B 的边。
+//   如果之前已经有 B → … → A 的路径，说明两个线程用相反的顺序加锁，
+//   即使这次没有卡住，也是一个潜在死锁（lock-order inversion）
+// - 等待图（wait-for graph）：线程 → 它在等的锁 → 锁的持有者 → …
+//   沿着走回到自己就是真正的死锁。这时 `lock` 返回 `Err(Deadlock)`
+//   而不是永远阻塞，调用方可以释放已经持有的锁来打破僵局
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, TryLockError};
+use std::thread::{self, ThreadId};
+
+/// 等待图里的一条边：`thread` 在等 `lock`，而 `lock` 被 `owner` 持有
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaitEdge {
+    pub thread: String,
+    pub lock: String,
+    pub owner: String,
+}
+
+/// 真正的死锁：等待图里的一个环
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deadlock {
+    pub cycle: Vec<WaitEdge>,
+}
+
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let edges: Vec<String> = self
+            .cycle
+            .iter()
+            .map(|edge| {
+                format!(
+                    "[{}] 等待 {}（被 [{}] 持有）",
+                    edge.thread, edge.lock, edge.owner
+                )
+            })
+            .collect();
+        write!(f, "检测到死锁: {}", edges.join("，"))
+    }
+}
+
+/// 潜在死锁：`thread` 持有 `held` 时请求 `acquiring`，
+/// 而 `earlier_thread` 之前按 `earlier_order` 的相反顺序加过锁
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inversion {
+    pub thread: String,
+    pub held: String,
+    pub acquiring: String,
+    pub earlier_thread: String,
+    pub earlier_order: Vec<String>,
+}
+
+impl fmt::Display for Inversion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "加锁顺序相反: [{}] 持有 {} 时请求 {}，但 [{}] 之前的顺序是 {}",
+            self.thread,
+            self.held,
+            self.acquiring,
+            self.earlier_thread,
+            self.earlier_order.join(" → ")
+        )
+    }
+}
+
+/// 检测器记录下来的问题
+#[derive(Debug, Clone, PartialEq)]
+pub enum Report {
+    Inversion(Inversion),
+    Deadlock(Deadlock),
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Report::Inversion(inversion) => write!(f, "⚠️  潜在死锁 - {}", inversion),
+            Report::Deadlock(deadlock) => write!(f, "💥 {}", deadlock),
+        }
+    }
+}
+
+type LockId = usize;
+
+#[derive(Default)]
+struct State {
+    lock_names: Vec<String>,
+    thread_names: HashMap<ThreadId, String>,
+    /// 锁 → 当前持有它的线程
+    owners: HashMap<LockId, ThreadId>,
+    /// 线程 → 它正在等待的锁
+    waiting: HashMap<ThreadId, LockId>,
+    /// 线程 → 它按顺序持有的锁
+    held: HashMap<ThreadId, Vec<LockId>>,
+    /// 加锁顺序图：先拿的锁 → [(后拿的锁, 第一次这样做的线程)]
+    order: HashMap<LockId, Vec<(LockId, ThreadId)>>,
+    reported: HashSet<(LockId, LockId)>,
+    reports: Vec<Report>,
+}
+
+impl State {
+    fn thread_name(&self, thread: ThreadId) -> String {
+        self.thread_names
+            .get(&thread)
+            .cloned()
+            .unwrap_or_else(|| format!("{:?}", thread))
+    }
+
+    /// 在加锁顺序图里找一条 from → … → to 的路径，返回路径上的锁和第一条边的线程
+    fn find_order_path(&self, from: LockId, to: LockId) -> Option<(Vec<LockId>, ThreadId)> {
+        let mut previous: HashMap<LockId, (LockId, ThreadId)> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(lock) = queue.pop_front() {
+            if lock == to {
+                let mut path = vec![to];
+                let mut first_thread = None;
+                let mut current = to;
+                while let Some(&(prev, thread)) = previous.get(&current) {
+                    path.push(prev);
+                    first_thread = Some(thread);
+                    current = prev;
+                }
+                path.reverse();
+                return first_thread.map(|thread| (path, thread));
+            }
+            for &(next, thread) in self.order.get(&lock).into_iter().flatten() {
+                if next != from && !previous.contains_key(&next) {
+                    previous.insert(next, (lock, thread));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// 记录 `thread` 在持有当前锁的情况下请求 `acquiring`，发现顺序相反时记录报告
+    fn record_order(&mut self, thread: ThreadId, acquiring: LockId) {
+        let held = self.held.get(&thread).cloned().unwrap_or_default();
+        for held in held.into_iter().filter(|&held| held != acquiring) {
+            if !self.reported.contains(&(held, acquiring)) {
+                if let Some((path, earlier)) = self.find_order_path(acquiring, held) {
+                    self.reported.insert((held, acquiring));
+                    let inversion = Inversion {
+                        thread: self.thread_name(thread),
+                        held: self.lock_names[held].clone(),
+                        acquiring: self.lock_names[acquiring].clone(),
+                        earlier_thread: self.thread_name(earlier),
+                        earlier_order: path.iter().map(|&l| self.lock_names[l].clone()).collect(),
+                    };
+                    self.reports.push(Report::Inversion(inversion));
+                }
+            }
+
+            let edges = self.order.entry(held).or_default();
+            if !edges.iter().any(|&(to, _)| to == acquiring) {
+                edges.push((acquiring, thread));
+            }
+        }
+    }
+
+    /// 从 `start` 出发沿等待图走，回到 `start` 说明出现了死锁
+    fn find_wait_cycle(&self, start: ThreadId) -> Option<Deadlock> {
+        let mut cycle = Vec::new();
+        let mut current = start;
+        loop {
+            let lock = *self.waiting.get(&current)?;
+            let owner = *self.owners.get(&lock)?;
+            cycle.push(WaitEdge {
+                thread: self.thread_name(current),
+                lock: self.lock_names[lock].clone(),
+                owner: self.thread_name(owner),
+            });
+            if owner == start {
+                return Some(Deadlock { cycle });
+            }
+            if cycle.len() > self.waiting.len() {
+                return None; // 环不经过 start，会由环上的线程自己发现
+            }
+            current = owner;
+        }
+    }
+
+    fn acquired(&mut self, thread: ThreadId, lock: LockId) {
+        self.waiting.remove(&thread);
+        self.owners.insert(lock, thread);
+        self.held.entry(thread).or_default().push(lock);
+    }
+
+    fn released(&mut self, thread: ThreadId, lock: LockId) {
+        // 只删自己的记录：锁可能已经被别的线程拿走并登记成了新的持有者
+        if self.owners.get(&lock) == Some(&thread) {
+            self.owners.remove(&lock);
+        }
+        if let Some(held) = self.held.get_mut(&thread) {
+            if let Some(index) = held.iter().rposition(|&l| l == lock) {
+                held.remove(index);
+            }
+        }
+    }
+}
+
+/// 被多个 `TrackedMutex` 共享的检测器
+#[derive(Default)]
+pub struct LockDetector {
+    state: Mutex<State>,
+    /// 每次有锁被释放都会通知，等待中的线程醒来重试
+    released: Condvar,
+}
+
+impl LockDetector {
+    pub fn new() -> Arc<LockDetector> {
+        Arc::new(LockDetector::default())
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 到目前为止发现的所有问题
+    pub fn reports(&self) -> Vec<Report> {
+        self.state().reports.clone()
+    }
+
+    /// 正在等待某个锁的线程数
+    pub fn waiting_threads(&self) -> usize {
+        self.state().waiting.len()
+    }
+}
+
+/// 会向 `LockDetector` 报告加锁情况的 Mutex
+pub struct TrackedMutex<T> {
+    id: LockId,
+    detector: Arc<LockDetector>,
+    inner: Mutex<T>,
+}
+
+impl<T> TrackedMutex<T> {
+    pub fn new(detector: &Arc<LockDetector>, name: &str, value: T) -> TrackedMutex<T> {
+        let mut state = detector.state();
+        state.lock_names.push(name.to_string());
+        TrackedMutex {
+            id: state.lock_names.len() - 1,
+            detector: Arc::clone(detector),
+            inner: Mutex::new(value),
+        }
+    }
+
+    /// 加锁；如果继续等待会形成死锁，返回 `Err(Deadlock)` 而不是阻塞
+    ///
+    /// 为了演示方便，被 panic 毒化的锁照常返回里面的数据。
+    pub fn lock(&self) -> Result<TrackedGuard<'_, T>, Deadlock> {
+        let current = thread::current();
+        let me = current.id();
+
+        let mut state = self.detector.state();
+        if let Some(name) = current.name() {
+            state.thread_names.insert(me, name.to_string());
+        }
+        state.record_order(me, self.id);
+
+        loop {
+            // 检测器的锁在 try_lock 和进入等待之间一直持有，
+            // 而释放锁的线程要拿到它才能发通知，所以不会错过唤醒
+            let guard = match self.inner.try_lock() {
+                Ok(guard) => guard,
+                Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+                Err(TryLockError::WouldBlock) => {
+                    state.waiting.insert(me, self.id);
+                    if let Some(deadlock) = state.find_wait_cycle(me) {
+                        state.waiting.remove(&me);
+                        state.reports.push(Report::Deadlock(deadlock.clone()));
+                        return Err(deadlock);
+                    }
+                    state = self
+                        .detector
+                        .released
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner);
+                    continue;
+                }
+            };
+            state.acquired(me, self.id);
+            return Ok(TrackedGuard {
+                mutex: self,
+                thread: me,
+                guard: Some(guard),
+            });
+        }
+    }
+}
+
+/// `TrackedMutex::lock` 返回的守卫，drop 时通知检测器
+pub struct TrackedGuard<'a, T> {
+    mutex: &'a TrackedMutex<T>,
+    thread: ThreadId,
+    guard: Option<MutexGuard<'a, T>>,
+}
+
+impl<T> Deref for TrackedGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.guard.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for TrackedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.guard.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for TrackedGuard<'_, T> {
+    fn drop(&mut self) {
+        // 拿着检测器的锁再真正解锁：否则在两步之间，别的线程可能拿到这把锁并登记为持有者，
+        // 接着又被这里的 released 抹掉，等待图就少了一条边。
+        // lock() 持有检测器的锁时只用 try_lock，不会在这里反过来等待
+        let detector = &self.mutex.detector;
+        let mut state = detector.state();
+        drop(self.guard.take());
+        state.released(self.thread, self.mutex.id);
+        drop(state);
+        detector.released.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+
+    fn spawn_named<F>(name: &str, f: F) -> thread::JoinHandle<()>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        thread::Builder::new()
+            .name(name.to_string())
+            .spawn(f)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_consistent_order_is_not_reported() {
+        let detector = LockDetector::new();
+        let a = Arc::new(TrackedMutex::new(&detector, "a", 0));
+        let b = Arc::new(TrackedMutex::new(&detector, "b", 0));
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let (a, b) = (Arc::clone(&a), Arc::clone(&b));
+                spawn_named(&format!("t{}", i), move || {
+                    for _ in 0..100 {
+                        let mut a = a.lock().unwrap();
+                        let mut b = b.lock().unwrap();
+                        *a += 1;
+                        *b += 1;
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(*a.lock().unwrap(), 400);
+        assert!(detector.reports().is_empty());
+    }
+
+    #[test]
+    fn test_inversion_reported_without_hanging() {
+        let detector = LockDetector::new();
+        let a = Arc::new(TrackedMutex::new(&detector, "a", ()));
+        let b = Arc::new(TrackedMutex::new(&detector, "b", ()));
+
+        let (a1, b1) = (Arc::clone(&a), Arc::clone(&b));
+        spawn_named("first", move || {
+            let _a = a1.lock().unwrap();
+            let _b = b1.lock().unwrap();
+        })
+        .join()
+        .unwrap();
+        spawn_named("second", move || {
+            let _b = b.lock().unwrap();
+            let _a = a.lock().unwrap();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(
+            detector.reports(),
+            vec![Report::Inversion(Inversion {
+                thread: "second".to_string(),
+                held: "b".to_string(),
+                acquiring: "a".to_string(),
+                earlier_thread: "first".to_string(),
+                earlier_order: vec!["a".to_string(), "b".to_string()],
+            })]
+        );
+    }
+
+    #[test]
+    fn test_real_deadlock_returns_error() {
+        let detector = LockDetector::new();
+        let a = Arc::new(TrackedMutex::new(&detector, "a", ()));
+        let b = Arc::new(TrackedMutex::new(&detector, "b", ()));
+        let barrier = Arc::new(Barrier::new(2));
+
+        let (a1, b1, barrier1) = (Arc::clone(&a), Arc::clone(&b), Arc::clone(&barrier));
+        let first = spawn_named("first", move || {
+            let _a = a1.lock().unwrap();
+            barrier1.wait();
+            // second 放弃后释放 b，这里最终能拿到
+            let _b = b1.lock().unwrap();
+        });
+
+        let detector2 = Arc::clone(&detector);
+        let second = thread::Builder::new()
+            .name("second".to_string())
+            .spawn(move || {
+                let _b = b.lock().unwrap();
+                barrier.wait();
+                while detector2.waiting_threads() == 0 {
+                    thread::yield_now();
+                }
+                a.lock().map(|_| ()).unwrap_err()
+            })
+            .unwrap();
+
+        let deadlock = second.join().unwrap();
+        first.join().unwrap();
+
+        assert_eq!(
+            deadlock.cycle,
+            vec![
+                WaitEdge {
+                    thread: "second".to_string(),
+                    lock: "a".to_string(),
+                    owner: "first".to_string(),
+                },
+                WaitEdge {
+                    thread: "first".to_string(),
+                    lock: "b".to_string(),
+                    owner: "second".to_string(),
+                },
+            ]
+        );
+        assert!(detector
+            .reports()
+            .contains(&Report::Deadlock(deadlock.clone())));
+    }
+
+    #[test]
+    fn test_owner_survives_handoff_between_threads() {
+        let detector = LockDetector::new();
+        let lock = Arc::new(TrackedMutex::new(&detector, "baton", 0));
+
+        // 两个线程来回抢同一把锁：每次拿到锁时，检测器记录的持有者都必须是自己，
+        // 不能被刚释放锁的那个线程抹掉
+        let handles: Vec<_> = ["ping", "pong"]
+            .into_iter()
+            .map(|name| {
+                let (detector, lock) = (Arc::clone(&detector), Arc::clone(&lock));
+                spawn_named(name, move || {
+                    let me = thread::current().id();
+                    for _ in 0..if cfg!(miri) { 50 } else { 20_000 } {
+                        let mut guard = lock.lock().unwrap();
+                        *guard += 1;
+                        assert_eq!(detector.state().owners.get(&lock.id), Some(&me));
+                        drop(guard);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert!(detector.state().owners.is_empty());
+        assert_eq!(detector.waiting_threads(), 0);
+    }
+
+    #[test]
+    fn test_relocking_on_same_thread_is_a_deadlock() {
+        let detector = LockDetector::new();
+        let lock = TrackedMutex::new(&detector, "self", 0);
+        let _guard = lock.lock().unwrap();
+        let err = lock.lock().map(|_| ()).unwrap_err();
+        assert_eq!(err.cycle.len(), 1);
+        assert_eq!(err.cycle[0].lock, "self");
+    }
+}
@@ -0,0 +1,357 @@
+// ============================================
+// 事件总线 - 用 Weak 登记的观察者
+// ============================================
+//
+// `interior_mutability_pattern` 里的 `Messenger` 是一对一的回调：`LimitTracker` 只认识一个
+// messenger。事件总线把它扩展成一对多：发布者只管 `publish`，谁关心这类事件谁就订阅。
+//
+// 几个要点：
+// - 总线只拿订阅者的 `Weak`，不决定订阅者活多久；订阅者 drop 之后，下次发布时自动清掉
+// - 事件按类型分发：订阅 `Subscriber<A>` 的只会收到 `A`，内部用 `TypeId` 区分
+// - 处理事件时可以再订阅、退订、甚至发布别的事件。订阅表放在 `RefCell` 里，
+//   如果一边遍历一边调用处理函数，处理函数里一改订阅表就是 borrow_mut 撞上 borrow，直接 panic。
+//   所以发布时先在一次短借用里把这一类的订阅者复制出来，松开借用之后再逐个调用
+//
+// 分发过程中订阅表的变化这样处理：
+// - 新订阅的从下一次发布开始收到事件
+// - 已经退订的，即使在快照里也不会再被调用
+// - 处理函数里发布的事件会立刻、同步地分发完，再回到外层继续
+
+use std::any::{Any, TypeId};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+/// 关心 `E` 类事件的订阅者
+///
+/// 处理函数拿到总线本身，可以在里面订阅、退订或者继续发布。
+pub trait Subscriber<E> {
+    fn on_event(&self, event: &E, bus: &EventBus);
+}
+
+/// 闭包也可以直接当订阅者
+impl<E, F> Subscriber<E> for F
+where
+    F: Fn(&E, &EventBus),
+{
+    fn on_event(&self, event: &E, bus: &EventBus) {
+        self(event, bus)
+    }
+}
+
+/// 订阅时拿到的编号，退订时用
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+impl fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+struct Entry {
+    id: SubscriptionId,
+    /// 其实是 `Weak<dyn Subscriber<E>>`，`E` 由它所在的 `TypeId` 决定
+    subscriber: Box<dyn Any>,
+}
+
+impl Entry {
+    fn subscriber<E: 'static>(&self) -> &Weak<dyn Subscriber<E>> {
+        self.subscriber
+            .downcast_ref()
+            .expect("订阅表里的类型和 TypeId 对不上")
+    }
+}
+
+/// 单线程的事件总线，所有方法都只需要 `&self`
+pub struct EventBus {
+    subscribers: RefCell<HashMap<TypeId, Vec<Entry>>>,
+    next_id: Cell<u64>,
+}
+
+impl EventBus {
+    pub fn new() -> EventBus {
+        EventBus {
+            subscribers: RefCell::new(HashMap::new()),
+            next_id: Cell::new(0),
+        }
+    }
+
+    /// 订阅 `E` 类事件；总线只保存 `Weak`，订阅者的生死由调用方的 `Rc` 决定
+    pub fn subscribe<E, S>(&self, subscriber: &Rc<S>) -> SubscriptionId
+    where
+        E: 'static,
+        S: Subscriber<E> + 'static,
+    {
+        let id = SubscriptionId(self.next_id.get());
+        self.next_id.set(id.0 + 1);
+        let weak: Weak<dyn Subscriber<E>> = Rc::downgrade(subscriber) as Weak<S>;
+        self.subscribers
+            .borrow_mut()
+            .entry(TypeId::of::<E>())
+            .or_default()
+            .push(Entry {
+                id,
+                subscriber: Box::new(weak),
+            });
+        id
+    }
+
+    /// 退订；这个编号已经不在订阅表里时返回 `false`
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut subscribers = self.subscribers.borrow_mut();
+        for entries in subscribers.values_mut() {
+            if let Some(index) = entries.iter().position(|entry| entry.id == id) {
+                entries.remove(index);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn is_subscribed(&self, id: SubscriptionId) -> bool {
+        self.subscribers
+            .borrow()
+            .values()
+            .any(|entries| entries.iter().any(|entry| entry.id == id))
+    }
+
+    /// 把事件发给所有还活着的 `E` 类订阅者，按订阅顺序调用，返回调用了几个
+    ///
+    /// 发现已经 drop 的订阅者时，分发结束后把它们从订阅表里删掉。
+    pub fn publish<E: 'static>(&self, event: &E) -> usize {
+        // 只在这一句里借用订阅表，调用处理函数时已经松开了
+        let snapshot: Vec<(SubscriptionId, Weak<dyn Subscriber<E>>)> =
+            match self.subscribers.borrow().get(&TypeId::of::<E>()) {
+                Some(entries) => entries
+                    .iter()
+                    .map(|entry| (entry.id, entry.subscriber::<E>().clone()))
+                    .collect(),
+                None => return 0,
+            };
+
+        let mut delivered = 0;
+        let mut found_dead = false;
+        for (id, weak) in snapshot {
+            // 前面的处理函数可能已经把它退订了
+            if !self.is_subscribed(id) {
+                continue;
+            }
+            match weak.upgrade() {
+                Some(subscriber) => {
+                    subscriber.on_event(event, self);
+                    delivered += 1;
+                }
+                None => found_dead = true,
+            }
+        }
+        if found_dead {
+            self.prune::<E>();
+        }
+        delivered
+    }
+
+    /// 还活着的 `E` 类订阅者个数，顺便清掉已经 drop 的
+    pub fn subscriber_count<E: 'static>(&self) -> usize {
+        self.prune::<E>();
+        self.subscribers
+            .borrow()
+            .get(&TypeId::of::<E>())
+            .map_or(0, Vec::len)
+    }
+
+    fn prune<E: 'static>(&self) {
+        if let Some(entries) = self.subscribers.borrow_mut().get_mut(&TypeId::of::<E>()) {
+            entries.retain(|entry| entry.subscriber::<E>().strong_count() > 0);
+        }
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Ping(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct Pong(u32);
+
+    /// 把收到的事件记下来，带上自己的名字
+    fn recorder<E: fmt::Debug>(
+        name: &'static str,
+        log: &Rc<RefCell<Vec<String>>>,
+    ) -> Rc<impl Fn(&E, &EventBus)> {
+        let log = Rc::clone(log);
+        Rc::new(move |event: &E, _: &EventBus| {
+            log.borrow_mut().push(format!("{} {:?}", name, event))
+        })
+    }
+
+    #[test]
+    fn test_events_are_routed_by_type() {
+        let bus = EventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let ping = recorder::<Ping>("a", &log);
+        let pong = recorder::<Pong>("b", &log);
+        bus.subscribe(&ping);
+        bus.subscribe(&pong);
+
+        assert_eq!(bus.publish(&Ping(1)), 1);
+        assert_eq!(bus.publish(&Pong(2)), 1);
+        assert_eq!(bus.publish(&"没人订阅的类型"), 0);
+        assert_eq!(*log.borrow(), ["a Ping(1)", "b Pong(2)"]);
+    }
+
+    #[test]
+    fn test_dropped_subscribers_are_pruned() {
+        let bus = EventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let a = recorder::<Ping>("a", &log);
+        let b = recorder::<Ping>("b", &log);
+        bus.subscribe(&a);
+        let b_id = bus.subscribe(&b);
+        assert_eq!(bus.subscriber_count::<Ping>(), 2);
+
+        drop(b);
+        assert_eq!(bus.publish(&Ping(1)), 1);
+        assert!(!bus.is_subscribed(b_id));
+        assert_eq!(bus.subscriber_count::<Ping>(), 1);
+        assert_eq!(*log.borrow(), ["a Ping(1)"]);
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let bus = EventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let a = recorder::<Ping>("a", &log);
+        let id = bus.subscribe(&a);
+        assert!(bus.unsubscribe(id));
+        assert!(!bus.unsubscribe(id));
+        assert_eq!(bus.publish(&Ping(1)), 0);
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_subscribe_during_dispatch_takes_effect_next_time() {
+        let bus = EventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let late = recorder::<Ping>("late", &log);
+        let adder = {
+            let late = Rc::clone(&late);
+            Rc::new(move |_: &Ping, bus: &EventBus| {
+                if bus.subscriber_count::<Ping>() == 1 {
+                    bus.subscribe(&late);
+                }
+            })
+        };
+        bus.subscribe(&adder);
+
+        assert_eq!(bus.publish(&Ping(1)), 1);
+        assert!(log.borrow().is_empty());
+        assert_eq!(bus.publish(&Ping(2)), 2);
+        assert_eq!(*log.borrow(), ["late Ping(2)"]);
+    }
+
+    #[test]
+    fn test_unsubscribe_during_dispatch() {
+        let bus = EventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        // 只收一次就退订自己
+        let own_id = Rc::new(Cell::new(None));
+        let once = {
+            let own_id = Rc::clone(&own_id);
+            let log = Rc::clone(&log);
+            Rc::new(move |event: &Ping, bus: &EventBus| {
+                log.borrow_mut().push(format!("once {:?}", event));
+                bus.unsubscribe(own_id.get().unwrap());
+            })
+        };
+        own_id.set(Some(bus.subscribe(&once)));
+
+        // 退订排在自己后面的订阅者，它在这一次分发里就不该再被调用
+        let victim_id = Rc::new(Cell::new(None));
+        let killer = {
+            let victim_id = Rc::clone(&victim_id);
+            Rc::new(move |_: &Ping, bus: &EventBus| {
+                if let Some(id) = victim_id.take() {
+                    bus.unsubscribe(id);
+                }
+            })
+        };
+        bus.subscribe(&killer);
+        let victim = recorder::<Ping>("victim", &log);
+        victim_id.set(Some(bus.subscribe(&victim)));
+
+        assert_eq!(bus.publish(&Ping(1)), 2);
+        assert_eq!(bus.publish(&Ping(2)), 1);
+        assert_eq!(*log.borrow(), ["once Ping(1)"]);
+    }
+
+    #[test]
+    fn test_subscriber_dropped_during_dispatch_is_skipped() {
+        let bus = EventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        // 排在前面的订阅者扔掉了后面那个订阅者的最后一个 Rc
+        let slot = Rc::new(RefCell::new(None));
+        let dropper = {
+            let slot = Rc::clone(&slot);
+            Rc::new(move |_: &Ping, _: &EventBus| {
+                slot.borrow_mut().take();
+            })
+        };
+        bus.subscribe(&dropper);
+        let doomed = recorder::<Ping>("doomed", &log);
+        bus.subscribe(&doomed);
+        *slot.borrow_mut() = Some(doomed);
+
+        assert_eq!(bus.publish(&Ping(1)), 1);
+        assert_eq!(bus.subscriber_count::<Ping>(), 1);
+        assert!(log.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_publish_during_dispatch() {
+        let bus = EventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let ponger = {
+            let log = Rc::clone(&log);
+            Rc::new(move |ping: &Ping, bus: &EventBus| {
+                log.borrow_mut().push(format!("收到 {:?}", ping));
+                if ping.0 < 3 {
+                    bus.publish(&Pong(ping.0));
+                }
+            })
+        };
+        let pinger = {
+            let log = Rc::clone(&log);
+            Rc::new(move |pong: &Pong, bus: &EventBus| {
+                log.borrow_mut().push(format!("收到 {:?}", pong));
+                bus.publish(&Ping(pong.0 + 1));
+            })
+        };
+        bus.subscribe(&ponger);
+        bus.subscribe(&pinger);
+
+        bus.publish(&Ping(1));
+        assert_eq!(
+            *log.borrow(),
+            [
+                "收到 Ping(1)",
+                "收到 Pong(1)",
+                "收到 Ping(2)",
+                "收到 Pong(2)",
+                "收到 Ping(3)"
+            ]
+        );
+    }
+}
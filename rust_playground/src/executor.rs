@@ -0,0 +1,572 @@
+// ============================================
+// 最小异步运行时 - 只用标准库
+// ============================================
+//
+// async 运行时做的事情其实不多：
+// - `Future::poll` 返回 `Pending` 时，future 已经把 `Waker` 交给了某个"事件源"
+//   （这里是定时器线程或者通道的发送端）
+// - 事件发生时事件源调用 `waker.wake()`，把任务重新放回任务队列
+// - 执行器不停地从队列里取任务来 poll，队列空了就睡眠，直到有任务被唤醒
+//
+// 这里的执行器是单线程的：所有任务都在调用 `block_on` 的线程上运行。
+// 唤醒可能来自其他线程（定时器），所以任务队列和 `Waker` 都要是线程安全的。
+
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+// ---------- 任务队列 ----------
+
+#[derive(Default)]
+struct Ready {
+    tasks: VecDeque<Arc<Task>>,
+    /// `block_on` 的主 future 被唤醒了
+    main_woken: bool,
+}
+
+/// 就绪任务队列：唤醒方往里放，执行器从里面取
+#[derive(Default)]
+struct Queue {
+    ready: Mutex<Ready>,
+    changed: Condvar,
+}
+
+impl Queue {
+    fn push(&self, task: Arc<Task>) {
+        self.ready.lock().unwrap().tasks.push_back(task);
+        self.changed.notify_one();
+    }
+}
+
+/// 一个被 `spawn` 出来的任务
+struct Task {
+    future: Mutex<Option<BoxFuture>>,
+    queue: Arc<Queue>,
+    /// 已经在队列里了，重复唤醒不用再排一次
+    scheduled: AtomicBool,
+}
+
+/// 任务自己就是 `Waker`：`Arc<Task>` 通过 `Wake` trait 变成 `Waker`
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        if !self.scheduled.swap(true, Ordering::SeqCst) {
+            let queue = Arc::clone(&self.queue);
+            queue.push(self);
+        }
+    }
+}
+
+impl Task {
+    fn poll(self: Arc<Self>) {
+        self.scheduled.store(false, Ordering::SeqCst);
+        let mut slot = self.future.lock().unwrap();
+        if let Some(mut future) = slot.take() {
+            let waker = Waker::from(Arc::clone(&self));
+            let mut cx = Context::from_waker(&waker);
+            if future.as_mut().poll(&mut cx).is_pending() {
+                *slot = Some(future);
+            }
+        }
+    }
+}
+
+/// 主 future 的 `Waker`：只需要在队列上做个标记
+struct MainWaker {
+    queue: Arc<Queue>,
+}
+
+impl Wake for MainWaker {
+    fn wake(self: Arc<Self>) {
+        self.queue.ready.lock().unwrap().main_woken = true;
+        self.queue.changed.notify_one();
+    }
+}
+
+thread_local! {
+    /// 当前线程正在运行的执行器，`spawn` 用它找到任务队列
+    static CURRENT: RefCell<Option<Arc<Queue>>> = const { RefCell::new(None) };
+}
+
+/// 离开 `block_on` 时（包括 panic）恢复外层的执行器
+struct ResetCurrent(Option<Arc<Queue>>);
+
+impl Drop for ResetCurrent {
+    fn drop(&mut self) {
+        CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+    }
+}
+
+/// 运行 `future` 直到完成，期间也运行它 `spawn` 出来的任务
+///
+/// 主 future 完成时还没结束的任务会被直接丢弃。
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    let queue = Arc::new(Queue::default());
+    let previous = CURRENT.with(|current| current.borrow_mut().replace(Arc::clone(&queue)));
+    let _reset = ResetCurrent(previous);
+
+    let mut future = Box::pin(future);
+    let waker = Waker::from(Arc::new(MainWaker {
+        queue: Arc::clone(&queue),
+    }));
+    let mut cx = Context::from_waker(&waker);
+    let mut poll_main = true;
+
+    loop {
+        if poll_main {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+
+        // 取出一个就绪任务；什么都没有就睡到有人唤醒为止
+        let next = {
+            let mut ready = queue.ready.lock().unwrap();
+            loop {
+                if ready.main_woken {
+                    ready.main_woken = false;
+                    poll_main = true;
+                    break None;
+                }
+                if let Some(task) = ready.tasks.pop_front() {
+                    poll_main = false;
+                    break Some(task);
+                }
+                ready = queue.changed.wait(ready).unwrap();
+            }
+        };
+        if let Some(task) = next {
+            task.poll();
+        }
+    }
+}
+
+// ---------- spawn 与 JoinHandle ----------
+
+struct JoinState<T> {
+    output: Option<T>,
+    waker: Option<Waker>,
+}
+
+/// `spawn` 返回的句柄，`.await` 它可以拿到任务的返回值
+pub struct JoinHandle<T> {
+    state: Arc<Mutex<JoinState<T>>>,
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap();
+        match state.output.take() {
+            Some(output) => Poll::Ready(output),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// 把 `future` 放进当前执行器的任务队列
+///
+/// # Panics
+///
+/// 不在 `block_on` 里调用时 panic。
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let queue = CURRENT
+        .with(|current| current.borrow().clone())
+        .expect("spawn 必须在 block_on 里调用");
+
+    let state = Arc::new(Mutex::new(JoinState {
+        output: None,
+        waker: None,
+    }));
+    let task_state = Arc::clone(&state);
+    let wrapped = async move {
+        let output = future.await;
+        let mut state = task_state.lock().unwrap();
+        state.output = Some(output);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    };
+
+    let task = Arc::new(Task {
+        future: Mutex::new(Some(Box::pin(wrapped))),
+        queue: Arc::clone(&queue),
+        scheduled: AtomicBool::new(true),
+    });
+    queue.push(task);
+    JoinHandle { state }
+}
+
+/// 让出执行权：这次 poll 返回 `Pending` 并立刻唤醒自己，排到队尾
+pub fn yield_now() -> YieldNow {
+    YieldNow { yielded: false }
+}
+
+pub struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.yielded {
+            return Poll::Ready(());
+        }
+        self.yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+// ---------- 定时器 ----------
+
+/// 后台定时器线程：按到期时间排序，到期就调用 `wake`
+fn timer() -> &'static Mutex<mpsc::Sender<(Instant, Waker)>> {
+    static TIMER: OnceLock<Mutex<mpsc::Sender<(Instant, Waker)>>> = OnceLock::new();
+    TIMER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<(Instant, Waker)>();
+        thread::Builder::new()
+            .name("timer".to_string())
+            .spawn(move || {
+                // 按到期时间排序的小顶堆；序号保证同一时刻到期的按注册顺序唤醒
+                let mut pending: BinaryHeap<Reverse<(Instant, u64, WakerEntry)>> =
+                    BinaryHeap::new();
+                let mut sequence = 0u64;
+                loop {
+                    let now = Instant::now();
+                    while pending
+                        .peek()
+                        .is_some_and(|Reverse((deadline, _, _))| *deadline <= now)
+                    {
+                        let Reverse((_, _, WakerEntry(waker))) = pending.pop().unwrap();
+                        waker.wake();
+                    }
+
+                    let message = match pending.peek() {
+                        Some(Reverse((deadline, _, _))) => {
+                            rx.recv_timeout(deadline.saturating_duration_since(now))
+                        }
+                        None => rx.recv().map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+                    };
+                    match message {
+                        Ok((deadline, waker)) => {
+                            sequence += 1;
+                            pending.push(Reverse((deadline, sequence, WakerEntry(waker))));
+                        }
+                        Err(mpsc::RecvTimeoutError::Timeout) => {}
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+            })
+            .expect("创建定时器线程失败");
+        Mutex::new(tx)
+    })
+}
+
+/// 让 `Waker` 可以放进 `BinaryHeap`：排序只看前面的到期时间和序号
+struct WakerEntry(Waker);
+
+impl PartialEq for WakerEntry {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for WakerEntry {}
+
+impl PartialOrd for WakerEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for WakerEntry {
+    fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
+
+/// 异步版本的 `thread::sleep`：不占用线程，到期后由定时器线程唤醒
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep {
+        deadline: Instant::now() + duration,
+        waker: None,
+    }
+}
+
+pub struct Sleep {
+    deadline: Instant,
+    /// 最近一次注册给定时器的 waker
+    waker: Option<Waker>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+        // future 可能被挪到别的任务里再 poll（比如经过 select），waker 变了就要重新注册，
+        // 否则到期时叫醒的是旧任务。旧的注册还留在定时器里，到期只是多一次无害的唤醒
+        let unchanged = self
+            .waker
+            .as_ref()
+            .is_some_and(|waker| waker.will_wake(cx.waker()));
+        if !unchanged {
+            let waker = cx.waker().clone();
+            timer()
+                .lock()
+                .unwrap()
+                .send((self.deadline, waker.clone()))
+                .unwrap();
+            self.waker = Some(waker);
+        }
+        Poll::Pending
+    }
+}
+
+// ---------- 异步通道 ----------
+
+struct Channel<T> {
+    queue: VecDeque<T>,
+    receiver: Option<Waker>,
+    senders: usize,
+}
+
+/// 异步通道的发送端，可以克隆出多个生产者
+pub struct Sender<T> {
+    channel: Arc<Mutex<Channel<T>>>,
+}
+
+/// 异步通道的接收端：`recv().await` 在通道为空时让出执行权，而不是阻塞线程
+pub struct Receiver<T> {
+    channel: Arc<Mutex<Channel<T>>>,
+}
+
+/// 创建不限长度的异步通道
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let channel = Arc::new(Mutex::new(Channel {
+        queue: VecDeque::new(),
+        receiver: None,
+        senders: 1,
+    }));
+    (
+        Sender {
+            channel: Arc::clone(&channel),
+        },
+        Receiver { channel },
+    )
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, value: T) {
+        let mut channel = self.channel.lock().unwrap();
+        channel.queue.push_back(value);
+        if let Some(waker) = channel.receiver.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.channel.lock().unwrap().senders += 1;
+        Sender {
+            channel: Arc::clone(&self.channel),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut channel = self.channel.lock().unwrap();
+        channel.senders -= 1;
+        // 最后一个发送端没了，唤醒接收端让它看到 None
+        if channel.senders == 0 {
+            if let Some(waker) = channel.receiver.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// 收到消息返回 `Some`；所有发送端都 drop 且队列为空时返回 `None`
+    pub fn recv(&mut self) -> Recv<'_, T> {
+        Recv { receiver: self }
+    }
+}
+
+pub struct Recv<'a, T> {
+    receiver: &'a mut Receiver<T>,
+}
+
+impl<T> Future for Recv<'_, T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut channel = self.receiver.channel.lock().unwrap();
+        if let Some(value) = channel.queue.pop_front() {
+            Poll::Ready(Some(value))
+        } else if channel.senders == 0 {
+            Poll::Ready(None)
+        } else {
+            channel.receiver = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_on_returns_output() {
+        assert_eq!(block_on(async { 1 + 2 }), 3);
+    }
+
+    #[test]
+    fn test_spawned_tasks_interleave_in_fifo_order() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        block_on(async {
+            let handles: Vec<_> = ["a", "b"]
+                .into_iter()
+                .map(|name| {
+                    let log = Arc::clone(&log);
+                    spawn(async move {
+                        for step in 0..2 {
+                            log.lock().unwrap().push(format!("{}{}", name, step));
+                            yield_now().await;
+                        }
+                        name
+                    })
+                })
+                .collect();
+            let mut names = Vec::new();
+            for handle in handles {
+                names.push(handle.await);
+            }
+            assert_eq!(names, ["a", "b"]);
+        });
+        assert_eq!(*log.lock().unwrap(), ["a0", "b0", "a1", "b1"]);
+    }
+
+    #[test]
+    fn test_sleep_waits_for_deadline_and_runs_concurrently() {
+        let start = Instant::now();
+        let finished = block_on(async {
+            let (tx, mut rx) = channel();
+            for ms in [30, 10, 20] {
+                let tx = tx.clone();
+                spawn(async move {
+                    let began = Instant::now();
+                    sleep(Duration::from_millis(ms)).await;
+                    tx.send((ms, began, Instant::now()));
+                });
+            }
+            drop(tx);
+            let mut finished = Vec::new();
+            while let Some(entry) = rx.recv().await {
+                finished.push(entry);
+            }
+            finished
+        });
+        let order: Vec<_> = finished.iter().map(|&(ms, _, _)| ms).collect();
+        assert_eq!(order, [10, 20, 30]);
+        for &(ms, began, ended) in &finished {
+            assert!(ended - began >= Duration::from_millis(ms));
+        }
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        // 并发进行：三个 sleep 都开始了，才有第一个结束（不依赖机器快慢）
+        let last_began = finished.iter().map(|&(_, began, _)| began).max().unwrap();
+        let first_ended = finished.iter().map(|&(_, _, ended)| ended).min().unwrap();
+        assert!(last_began <= first_ended, "三个 sleep 应该并发进行");
+    }
+
+    #[test]
+    fn test_sleep_wakes_the_latest_waker() {
+        struct Flag(AtomicBool);
+
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        // 同一个 Sleep 先后被两个不同的 waker poll，到期时要叫醒后一个
+        let mut timer = Box::pin(sleep(Duration::from_millis(10)));
+        let first = Waker::from(Arc::new(Flag(AtomicBool::new(false))));
+        let second = Arc::new(Flag(AtomicBool::new(false)));
+        let second_waker = Waker::from(Arc::clone(&second));
+        assert!(timer
+            .as_mut()
+            .poll(&mut Context::from_waker(&first))
+            .is_pending());
+        assert!(timer
+            .as_mut()
+            .poll(&mut Context::from_waker(&second_waker))
+            .is_pending());
+
+        let give_up = Instant::now() + Duration::from_secs(5);
+        while !second.0.load(Ordering::SeqCst) {
+            assert!(Instant::now() < give_up, "新的 waker 没有被唤醒");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_channel_closes_when_all_senders_dropped() {
+        block_on(async {
+            let (tx, mut rx) = channel();
+            let tx2 = tx.clone();
+            tx.send(1);
+            drop(tx);
+            spawn(async move {
+                yield_now().await;
+                tx2.send(2);
+            });
+            assert_eq!(rx.recv().await, Some(1));
+            assert_eq!(rx.recv().await, Some(2));
+            assert_eq!(rx.recv().await, None);
+        });
+    }
+
+    #[test]
+    fn test_waker_from_another_thread() {
+        let value = block_on(async {
+            let (tx, mut rx) = channel();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+                tx.send("来自另一个线程");
+            });
+            rx.recv().await
+        });
+        assert_eq!(value, Some("来自另一个线程"));
+    }
+
+    #[test]
+    #[should_panic(expected = "block_on")]
+    fn test_spawn_outside_block_on_panics() {
+        spawn(async {});
+    }
+}
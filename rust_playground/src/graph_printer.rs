@@ -0,0 +1,357 @@
+// ============================================
+// 环安全的图打印 - 遇到回边就停下来
+// ============================================
+//
+// `#[derive(Debug)]` 会沿着字段一路递归下去，碰到 `Rc` 组成的环就会无限递归直到栈溢出。
+// 这里的打印器记住"正在访问的路径"和"已经访问过的节点"（按地址区分节点）：
+// - 指回路径上祖先的边渲染成 `<cycle #n>`
+// - 指向别处已经打印过的节点（多个 `Rc` 共享同一个节点）渲染成 `<shared #n>`
+// - 被这样引用到的节点在第一次出现时加上 `#n` 编号
+//
+// 同样的遍历也可以导出 Graphviz DOT，用 `dot -Tpng` 画出来。
+//
+// 遍历用显式的栈而不是递归（和 `arena_graph::find_cycle` 一样），很长的链表也不会栈溢出。
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Deref;
+use std::vec;
+
+/// 可以被打印的图节点
+///
+/// 节点按地址区分，所以同一个节点必须总是以同一个引用出现
+/// （放在 `Rc`/`Box` 里的节点天然满足这一点）。
+pub trait GraphNode {
+    /// 子节点的句柄，通常是 `Rc<Self>`
+    ///
+    /// 打印器用显式的栈遍历，子节点要先存起来、过一会儿再访问，
+    /// 所以要的是能拿在手里的句柄，而不是只在 `RefCell` 借用期间有效的引用。
+    type Child: Deref<Target = Self>;
+
+    /// 节点自己的描述，不包括子节点，例如 `Cons(5)`
+    fn label(&self) -> String;
+
+    /// 按顺序列出子节点
+    fn children(&self) -> Vec<Self::Child>;
+}
+
+fn key<N>(node: &N) -> *const N {
+    node
+}
+
+/// 第一遍：找出会被第二次遇到的节点，只有它们需要编号
+///
+/// 每个节点只展开一次，所以遍历顺序不影响结果，用栈就行。
+fn find_revisited<N: GraphNode>(root: &N) -> HashSet<*const N> {
+    let mut seen = HashSet::from([key(root)]);
+    let mut revisited = HashSet::new();
+    let mut stack = root.children();
+    while let Some(node) = stack.pop() {
+        if !seen.insert(key(&*node)) {
+            revisited.insert(key(&*node));
+            continue;
+        }
+        stack.extend(node.children());
+    }
+    revisited
+}
+
+/// 显式栈里的一层，对应递归写法里的一次调用
+struct Frame<N: GraphNode> {
+    key: *const N,
+    /// 拿着句柄，保证节点出栈之前不会被释放、地址不会被别的节点复用（根节点是 `None`）
+    _node: Option<N::Child>,
+    children: vec::IntoIter<N::Child>,
+    count: usize,
+}
+
+impl<N: GraphNode> Frame<N> {
+    fn new(key: *const N, node: Option<N::Child>, children: Vec<N::Child>) -> Frame<N> {
+        Frame {
+            key,
+            _node: node,
+            count: children.len(),
+            children: children.into_iter(),
+        }
+    }
+}
+
+struct Renderer<N> {
+    revisited: HashSet<*const N>,
+    numbers: HashMap<*const N, usize>,
+    on_path: HashSet<*const N>,
+    output: String,
+}
+
+impl<N: GraphNode> Renderer<N> {
+    /// 用显式的栈做深度优先遍历，边走边写，长链表也不会栈溢出
+    fn render(&mut self, root: &N) {
+        let mut stack = Vec::new();
+        if let Some(children) = self.enter(root) {
+            stack.push(Frame::new(key(root), None, children));
+        }
+        while let Some(frame) = stack.last_mut() {
+            let index = frame.count - frame.children.len();
+            let Some(child) = frame.children.next() else {
+                if frame.count > 1 {
+                    self.output.push(']');
+                }
+                self.on_path.remove(&frame.key);
+                stack.pop();
+                continue;
+            };
+            self.output.push_str(match (index, frame.count) {
+                (0, 1) => " → ",
+                (0, _) => " → [",
+                _ => ", ",
+            });
+            if let Some(children) = self.enter(&child) {
+                stack.push(Frame::new(key(&*child), Some(child), children));
+            }
+        }
+    }
+
+    /// 写出节点自己；第一次展开的节点返回它的子节点，由调用方入栈
+    fn enter(&mut self, node: &N) -> Option<Vec<N::Child>> {
+        let key = key(node);
+        if let Some(number) = self.numbers.get(&key) {
+            let kind = if self.on_path.contains(&key) {
+                "cycle"
+            } else {
+                "shared"
+            };
+            self.output.push_str(&format!("<{} #{}>", kind, number));
+            return None;
+        }
+
+        if self.revisited.contains(&key) {
+            let number = self.numbers.len() + 1;
+            self.numbers.insert(key, number);
+            self.output.push_str(&format!("#{} ", number));
+        }
+        self.output.push_str(&node.label());
+        self.on_path.insert(key);
+        Some(node.children())
+    }
+}
+
+/// 渲染成一行，例如 `#1 Cons(5) → Cons(10) → <cycle #1>`
+///
+/// 有多个子节点时写成 `父 → [子1, 子2]`。
+pub fn render<N: GraphNode>(root: &N) -> String {
+    let mut renderer = Renderer {
+        revisited: find_revisited(root),
+        numbers: HashMap::new(),
+        on_path: HashSet::new(),
+        output: String::new(),
+    };
+    renderer.render(root);
+    renderer.output
+}
+
+/// `DotWriter` 的栈帧：`parent` 是入栈时那条边的起点，出栈时补上这条边
+struct DotFrame<N: GraphNode> {
+    id: usize,
+    parent: Option<usize>,
+    frame: Frame<N>,
+}
+
+struct DotWriter<N> {
+    ids: HashMap<*const N, usize>,
+    on_path: HashSet<*const N>,
+    nodes: Vec<String>,
+    edges: Vec<String>,
+}
+
+impl<N: GraphNode> DotWriter<N> {
+    /// 和 `Renderer` 一样用显式的栈；边在子节点整个访问完之后才写，顺序和递归写法相同
+    fn visit(&mut self, root: &N) {
+        let mut stack = vec![DotFrame {
+            id: self.add_node(root),
+            parent: None,
+            frame: Frame::new(key(root), None, root.children()),
+        }];
+        while let Some(top) = stack.last_mut() {
+            let Some(child) = top.frame.children.next() else {
+                let done = stack.pop().unwrap();
+                self.on_path.remove(&done.frame.key);
+                if let Some(parent) = done.parent {
+                    self.edges.push(format!("    n{} -> n{};", parent, done.id));
+                }
+                continue;
+            };
+            let id = top.id;
+            let child_key = key(&*child);
+            match self.ids.get(&child_key) {
+                Some(&child_id) => {
+                    // 子节点还在路径上，说明这条边闭合了一个环
+                    let style = if self.on_path.contains(&child_key) {
+                        " [style=dashed, color=red]"
+                    } else {
+                        ""
+                    };
+                    self.edges
+                        .push(format!("    n{} -> n{}{};", id, child_id, style));
+                }
+                None => {
+                    let children = child.children();
+                    stack.push(DotFrame {
+                        id: self.add_node(&child),
+                        parent: Some(id),
+                        frame: Frame::new(child_key, Some(child), children),
+                    });
+                }
+            }
+        }
+    }
+
+    /// 给第一次遇到的节点编号，写出它的定义，并把它放到当前路径上
+    fn add_node(&mut self, node: &N) -> usize {
+        let id = self.ids.len();
+        self.ids.insert(key(node), id);
+        self.on_path.insert(key(node));
+        self.nodes.push(format!(
+            "    n{} [label=\"{}\"];",
+            id,
+            escape(&node.label())
+        ));
+        id
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// 导出 Graphviz DOT，闭合环的回边画成红色虚线
+pub fn to_dot<N: GraphNode>(root: &N, name: &str) -> String {
+    let mut writer = DotWriter {
+        ids: HashMap::new(),
+        on_path: HashSet::new(),
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    writer.visit(root);
+
+    let mut dot = format!("digraph {} {{\n", name);
+    for line in writer.nodes.into_iter().chain(writer.edges) {
+        dot.push_str(&line);
+        dot.push('\n');
+    }
+    dot.push('}');
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Node {
+        name: &'static str,
+        children: RefCell<Vec<Rc<Node>>>,
+    }
+
+    impl GraphNode for Node {
+        type Child = Rc<Node>;
+
+        fn label(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn children(&self) -> Vec<Rc<Node>> {
+            self.children.borrow().clone()
+        }
+    }
+
+    fn node(name: &'static str) -> Rc<Node> {
+        Rc::new(Node {
+            name,
+            children: RefCell::new(vec![]),
+        })
+    }
+
+    fn link(from: &Rc<Node>, to: &Rc<Node>) {
+        from.children.borrow_mut().push(Rc::clone(to));
+    }
+
+    /// 测试结束时把边清掉，打破环，避免测试本身泄漏
+    fn unlink(nodes: &[&Rc<Node>]) {
+        for node in nodes {
+            node.children.borrow_mut().clear();
+        }
+    }
+
+    #[test]
+    fn test_acyclic_graph_has_no_numbers() {
+        let (a, b, c) = (node("a"), node("b"), node("c"));
+        link(&a, &b);
+        link(&a, &c);
+        assert_eq!(render(&*a), "a → [b, c]");
+        assert_eq!(render(&*b), "b");
+    }
+
+    #[test]
+    fn test_cycle_is_rendered_as_back_reference() {
+        let (a, b, c) = (node("a"), node("b"), node("c"));
+        link(&a, &b);
+        link(&b, &c);
+        link(&c, &a);
+        assert_eq!(render(&*a), "#1 a → b → c → <cycle #1>");
+        assert_eq!(render(&*b), "#1 b → c → a → <cycle #1>");
+
+        // 自环
+        link(&c, &c);
+        assert_eq!(render(&*c), "#1 c → [a → b → <cycle #1>, <cycle #1>]");
+        unlink(&[&a, &b, &c]);
+    }
+
+    #[test]
+    fn test_shared_node_is_not_a_cycle() {
+        let (root, left, right, shared) = (node("root"), node("l"), node("r"), node("s"));
+        link(&root, &left);
+        link(&root, &right);
+        link(&left, &shared);
+        link(&right, &shared);
+        assert_eq!(render(&*root), "root → [l → #1 s, r → <shared #1>]");
+    }
+
+    #[test]
+    fn test_dot_export_marks_back_edges() {
+        let (a, b) = (node("a"), node("say \"hi\""));
+        link(&a, &b);
+        link(&b, &a);
+        let expected = "\
+digraph g {
+    n0 [label=\"a\"];
+    n1 [label=\"say \\\"hi\\\"\"];
+    n1 -> n0 [style=dashed, color=red];
+    n0 -> n1;
+}";
+        assert_eq!(to_dot(&*a, "g"), expected);
+        unlink(&[&a, &b]);
+    }
+
+    #[test]
+    fn test_long_list_does_not_overflow_the_stack() {
+        // 递归写法每个节点占一层调用栈，十万个节点的链表会把测试线程的栈撑爆
+        let len = if cfg!(miri) { 100 } else { 100_000 };
+        let nodes: Vec<_> = (0..len).map(|_| node("n")).collect();
+        for pair in nodes.windows(2) {
+            link(&pair[0], &pair[1]);
+        }
+        link(&nodes[len - 1], &nodes[0]);
+
+        let line = render(&*nodes[0]);
+        assert!(line.starts_with("#1 n → n → "));
+        assert!(line.ends_with("n → <cycle #1>"));
+        assert_eq!(line.matches(" → ").count(), len);
+
+        let dot = to_dot(&*nodes[0], "g");
+        assert_eq!(dot.matches(" -> ").count(), len);
+        assert_eq!(dot.matches("style=dashed").count(), 1);
+
+        // 逐个断开，链表 drop 时也不会递归
+        unlink(&nodes.iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,206 @@
+// ============================================
+// 泄漏追踪 - 找出没有被 drop 的对象
+// ============================================
+//
+// `Rc` 组成的引用循环不会报错，也不会崩溃，只是悄悄地永远不释放。
+// 这里用一个很朴素的办法把它变得"看得见"：
+// - `Tracked<T>` 包一层值，创建时登记到当前线程的存活表，drop 时注销
+// - `LeakScope` 记下开始时的编号，结束时列出在这之后创建、却还活着的对象
+//
+// 存活表是线程局部的：`Rc` 本来就不能跨线程，追踪它管理的对象也只需要看当前线程。
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+thread_local! {
+    /// 当前线程所有还活着的 `Tracked`：编号 → 标签
+    static LIVE: RefCell<BTreeMap<u64, String>> = const { RefCell::new(BTreeMap::new()) };
+    static NEXT_ID: Cell<u64> = const { Cell::new(0) };
+}
+
+/// 被追踪的值：创建和 drop 都会记录下来
+pub struct Tracked<T> {
+    id: u64,
+    value: T,
+}
+
+impl<T> Tracked<T> {
+    /// `label` 会出现在泄漏报告里，用来认出是哪个对象
+    pub fn new(label: impl Into<String>, value: T) -> Tracked<T> {
+        let id = NEXT_ID.with(|next| next.replace(next.get() + 1));
+        LIVE.with(|live| live.borrow_mut().insert(id, label.into()));
+        Tracked { id, value }
+    }
+}
+
+impl<T> Drop for Tracked<T> {
+    fn drop(&mut self) {
+        // 线程退出时存活表可能已经销毁，这时就不用记了
+        let _ = LIVE.try_with(|live| live.borrow_mut().remove(&self.id));
+    }
+}
+
+impl<T> Deref for Tracked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tracked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tracked<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+/// 一段检查范围：只关心在它创建之后出现的对象
+pub struct LeakScope {
+    first_id: u64,
+}
+
+impl LeakScope {
+    pub fn new() -> LeakScope {
+        LeakScope {
+            first_id: NEXT_ID.with(Cell::get),
+        }
+    }
+
+    /// 到目前为止的统计：创建了多少，还有哪些活着
+    pub fn report(&self) -> LeakReport {
+        let created = NEXT_ID.with(Cell::get) - self.first_id;
+        let alive = LIVE.with(|live| {
+            live.borrow()
+                .range(self.first_id..)
+                .map(|(_, label)| label.clone())
+                .collect()
+        });
+        LeakReport {
+            created: created as usize,
+            alive,
+        }
+    }
+}
+
+impl Default for LeakScope {
+    fn default() -> Self {
+        LeakScope::new()
+    }
+}
+
+/// `LeakScope::report` 的结果
+#[derive(Debug)]
+pub struct LeakReport {
+    pub created: usize,
+    /// 还没有被 drop 的对象标签，按创建顺序排列
+    pub alive: Vec<String>,
+}
+
+impl LeakReport {
+    pub fn dropped(&self) -> usize {
+        self.created - self.alive.len()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.alive.is_empty()
+    }
+}
+
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "创建 {} 个，释放 {} 个", self.created, self.dropped())?;
+        if self.is_clean() {
+            write!(f, "，全部回收 ✅")
+        } else {
+            write!(
+                f,
+                "，泄漏 {} 个 ❌: {}",
+                self.alive.len(),
+                self.alive.join(", ")
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::{Rc, Weak};
+
+    struct Node {
+        _name: Tracked<&'static str>,
+        next: RefCell<Option<Rc<Node>>>,
+        prev: RefCell<Weak<Node>>,
+    }
+
+    fn node(name: &'static str) -> Rc<Node> {
+        Rc::new(Node {
+            _name: Tracked::new(name, name),
+            next: RefCell::new(None),
+            prev: RefCell::new(Weak::new()),
+        })
+    }
+
+    #[test]
+    fn test_dropped_values_are_not_reported() {
+        let scope = LeakScope::new();
+        {
+            let mut value = Tracked::new("vec", vec![1, 2]);
+            value.push(3);
+            assert_eq!(*value, [1, 2, 3]);
+            assert_eq!(scope.report().alive, ["vec"]);
+        }
+        let report = scope.report();
+        assert_eq!(report.created, 1);
+        assert!(report.is_clean());
+        assert_eq!(report.to_string(), "创建 1 个，释放 1 个，全部回收 ✅");
+    }
+
+    #[test]
+    fn test_rc_cycle_is_reported() {
+        let scope = LeakScope::new();
+        {
+            let a = node("a");
+            let b = node("b");
+            let _c = node("c");
+            *a.next.borrow_mut() = Some(Rc::clone(&b));
+            *b.next.borrow_mut() = Some(Rc::clone(&a));
+        }
+        let report = scope.report();
+        assert_eq!(report.alive, ["a", "b"]);
+        assert_eq!(
+            report.to_string(),
+            "创建 3 个，释放 1 个，泄漏 2 个 ❌: a, b"
+        );
+    }
+
+    #[test]
+    fn test_weak_back_edge_is_reclaimed() {
+        let scope = LeakScope::new();
+        {
+            let a = node("a");
+            let b = node("b");
+            *a.next.borrow_mut() = Some(Rc::clone(&b));
+            *b.prev.borrow_mut() = Rc::downgrade(&a);
+        }
+        assert!(scope.report().is_clean());
+    }
+
+    #[test]
+    fn test_scope_ignores_objects_created_before_it() {
+        let outer = Tracked::new("outer", ());
+        let scope = LeakScope::new();
+        let inner = Tracked::new("inner", ());
+        assert_eq!(scope.report().alive, ["inner"]);
+        drop((outer, inner));
+        assert!(scope.report().is_clean());
+    }
+}
@@ -0,0 +1,313 @@
+// ============================================
+// 课程注册表 - 每个示例函数的元数据
+// ============================================
+//
+// 每个示例模块提供一个 `register(&mut Registry)`，把自己的示例函数
+// 连同标题、难度、预计时长、标签和前置课程登记进来。
+// 列表、学习顺序和 `run_all_*` 都从这里的数据生成，而不是手写调用序列。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// 难度等级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Beginner,
+    Intermediate,
+    Advanced,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label = match self {
+            Difficulty::Beginner => "⭐ 入门",
+            Difficulty::Intermediate => "⭐⭐ 进阶",
+            Difficulty::Advanced => "⭐⭐⭐ 高级",
+        };
+        f.write_str(label)
+    }
+}
+
+/// 被 `run_all_*` 跳过的课程标签（耗时较长，需要单独运行）
+pub const SLOW: &str = "slow";
+
+/// 输出的行集合固定、但行的先后顺序每次运行都可能不同（多线程交错打印）
+pub const UNORDERED: &str = "unordered";
+
+/// 支持确定性调度模式（`run <id> --seed <n>`），同一个种子输出完全一致
+pub const SEEDED: &str = "seeded";
+
+/// 输出内容本身每次运行都可能不同（HashMap 遍历顺序、线程抢任务等），
+/// 不参与快照比较
+pub const NONDETERMINISTIC: &str = "nondeterministic";
+
+/// 一个学习主题，对应一个示例模块
+pub struct Topic {
+    pub name: &'static str,
+    pub title: &'static str,
+    /// 带横幅的完整运行入口，例如 `run_all_ownership_examples`
+    pub run_all: fn(),
+}
+
+/// 一节课：一个可以单独运行的示例函数
+pub struct Lesson {
+    /// 完整 id，形如 `ownership::basic_ownership`
+    pub id: String,
+    pub topic: &'static str,
+    pub title: &'static str,
+    pub difficulty: Difficulty,
+    /// 预计学习时长（分钟）
+    pub minutes: u32,
+    pub tags: &'static [&'static str],
+    /// 前置课程的完整 id
+    pub prerequisites: &'static [&'static str],
+    pub run: fn(),
+}
+
+impl Lesson {
+    pub fn level(&mut self, difficulty: Difficulty) -> &mut Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    pub fn minutes(&mut self, minutes: u32) -> &mut Self {
+        self.minutes = minutes;
+        self
+    }
+
+    pub fn tags(&mut self, tags: &'static [&'static str]) -> &mut Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn requires(&mut self, prerequisites: &'static [&'static str]) -> &mut Self {
+        self.prerequisites = prerequisites;
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.contains(&tag)
+    }
+}
+
+/// 注册某个主题时拿到的句柄，只能往这个主题里加课程
+pub struct TopicRegistrar<'r> {
+    registry: &'r mut Registry,
+    topic: &'static str,
+}
+
+impl TopicRegistrar<'_> {
+    /// 登记一节课，默认难度为入门、时长 5 分钟，可以链式修改
+    pub fn lesson(&mut self, name: &'static str, title: &'static str, run: fn()) -> &mut Lesson {
+        let lessons = &mut self.registry.lessons;
+        lessons.push(Lesson {
+            id: format!("{}::{}", self.topic, name),
+            topic: self.topic,
+            title,
+            difficulty: Difficulty::Beginner,
+            minutes: 5,
+            tags: &[],
+            prerequisites: &[],
+            run,
+        });
+        lessons.last_mut().unwrap()
+    }
+}
+
+/// 所有主题和课程的集合，顺序即注册顺序
+#[derive(Default)]
+pub struct Registry {
+    topics: Vec<Topic>,
+    lessons: Vec<Lesson>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 开始注册一个主题
+    pub fn topic(
+        &mut self,
+        name: &'static str,
+        title: &'static str,
+        run_all: fn(),
+    ) -> TopicRegistrar<'_> {
+        self.topics.push(Topic {
+            name,
+            title,
+            run_all,
+        });
+        TopicRegistrar {
+            registry: self,
+            topic: name,
+        }
+    }
+
+    pub fn topics(&self) -> &[Topic] {
+        &self.topics
+    }
+
+    pub fn lessons(&self) -> &[Lesson] {
+        &self.lessons
+    }
+
+    pub fn find_topic(&self, name: &str) -> Option<&Topic> {
+        self.topics.iter().find(|topic| topic.name == name)
+    }
+
+    pub fn find(&self, id: &str) -> Option<&Lesson> {
+        self.lessons.iter().find(|lesson| lesson.id == id)
+    }
+
+    /// 某个主题下的全部课程
+    pub fn lessons_in<'a>(&'a self, topic: &'a str) -> impl Iterator<Item = &'a Lesson> + 'a {
+        self.lessons
+            .iter()
+            .filter(move |lesson| lesson.topic == topic)
+    }
+
+    /// 依次运行某个主题的课程，跳过标记为 [`SLOW`] 的课程
+    pub fn run_topic(&self, topic: &str) {
+        for lesson in self.lessons_in(topic) {
+            if lesson.has_tag(SLOW) {
+                outln!("\n[跳过{}示例以节省时间]", lesson.title);
+            } else {
+                (lesson.run)();
+            }
+        }
+    }
+
+    /// 检查 id 唯一、前置课程存在且没有循环依赖
+    pub fn validate(&self) -> Result<(), String> {
+        for (i, lesson) in self.lessons.iter().enumerate() {
+            if self.lessons[..i].iter().any(|other| other.id == lesson.id) {
+                return Err(format!("重复的课程 id: {}", lesson.id));
+            }
+            for prerequisite in lesson.prerequisites {
+                if self.find(prerequisite).is_none() {
+                    return Err(format!("{} 的前置课程不存在: {}", lesson.id, prerequisite));
+                }
+            }
+        }
+        self.learning_path().map(|_| ())
+    }
+
+    /// 按前置关系排出学习顺序；没有依赖约束时保持注册顺序
+    pub fn learning_path(&self) -> Result<Vec<&Lesson>, String> {
+        let index: HashMap<&str, usize> = self
+            .lessons
+            .iter()
+            .enumerate()
+            .map(|(i, lesson)| (lesson.id.as_str(), i))
+            .collect();
+
+        let mut remaining: Vec<usize> = self
+            .lessons
+            .iter()
+            .map(|lesson| lesson.prerequisites.len())
+            .collect();
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); self.lessons.len()];
+        for (i, lesson) in self.lessons.iter().enumerate() {
+            for prerequisite in lesson.prerequisites {
+                match index.get(prerequisite) {
+                    Some(&p) => dependents[p].push(i),
+                    None => remaining[i] -= 1, // 不存在的前置课程交给 validate 报告
+                }
+            }
+        }
+
+        let mut done = vec![false; self.lessons.len()];
+        let mut path = Vec::with_capacity(self.lessons.len());
+        while path.len() < self.lessons.len() {
+            // 每次取注册顺序最靠前的可学课程，保证结果稳定
+            let next = (0..self.lessons.len())
+                .find(|&i| !done[i] && remaining[i] == 0)
+                .ok_or_else(|| {
+                    let stuck: Vec<&str> = (0..self.lessons.len())
+                        .filter(|&i| !done[i])
+                        .map(|i| self.lessons[i].id.as_str())
+                        .collect();
+                    format!("前置课程存在循环依赖: {}", stuck.join(", "))
+                })?;
+            done[next] = true;
+            for &dependent in &dependents[next] {
+                remaining[dependent] -= 1;
+            }
+            path.push(&self.lessons[next]);
+        }
+        Ok(path)
+    }
+}
+
+/// 全局注册表：第一次使用时由各模块的 `register` 填充
+pub fn registry() -> &'static Registry {
+    static REGISTRY: OnceLock<Registry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let mut registry = Registry::new();
+        crate::example::register(&mut registry);
+        crate::utils::register(&mut registry);
+        crate::ownership_examples::register(&mut registry);
+        crate::lifetime_examples::register(&mut registry);
+        crate::trait_generic_examples::register(&mut registry);
+        crate::smart_pointer_examples::register(&mut registry);
+        crate::concurrency_examples::register(&mut registry);
+        crate::async_examples::register(&mut registry);
+        if let Err(message) = registry.validate() {
+            panic!("课程注册表有误: {}", message);
+        }
+        registry
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noop() {}
+
+    #[test]
+    fn test_global_registry_is_valid() {
+        let registry = registry();
+        assert_eq!(registry.validate(), Ok(()));
+        assert!(registry.find("ownership::basic_ownership").is_some());
+        assert!(registry.find("concurrency::barrier_example").is_some());
+        for topic in registry.topics() {
+            assert!(registry.lessons_in(topic.name).next().is_some());
+        }
+    }
+
+    #[test]
+    fn test_learning_path_respects_prerequisites() {
+        let mut registry = Registry::new();
+        let mut topic = registry.topic("t", "测试", noop);
+        topic.lesson("c", "C", noop).requires(&["t::b"]);
+        topic.lesson("a", "A", noop);
+        topic.lesson("b", "B", noop).requires(&["t::a"]);
+
+        let path: Vec<&str> = registry
+            .learning_path()
+            .unwrap()
+            .iter()
+            .map(|lesson| lesson.id.as_str())
+            .collect();
+        assert_eq!(path, ["t::a", "t::b", "t::c"]);
+    }
+
+    #[test]
+    fn test_validate_reports_problems() {
+        let mut registry = Registry::new();
+        registry
+            .topic("t", "测试", noop)
+            .lesson("a", "A", noop)
+            .requires(&["t::x"]);
+        assert!(registry.validate().unwrap_err().contains("t::x"));
+
+        let mut registry = Registry::new();
+        let mut topic = registry.topic("t", "测试", noop);
+        topic.lesson("a", "A", noop).requires(&["t::b"]);
+        topic.lesson("b", "B", noop).requires(&["t::a"]);
+        assert!(registry.validate().unwrap_err().contains("循环依赖"));
+    }
+}
@@ -2,9 +2,11 @@
 // Rust 生命周期 - 深度示例
 // ============================================
 
+use crate::lesson::{self, Difficulty, Registry};
+
 /// 生命周期基础
 pub fn lifetime_basics() {
-    println!("\n=== 1. 为什么需要生命周期 ===");
+    outln!("\n=== 1. 为什么需要生命周期 ===");
 
     // 编译器需要确保引用总是有效的
     let r;
@@ -12,26 +14,26 @@ pub fn lifetime_basics() {
         let x = 5;
         r = &x; // ❌ x 在这个作用域结束后被释放
     }
-    // println!("r: {}", r); // ❌ 悬垂引用！
+    // outln!("r: {}", r); // ❌ 悬垂引用！
 
     // 正确的做法
     let x = 5;
     let r = &x;
-    println!("r: {}", r); // ✅ x 在 r 使用时仍然有效
+    outln!("r: {}", r); // ✅ x 在 r 使用时仍然有效
 }
 
 /// 函数中的生命周期标注
 pub fn lifetime_annotations() {
-    println!("\n=== 2. 生命周期标注语法 ===");
+    outln!("\n=== 2. 生命周期标注语法 ===");
 
     let string1 = String::from("long string is long");
     let result;
     {
         let string2 = String::from("xyz");
         result = longest(string1.as_str(), string2.as_str());
-        println!("最长的字符串是: {}", result);
+        outln!("最长的字符串是: {}", result);
     }
-    // println!("结果: {}", result); // ❌ 如果取消注释会报错
+    // outln!("结果: {}", result); // ❌ 如果取消注释会报错
 }
 
 // 生命周期标注语法
@@ -49,7 +51,7 @@ fn longest<'a>(x: &'a str, y: &'a str) -> &'a str {
 
 /// 生命周期标注详解
 pub fn lifetime_annotation_details() {
-    println!("\n=== 3. 生命周期标注详解 ===");
+    outln!("\n=== 3. 生命周期标注详解 ===");
 
     // 示例1: 返回的引用与 x 相关
     fn first<'a>(x: &'a str, _y: &str) -> &'a str {
@@ -59,21 +61,21 @@ pub fn lifetime_annotation_details() {
     let s1 = String::from("hello");
     let s2 = String::from("world");
     let result = first(&s1, &s2);
-    println!("first: {}", result);
+    outln!("first: {}", result);
 
     // 示例2: 不同的生命周期参数
     fn different_lifetimes<'a, 'b>(x: &'a str, y: &'b str) -> &'a str {
-        println!("x: {}, y: {}", x, y);
+        outln!("x: {}, y: {}", x, y);
         x // 只返回 x，所以只需要 'a
     }
 
     let result = different_lifetimes(&s1, &s2);
-    println!("different_lifetimes: {}", result);
+    outln!("different_lifetimes: {}", result);
 }
 
 /// 结构体中的生命周期
 pub fn struct_lifetimes() {
-    println!("\n=== 4. 结构体中的生命周期 ===");
+    outln!("\n=== 4. 结构体中的生命周期 ===");
 
     // 结构体持有引用，需要生命周期标注
     #[derive(Debug)]
@@ -88,17 +90,17 @@ pub fn struct_lifetimes() {
         part: first_sentence,
     };
 
-    println!("ImportantExcerpt: {:?}", i);
+    outln!("ImportantExcerpt: {:?}", i);
     // i 的生命周期不能超过 novel
 }
 
 /// 生命周期省略规则
 pub fn lifetime_elision() {
-    println!("\n=== 5. 生命周期省略规则 ===");
+    outln!("\n=== 5. 生命周期省略规则 ===");
 
     // 规则1: 每个引用参数都有自己的生命周期
     fn print_str(s: &str) { // 等价于 fn print_str<'a>(s: &'a str)
-        println!("{}", s);
+        outln!("{}", s);
     }
 
     // 规则2: 如果只有一个输入生命周期参数，
@@ -113,12 +115,12 @@ pub fn lifetime_elision() {
 
     print_str("hello");
     let result = first_word("hello world");
-    println!("first_word: {}", result);
+    outln!("first_word: {}", result);
 }
 
 /// 方法中的生命周期
 pub fn method_lifetimes() {
-    println!("\n=== 6. 方法中的生命周期 ===");
+    outln!("\n=== 6. 方法中的生命周期 ===");
 
     struct ImportantExcerpt<'a> {
         part: &'a str,
@@ -132,13 +134,13 @@ pub fn method_lifetimes() {
 
         // 返回引用，应用规则3
         fn announce_and_return_part(&self, announcement: &str) -> &str {
-            println!("Attention please: {}", announcement);
+            outln!("Attention please: {}", announcement);
             self.part
         }
 
         // 明确的生命周期标注
         fn announce_and_return_param<'b>(&self, announcement: &'b str) -> &'b str {
-            println!("Attention: {}", announcement);
+            outln!("Attention: {}", announcement);
             announcement
         }
     }
@@ -149,24 +151,24 @@ pub fn method_lifetimes() {
         part: first_sentence,
     };
 
-    println!("Level: {}", excerpt.level());
+    outln!("Level: {}", excerpt.level());
     let announcement = String::from("Breaking news!");
     excerpt.announce_and_return_part(&announcement);
 }
 
 /// 静态生命周期
 pub fn static_lifetime() {
-    println!("\n=== 7. 静态生命周期 'static ===");
+    outln!("\n=== 7. 静态生命周期 'static ===");
 
     // 'static 表示引用在整个程序运行期间都有效
     let s: &'static str = "I have a static lifetime.";
-    println!("{}", s);
+    outln!("{}", s);
 
     // 字符串字面量都是 'static
     let s1: &'static str = "hello";
     let s2 = "world"; // 类型推断为 &'static str
 
-    println!("{} {}", s1, s2);
+    outln!("{} {}", s1, s2);
 
     // 警告：不要随意使用 'static
     // 大多数情况下，你不需要 'static
@@ -174,7 +176,7 @@ pub fn static_lifetime() {
 
 /// 生命周期、泛型和 trait bound 结合
 pub fn lifetime_generic_trait() {
-    println!("\n=== 8. 生命周期 + 泛型 + Trait Bound ===");
+    outln!("\n=== 8. 生命周期 + 泛型 + Trait Bound ===");
 
     use std::fmt::Display;
 
@@ -187,7 +189,7 @@ pub fn lifetime_generic_trait() {
     where
         T: Display,
     {
-        println!("Announcement! {}", ann);
+        outln!("Announcement! {}", ann);
         if x.len() > y.len() {
             x
         } else {
@@ -202,12 +204,12 @@ pub fn lifetime_generic_trait() {
         string2.as_str(),
         "Today is someone's birthday!",
     );
-    println!("最长的字符串是: {}", result);
+    outln!("最长的字符串是: {}", result);
 }
 
 /// 复杂的生命周期场景
 pub fn complex_lifetime_scenarios() {
-    println!("\n=== 9. 复杂的生命周期场景 ===");
+    outln!("\n=== 9. 复杂的生命周期场景 ===");
 
     // 场景1: 结构体包含多个引用
     #[derive(Debug)]
@@ -222,7 +224,7 @@ pub fn complex_lifetime_scenarios() {
         s: &s,
         t: &t,
     };
-    println!("Context: {:?}", ctx);
+    outln!("Context: {:?}", ctx);
 
     // 场景2: 生命周期约束
     struct Parser<'a, 'b: 'a> { // 'b: 'a 表示 'b 至少要和 'a 一样长
@@ -245,16 +247,16 @@ pub fn complex_lifetime_scenarios() {
         data: String::from("data"),
     };
     let data_ref = holder.get_data();
-    println!("StringHolder data: {}", data_ref);
+    outln!("StringHolder data: {}", data_ref);
 }
 
 /// 生命周期子类型化
 pub fn lifetime_subtyping() {
-    println!("\n=== 10. 生命周期子类型化 ===");
+    outln!("\n=== 10. 生命周期子类型化 ===");
 
     // 'static 是所有生命周期的子类型
     fn print_it<'a>(input: &'a str) {
-        println!("{}", input);
+        outln!("{}", input);
     }
 
     let static_str: &'static str = "I'm static";
@@ -270,15 +272,15 @@ pub fn lifetime_subtyping() {
     {
         let string2 = String::from("short");
         result = longest(&string1, &string2);
-        println!("最长的: {}", result);
+        outln!("最长的: {}", result);
     }
 }
 
 /// 常见的生命周期错误和解决方案
 pub fn common_lifetime_errors() {
-    println!("\n=== 11. 常见生命周期错误 ===");
+    outln!("\n=== 11. 常见生命周期错误 ===");
 
-    println!("错误1: 返回悬垂引用");
+    outln!("错误1: 返回悬垂引用");
     // fn dangle() -> &str { // ❌ 缺少生命周期参数
     //     let s = String::from("hello");
     //     &s
@@ -288,31 +290,31 @@ pub fn common_lifetime_errors() {
     fn no_dangle() -> String {
         String::from("hello")
     }
-    println!("no_dangle: {}", no_dangle());
+    outln!("no_dangle: {}", no_dangle());
 
-    println!("\n错误2: 生命周期不够长");
+    outln!("\n错误2: 生命周期不够长");
     // let r;
     // {
     //     let x = 5;
     //     r = &x; // ❌ x 的生命周期不够长
     // }
-    // println!("{}", r);
+    // outln!("{}", r);
 
     // 解决方案：扩大 x 的作用域
     let x = 5;
     let r = &x;
-    println!("r: {}", r);
+    outln!("r: {}", r);
 }
 
 /// 实用技巧和最佳实践
 pub fn lifetime_best_practices() {
-    println!("\n=== 12. 生命周期最佳实践 ===");
+    outln!("\n=== 12. 生命周期最佳实践 ===");
 
-    println!("✅ 1. 尽可能依赖生命周期省略规则");
-    println!("✅ 2. 只在必要时显式标注生命周期");
-    println!("✅ 3. 考虑返回所有权而不是引用");
-    println!("✅ 4. 使用 'static 要慎重");
-    println!("✅ 5. 结构体中存储引用要慎重，考虑存储所有权");
+    outln!("✅ 1. 尽可能依赖生命周期省略规则");
+    outln!("✅ 2. 只在必要时显式标注生命周期");
+    outln!("✅ 3. 考虑返回所有权而不是引用");
+    outln!("✅ 4. 使用 'static 要慎重");
+    outln!("✅ 5. 结构体中存储引用要慎重，考虑存储所有权");
 
     // 示例：避免生命周期，使用所有权
     #[derive(Debug)]
@@ -323,28 +325,92 @@ pub fn lifetime_best_practices() {
     let excerpt = BetterExcerpt {
         part: String::from("Call me Ishmael"),
     };
-    println!("BetterExcerpt: {:?}", excerpt);
+    outln!("BetterExcerpt: {:?}", excerpt);
+}
+
+/// 登记本模块的示例
+pub fn register(registry: &mut Registry) {
+    let mut topic = registry.topic("lifetime", "生命周期", run_all_lifetime_examples);
+
+    topic
+        .lesson("lifetime_basics", "为什么需要生命周期", lifetime_basics)
+        .minutes(5)
+        .tags(&["lifetime"])
+        .requires(&["ownership::borrowing_basics"]);
+    topic
+        .lesson("lifetime_annotations", "生命周期标注语法", lifetime_annotations)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["lifetime"])
+        .requires(&["lifetime::lifetime_basics"]);
+    topic
+        .lesson("lifetime_annotation_details", "生命周期标注详解", lifetime_annotation_details)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["lifetime"])
+        .requires(&["lifetime::lifetime_annotations"]);
+    topic
+        .lesson("struct_lifetimes", "结构体中的生命周期", struct_lifetimes)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["lifetime", "struct"])
+        .requires(&["lifetime::lifetime_annotations"]);
+    topic
+        .lesson("lifetime_elision", "生命周期省略规则", lifetime_elision)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["lifetime", "elision"])
+        .requires(&["lifetime::lifetime_annotations"]);
+    topic
+        .lesson("method_lifetimes", "方法中的生命周期", method_lifetimes)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["lifetime", "impl"])
+        .requires(&["lifetime::struct_lifetimes", "lifetime::lifetime_elision"]);
+    topic
+        .lesson("static_lifetime", "静态生命周期 'static", static_lifetime)
+        .minutes(5)
+        .tags(&["lifetime", "static"])
+        .requires(&["lifetime::lifetime_basics"]);
+    topic
+        .lesson("lifetime_generic_trait", "生命周期 + 泛型 + Trait Bound", lifetime_generic_trait)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["lifetime", "generics", "trait"])
+        .requires(&["lifetime::lifetime_annotations", "trait_generic::trait_as_parameter"]);
+    topic
+        .lesson("complex_lifetime_scenarios", "复杂的生命周期场景", complex_lifetime_scenarios)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["lifetime"])
+        .requires(&["lifetime::method_lifetimes"]);
+    topic
+        .lesson("lifetime_subtyping", "生命周期子类型化", lifetime_subtyping)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["lifetime", "variance"])
+        .requires(&["lifetime::static_lifetime", "lifetime::lifetime_annotations"]);
+    topic
+        .lesson("common_lifetime_errors", "常见生命周期错误", common_lifetime_errors)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["lifetime"])
+        .requires(&["lifetime::lifetime_basics"]);
+    topic
+        .lesson("lifetime_best_practices", "生命周期最佳实践", lifetime_best_practices)
+        .level(Difficulty::Intermediate)
+        .minutes(3)
+        .tags(&["lifetime"]);
 }
 
 /// 运行所有示例
 pub fn run_all_lifetime_examples() {
-    println!("\n");
-    println!("╔════════════════════════════════════════╗");
-    println!("║   Rust 生命周期 - 完整示例            ║");
-    println!("╚════════════════════════════════════════╝");
-
-    lifetime_basics();
-    lifetime_annotations();
-    lifetime_annotation_details();
-    struct_lifetimes();
-    lifetime_elision();
-    method_lifetimes();
-    static_lifetime();
-    lifetime_generic_trait();
-    complex_lifetime_scenarios();
-    lifetime_subtyping();
-    common_lifetime_errors();
-    lifetime_best_practices();
-
-    println!("\n=== 生命周期示例完成 ===\n");
+    outln!("\n");
+    outln!("╔════════════════════════════════════════╗");
+    outln!("║   Rust 生命周期 - 完整示例            ║");
+    outln!("╚════════════════════════════════════════╝");
+
+    lesson::registry().run_topic("lifetime");
+
+    outln!("\n=== 生命周期示例完成 ===\n");
 }
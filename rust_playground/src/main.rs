@@ -1,18 +1,59 @@
 // 声明模块 - 告诉编译器包含这些模块
+// output 定义了 `out!` / `outln!` 宏，#[macro_use] 让后面声明的模块都能使用
+#[macro_use]
+mod output;
+mod scheduler; // 确定性调度器：让并发示例的输出可以复现
+mod thread_pool; // 可复用的线程池
+mod parallel; // 基于作用域线程的并行 map/reduce
+mod bench; // 简易计时工具
+mod channels; // 通道工具：手写 select、广播通道
+mod counters; // 计数器：Mutex / RwLock / 原子操作 / 分片
+mod executor; // 最小异步运行时：block_on、spawn、定时器
+mod work_stealing; // 工作窃取调度器：每个 worker 一个双端队列
+mod deadlock_detector; // 死锁检测：记录加锁顺序和等待图
+mod sync_primitives; // 用 Mutex + Condvar 实现的信号量、门闩、阻塞队列、屏障
+mod tree; // Rc/Weak 树：增删、换父节点、遍历、打印
+mod leak_tracker; // 泄漏追踪：报告没有被 drop 的对象
+mod graph_printer; // 环安全的图打印：<cycle #n> 与 Graphviz DOT
+mod persistent_list; // 持久化链表：用 Rc 共享尾部的不可变 cons 列表
+mod my_box; // 用 std::alloc 手写的 Box
+mod my_rc; // 手写的 Rc / Weak / Arc
+mod my_refcell; // 手写的 RefCell：冲突时报告借用位置
+mod arena; // 用带代数的下标代替指针的对象池
+mod arena_tree; // Arena 树：用 NodeId 代替 Rc/Weak
+mod arena_graph; // Arena 有向图：BFS、DFS、找环、拓扑排序
+mod rc_list; // 双向链表（安全版）：Rc<RefCell<Node>> + Weak
+mod raw_list; // 双向链表（unsafe 版）：NonNull 裸指针
+mod weak_cache; // 淘汰后还能通过 Weak 找回的 LRU / TTL 缓存
+mod event_bus; // 事件总线：用 Weak 登记订阅者，分发时可以重入
+mod quota; // 配额追踪：可配置阈值、边沿触发、回差、多个配额
+mod cli; // 命令行：按主题运行示例
+// 教学代码里故意保留了很多"反面示例"（未使用的变量、&String 参数、手写 match 等），
+// 只在这些原有的教学模块上放宽对应的 lint，其余模块仍然受 `-D warnings` 的完整检查
+#[allow(dead_code, clippy::approx_constant, clippy::ptr_arg)]
 mod example; // 对应 example.rs
+mod lesson; // 课程注册表：每个示例函数的元数据
+#[allow(clippy::manual_is_multiple_of)]
 mod utils; // 对应 utils.rs
 
 // 新增：Rust 学习难点示例模块
+#[allow(dead_code, unused_variables, clippy::let_and_return, clippy::ptr_arg, clippy::redundant_slicing)]
 mod ownership_examples;
+#[allow(dead_code, unused_variables, unused_assignments, clippy::needless_lifetimes)]
 mod lifetime_examples;
+#[allow(dead_code, unused_variables)]
 mod trait_generic_examples;
+#[allow(dead_code, unused_variables)]
 mod smart_pointer_examples;
+#[allow(unused_variables, clippy::manual_is_multiple_of)]
 mod concurrency_examples;
+mod async_examples;
 
 // 导入模块中的函数和类型
 use example::run_all_examples;
 use utils::{math_utils, multiply, string_utils};
 
+#[allow(dead_code, clippy::manual_map)]
 fn plus_one(x: Option<i32>) -> Option<i32> {
     match x {
         None => None,
@@ -25,6 +66,37 @@ struct EStruct {
 }
 
 fn main() {
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("错误: {}\n", message);
+            cli::print_help();
+            std::process::exit(2);
+        }
+    };
+
+    match command {
+        cli::Command::Playground => playground(),
+        cli::Command::List(options) => cli::print_list(&options),
+        cli::Command::Path => {
+            if let Err(message) = cli::print_learning_path() {
+                eprintln!("错误: {}", message);
+                std::process::exit(1);
+            }
+        }
+        cli::Command::Help => cli::print_help(),
+        cli::Command::Run(options) => {
+            if let Err(message) = cli::run_targets(&options) {
+                eprintln!("错误: {}", message);
+                std::process::exit(2);
+            }
+        }
+    }
+}
+
+/// 不带参数运行时的基础语法演练
+#[allow(unused_variables)]
+fn playground() {
     println!("=== 原有代码示例 ===\n");
 
     let condition = true;
@@ -121,36 +193,26 @@ fn main() {
     println!("║  以下是 5 个最重要且最难的概念                  ║");
     println!("╚══════════════════════════════════════════════════╝\n");
 
-    println!("💡 提示：你可以注释/取消注释下面的函数来运行特定示例\n");
-
-    // 1. 所有权系统示例
-    // ownership_examples::run_all_ownership_examples();
-
-    // 2. 生命周期示例
-    // lifetime_examples::run_all_lifetime_examples();
-
-    // 3. Trait 和泛型示例
-    // trait_generic_examples::run_all_trait_generic_examples();
-
-    // 4. 智能指针示例
-    // smart_pointer_examples::run_all_smart_pointer_examples();
-
-    // 5. 并发编程示例
-    // concurrency_examples::run_all_concurrency_examples();
+    println!("💡 提示：不需要修改源码，用命令行选择要运行的示例：\n");
+    cli::print_help();
+    println!();
 
     println!("\n📚 查看 RUST_LEARNING_GUIDE.md 了解更多学习资源和建议！");
 }
 
+#[allow(dead_code)]
 fn add(x: u32, y: u32) -> u32 {
     x + y
     // x + y; 不能写分号
 }
 
 // 发散函数
+#[allow(dead_code)]
 fn dead_end() -> ! {
     panic!("你已经到了穷途末路，崩溃吧！");
 }
 
+#[allow(dead_code, clippy::empty_loop)]
 fn forever() -> ! {
     loop {
         //...
@@ -185,6 +247,7 @@ fn makes_copy(some_integer: i32) {
 } // 这里，some_integer 移出作用域。不会有特殊操作
 
 // 引用
+#[allow(unused_variables)]
 fn main_reference() {
     let x = 5;
     let y = &x;
@@ -232,6 +295,7 @@ fn add_str(some_string: &mut String, other_string: &str) {
     some_string.push_str(other_string);
 }
 
+#[allow(clippy::ptr_arg)]
 fn calculate_length(s: &String) -> usize {
     s.len()
 }
@@ -0,0 +1,338 @@
+// ============================================
+// MyBox<T> - 用 std::alloc 手写的 Box
+// ============================================
+//
+// `Box<T>` 做的事情其实只有三件：
+// 1. 按 `T` 的大小和对齐向分配器要一块内存，把值写进去
+// 2. 通过 `Deref`/`DerefMut` 把这块内存当成 `T` 来用
+// 3. drop 时先 drop 里面的值，再把内存还给分配器
+//
+// 零大小类型（ZST）不需要内存：分配器不接受大小为 0 的请求，
+// 这时用 `NonNull::dangling()` 当指针，释放时也跳过。
+//
+// `MyBox<dyn Trait>` / `MyBox<[T]>` 的指针是"胖指针"，除了地址还带着 vtable 或长度，
+// 释放时用 `Layout::for_value` 从胖指针里算出当初分配的大小。
+// 标准库的 `Box` 可以自动把 `Box<Circle>` 变成 `Box<dyn Shape>`，
+// 靠的是还没稳定的 `CoerceUnsized`，这里用 `unsize!` 宏代替。
+//
+// 这里的测试不依赖线程和时间，可以用 `cargo +nightly miri test my_box` 检查未定义行为。
+
+use std::alloc::{self, Layout};
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+
+/// 在堆上独占一个 `T`
+pub struct MyBox<T: ?Sized> {
+    ptr: NonNull<T>,
+    /// 告诉编译器（drop 检查）：`MyBox<T>` 拥有一个 `T`
+    _owns: PhantomData<T>,
+}
+
+// 和 Box 一样：里面的值能跨线程，MyBox 就能跨线程
+unsafe impl<T: ?Sized + Send> Send for MyBox<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for MyBox<T> {}
+
+/// 按 `layout` 分配内存；大小为 0 时不分配，返回一个对齐的悬垂指针
+fn allocate(layout: Layout) -> NonNull<u8> {
+    if layout.size() == 0 {
+        // 地址等于对齐值，既非空又满足对齐
+        return NonNull::new(ptr::without_provenance_mut(layout.align())).unwrap();
+    }
+    // SAFETY: layout 的大小不为 0
+    let raw = unsafe { alloc::alloc(layout) };
+    NonNull::new(raw).unwrap_or_else(|| alloc::handle_alloc_error(layout))
+}
+
+impl<T> MyBox<T> {
+    pub fn new(value: T) -> MyBox<T> {
+        let ptr = allocate(Layout::new::<T>()).cast::<T>();
+        // SAFETY: ptr 指向一块大小、对齐都适合 T 的新内存（ZST 不需要内存）
+        unsafe { ptr.as_ptr().write(value) };
+        MyBox {
+            ptr,
+            _owns: PhantomData,
+        }
+    }
+
+    /// 把值移出来，释放堆内存
+    pub fn into_inner(this: MyBox<T>) -> T {
+        let raw = MyBox::into_raw(this);
+        // SAFETY: raw 来自 MyBox::new，值还没有被移走；读出来之后只释放内存，不再 drop 值
+        unsafe {
+            let value = raw.read();
+            deallocate(raw, Layout::new::<T>());
+            value
+        }
+    }
+}
+
+impl<T: ?Sized> MyBox<T> {
+    /// 交出裸指针，之后由调用者负责释放（通常是再交给 `from_raw`）
+    pub fn into_raw(this: MyBox<T>) -> *mut T {
+        let raw = this.ptr.as_ptr();
+        std::mem::forget(this);
+        raw
+    }
+
+    /// # Safety
+    ///
+    /// `raw` 必须来自 `MyBox::into_raw`（可以经过 unsize 转换），并且只能用一次。
+    pub unsafe fn from_raw(raw: *mut T) -> MyBox<T> {
+        MyBox {
+            // SAFETY: 调用者保证 raw 来自 into_raw，不会为空
+            ptr: unsafe { NonNull::new_unchecked(raw) },
+            _owns: PhantomData,
+        }
+    }
+}
+
+/// 只释放内存，不 drop 里面的值
+///
+/// # Safety
+///
+/// `raw` 指向的内存必须由 `allocate(layout)` 分配，之后不能再使用。
+unsafe fn deallocate<T: ?Sized>(raw: *mut T, layout: Layout) {
+    if layout.size() != 0 {
+        // SAFETY: 这块内存正是按这个 layout 分配的
+        unsafe { alloc::dealloc(raw.cast::<u8>(), layout) };
+    }
+}
+
+impl<T: ?Sized> Drop for MyBox<T> {
+    fn drop(&mut self) {
+        // 胖指针带着长度或 vtable，for_value 能算出当初分配的大小；
+        // 必须在 drop 值之前算，drop 之后就不能再通过引用访问它了
+        let layout = Layout::for_value::<T>(self);
+        let raw = self.ptr.as_ptr();
+        // SAFETY: 我们独占这个值；先 drop 值（dyn 时通过 vtable 调用），再释放内存
+        unsafe {
+            ptr::drop_in_place(raw);
+            deallocate(raw, layout);
+        }
+    }
+}
+
+impl<T: ?Sized> Deref for MyBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: ptr 在 MyBox 存活期间一直指向有效的 T
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MyBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: &mut self 保证了独占访问
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T> From<T> for MyBox<T> {
+    fn from(value: T) -> Self {
+        MyBox::new(value)
+    }
+}
+
+/// 把 Vec 里的元素搬到一块刚好装下它们的新内存里
+impl<T> From<Vec<T>> for MyBox<[T]> {
+    fn from(mut vec: Vec<T>) -> Self {
+        let len = vec.len();
+        let layout = Layout::array::<T>(len).expect("切片太大");
+        let data = allocate(layout).cast::<T>();
+        // SAFETY: 两块内存不重叠；拷贝之后把 vec 的长度设为 0，元素的所有权就转给了新内存，
+        // vec drop 时只释放它自己的缓冲区
+        unsafe {
+            ptr::copy_nonoverlapping(vec.as_ptr(), data.as_ptr(), len);
+            vec.set_len(0);
+        }
+        let slice = ptr::slice_from_raw_parts_mut(data.as_ptr(), len);
+        // SAFETY: slice 指向 allocate(Layout::array::<T>(len))，和 for_value 算出的一致
+        unsafe { MyBox::from_raw(slice) }
+    }
+}
+
+impl<T: Clone> From<&[T]> for MyBox<[T]> {
+    fn from(slice: &[T]) -> Self {
+        MyBox::from(slice.to_vec())
+    }
+}
+
+impl<T: Clone> Clone for MyBox<T> {
+    fn clone(&self) -> Self {
+        MyBox::new((**self).clone())
+    }
+}
+
+impl<T: Clone> Clone for MyBox<[T]> {
+    fn clone(&self) -> Self {
+        MyBox::from(&**self)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MyBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for MyBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+/// 把 `MyBox<具体类型>` 转换成 `MyBox<dyn Trait>` 或 `MyBox<[T]>`
+///
+/// ```ignore
+/// let shape: MyBox<dyn Shape> = unsize!(MyBox::new(Circle { r: 1.0 }) => dyn Shape);
+/// let slice: MyBox<[i32]> = unsize!(MyBox::new([1, 2, 3]) => [i32]);
+/// ```
+///
+/// 转换用的是隐式的指针强制转换（不是 `as`），编译器只允许合法的 unsize，
+/// 比如 `*mut Circle → *mut dyn Shape`，写错目标类型会编译失败。
+macro_rules! unsize {
+    ($boxed:expr => $target:ty) => {{
+        // 先拿到具体类型的裸指针，再在 let 处发生 unsize 强制转换
+        let raw = $crate::my_box::MyBox::into_raw($boxed);
+        let raw: *mut $target = raw;
+        // SAFETY: raw 刚从 into_raw 得到，只是加上了元数据，地址和分配都没变
+        unsafe { $crate::my_box::MyBox::<$target>::from_raw(raw) }
+    }};
+}
+pub(crate) use unsize;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// drop 时给计数器加一
+    struct DropCounter<'a>(&'a Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    trait Shape {
+        fn area(&self) -> f64;
+    }
+
+    struct Square(f64);
+
+    impl Shape for Square {
+        fn area(&self) -> f64 {
+            self.0 * self.0
+        }
+    }
+
+    /// `_drops` 只用来统计 drop 的次数
+    struct Named<'a> {
+        name: &'static str,
+        _drops: DropCounter<'a>,
+    }
+
+    impl Shape for Named<'_> {
+        fn area(&self) -> f64 {
+            self.name.len() as f64
+        }
+    }
+
+    #[test]
+    fn test_deref_and_deref_mut() {
+        let mut b = MyBox::new(String::from("hello"));
+        b.push_str(", world");
+        assert_eq!(*b, "hello, world");
+        assert_eq!(b.len(), 12);
+        assert_eq!(MyBox::into_inner(b), "hello, world");
+    }
+
+    #[test]
+    fn test_drop_runs_exactly_once() {
+        let drops = Cell::new(0);
+        let b = MyBox::new(DropCounter(&drops));
+        assert_eq!(drops.get(), 0);
+        drop(b);
+        assert_eq!(drops.get(), 1);
+
+        // into_inner 把值交出去，不在 MyBox 里 drop
+        let inner = MyBox::into_inner(MyBox::from(DropCounter(&drops)));
+        assert_eq!(drops.get(), 1);
+        drop(inner);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn test_zero_sized_and_over_aligned_types() {
+        let unit = MyBox::new(());
+        assert_eq!(*unit, ());
+
+        #[repr(align(64))]
+        #[derive(Clone)]
+        struct Aligned(u8);
+        let aligned = MyBox::new(Aligned(7));
+        assert_eq!((&*aligned as *const Aligned as usize) % 64, 0);
+        assert_eq!(aligned.clone().0, 7);
+    }
+
+    #[test]
+    fn test_clone_is_deep() {
+        let original = MyBox::new(vec![1, 2, 3]);
+        let mut copy = original.clone();
+        copy.push(4);
+        assert_eq!(*original, [1, 2, 3]);
+        assert_eq!(*copy, [1, 2, 3, 4]);
+
+        let shared = Rc::new(5);
+        let boxed = MyBox::new(Rc::clone(&shared));
+        let cloned = boxed.clone();
+        assert_eq!(Rc::strong_count(&shared), 3);
+        drop((boxed, cloned));
+        assert_eq!(Rc::strong_count(&shared), 1);
+    }
+
+    #[test]
+    fn test_slices() {
+        let drops = Cell::new(0);
+        let items: MyBox<[DropCounter]> =
+            MyBox::from(vec![DropCounter(&drops), DropCounter(&drops)]);
+        assert_eq!(items.len(), 2);
+        assert_eq!(drops.get(), 0);
+        drop(items);
+        assert_eq!(drops.get(), 2);
+
+        let mut numbers: MyBox<[i32]> = MyBox::from(&[3, 1, 2][..]);
+        numbers.sort();
+        let copy = numbers.clone();
+        assert_eq!(&*copy, [1, 2, 3]);
+
+        let empty: MyBox<[String]> = MyBox::from(Vec::new());
+        assert!(empty.is_empty());
+
+        let array: MyBox<[u8]> = unsize!(MyBox::new([1u8, 2, 3]) => [u8]);
+        assert_eq!(&*array, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_trait_objects() {
+        let drops = Cell::new(0);
+        let shapes: Vec<MyBox<dyn Shape + '_>> = vec![
+            unsize!(MyBox::new(Square(2.0)) => dyn Shape),
+            unsize!(MyBox::new(Named { name: "abc", _drops: DropCounter(&drops) }) => dyn Shape + '_),
+        ];
+        let areas: Vec<f64> = shapes.iter().map(|shape| shape.area()).collect();
+        assert_eq!(areas, [4.0, 3.0]);
+
+        // 通过 vtable 调用 Named 的 drop，并按它真实的大小释放内存
+        drop(shapes);
+        assert_eq!(drops.get(), 1);
+
+        let message: MyBox<dyn fmt::Display> = unsize!(MyBox::new(42) => dyn fmt::Display);
+        assert_eq!(message.to_string(), "42");
+    }
+}
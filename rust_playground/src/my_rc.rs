@@ -0,0 +1,758 @@
+// ============================================
+// MyRc / MyWeak / MyArc - 手写引用计数
+// ============================================
+//
+// 每个引用计数指针都指向同一个堆上的"计数盒子"：
+//
+//   RcBox { strong, weak, value }
+//
+// - `strong`：有多少个 `MyRc`。降到 0 时 drop `value`
+// - `weak`：有多少个 `MyWeak`，再加 1（所有强引用合起来持有一个"隐式的弱引用"）。
+//   降到 0 时释放整个盒子
+//
+// 这个"隐式的 +1"和标准库一样：最后一个强引用消失时，value 先被 drop，
+// 但盒子还得留着，让剩下的 `MyWeak` 能读到 `strong == 0`，`upgrade` 返回 `None`。
+//
+// API 和 `std::rc` 保持一致（`MyRc::clone(&a)`、`MyRc::downgrade(&a)`、`weak.upgrade()`……），
+// 所以 `tree.rs`、`persistent_list.rs` 和它们的课程都通过 `my_rc::rc` 引入 `Rc`/`Weak`，
+// 默认是标准库的实现，加上 `--cfg my_rc` 就整体换成这里的实现，测试和课程输出应该完全一样：
+//
+//   RUSTFLAGS="--cfg my_rc" cargo test
+//
+// `MyArc` 是线程安全版本：计数换成原子类型，内存顺序的选择和标准库的 `Arc` 相同。
+
+use std::cell::Cell;
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{self, AtomicUsize, Ordering};
+
+/// 弱引用看到的盒子：只有两个计数
+struct Counts<'a, C> {
+    strong: &'a C,
+    weak: &'a C,
+}
+
+/// 随 `--cfg my_rc` 切换的 `Rc`/`Weak`
+pub mod rc {
+    #[cfg(my_rc)]
+    pub use super::{MyRc as Rc, MyWeak as Weak};
+    #[cfg(not(my_rc))]
+    pub use std::rc::{Rc, Weak};
+}
+
+// ==================== 单线程：MyRc / MyWeak ====================
+
+struct RcBox<T> {
+    strong: Cell<usize>,
+    weak: Cell<usize>,
+    /// 强引用归零时手动 drop，盒子本身可能还要留给弱引用
+    value: ManuallyDrop<T>,
+}
+
+/// 单线程引用计数指针，对应 `std::rc::Rc`
+pub struct MyRc<T> {
+    ptr: NonNull<RcBox<T>>,
+    _owns: PhantomData<RcBox<T>>,
+}
+
+/// 不阻止值被释放的弱引用，对应 `std::rc::Weak`
+pub struct MyWeak<T> {
+    /// `MyWeak::new()` 创建的弱引用不指向任何盒子
+    ptr: Option<NonNull<RcBox<T>>>,
+}
+
+impl<T> MyRc<T> {
+    pub fn new(value: T) -> MyRc<T> {
+        let boxed = Box::new(RcBox {
+            strong: Cell::new(1),
+            weak: Cell::new(1), // 强引用共同持有的那一个
+            value: ManuallyDrop::new(value),
+        });
+        MyRc {
+            ptr: NonNull::from(Box::leak(boxed)),
+            _owns: PhantomData,
+        }
+    }
+
+    fn inner(&self) -> &RcBox<T> {
+        // SAFETY: 只要还有一个 MyRc，盒子就不会被释放
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub fn strong_count(this: &MyRc<T>) -> usize {
+        this.inner().strong.get()
+    }
+
+    /// 不算强引用共同持有的那一个
+    pub fn weak_count(this: &MyRc<T>) -> usize {
+        this.inner().weak.get() - 1
+    }
+
+    pub fn downgrade(this: &MyRc<T>) -> MyWeak<T> {
+        let inner = this.inner();
+        inner.weak.set(inner.weak.get() + 1);
+        MyWeak {
+            ptr: Some(this.ptr),
+        }
+    }
+
+    pub fn ptr_eq(a: &MyRc<T>, b: &MyRc<T>) -> bool {
+        a.ptr == b.ptr
+    }
+
+    /// 只有一个强引用、也没有弱引用时，才能拿到 `&mut T`
+    pub fn get_mut(this: &mut MyRc<T>) -> Option<&mut T> {
+        if MyRc::strong_count(this) == 1 && MyRc::weak_count(this) == 0 {
+            // SAFETY: 没有别人能看到这个值
+            Some(unsafe { &mut (*this.ptr.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    /// 只剩这一个强引用时把值拿出来，否则原样退回
+    pub fn try_unwrap(this: MyRc<T>) -> Result<T, MyRc<T>> {
+        if MyRc::strong_count(&this) != 1 {
+            return Err(this);
+        }
+        let this = ManuallyDrop::new(this);
+        let inner = this.inner();
+        // SAFETY: 唯一的强引用；读出来之后 strong 归零，这个值不会再被 drop
+        let value = unsafe { ptr::read(&*inner.value) };
+        inner.strong.set(0);
+        // 交还强引用共同持有的那个弱引用，没有别的弱引用时顺便释放盒子
+        drop(MyWeak {
+            ptr: Some(this.ptr),
+        });
+        Ok(value)
+    }
+}
+
+impl<T: Clone> MyRc<T> {
+    /// 写时复制：值被共享时先克隆一份，再返回新值的 `&mut T`
+    ///
+    /// 有弱引用时也会克隆：原来的值随着这个强引用离开而释放，那些弱引用就此失效。
+    /// （标准库在这种情况下是把值搬到新盒子里，省掉一次克隆，效果相同。）
+    pub fn make_mut(this: &mut MyRc<T>) -> &mut T {
+        if MyRc::get_mut(this).is_none() {
+            *this = MyRc::new((**this).clone());
+        }
+        MyRc::get_mut(this).unwrap()
+    }
+}
+
+impl<T> Clone for MyRc<T> {
+    fn clone(&self) -> Self {
+        let inner = self.inner();
+        inner.strong.set(inner.strong.get() + 1);
+        MyRc {
+            ptr: self.ptr,
+            _owns: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for MyRc<T> {
+    fn drop(&mut self) {
+        let inner = self.inner();
+        inner.strong.set(inner.strong.get() - 1);
+        if inner.strong.get() == 0 {
+            // SAFETY: 最后一个强引用，以后没有人能再访问这个值；
+            // 通过裸指针 drop，不借出 value，弱引用还在读写旁边的计数
+            unsafe { ptr::drop_in_place((&raw mut (*self.ptr.as_ptr()).value).cast::<T>()) };
+            drop(MyWeak {
+                ptr: Some(self.ptr),
+            });
+        }
+    }
+}
+
+impl<T> Deref for MyRc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MyRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for MyRc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T> MyWeak<T> {
+    /// 不指向任何值的弱引用，`upgrade` 总是返回 `None`
+    pub fn new() -> MyWeak<T> {
+        MyWeak { ptr: None }
+    }
+
+    /// 只借出两个计数，不碰 value：弱引用可能在 value 正被 drop 的时候被访问
+    /// （比如子节点里指回父节点的弱引用），这时不能有覆盖整个盒子的引用
+    fn inner(&self) -> Option<Counts<'_, Cell<usize>>> {
+        // SAFETY: 只要还有一个 MyWeak，盒子就不会被释放
+        self.ptr.map(|ptr| unsafe {
+            let ptr = ptr.as_ptr();
+            Counts {
+                strong: &(*ptr).strong,
+                weak: &(*ptr).weak,
+            }
+        })
+    }
+
+    /// 值还活着就得到一个新的强引用
+    pub fn upgrade(&self) -> Option<MyRc<T>> {
+        let inner = self.inner()?;
+        if inner.strong.get() == 0 {
+            return None;
+        }
+        inner.strong.set(inner.strong.get() + 1);
+        Some(MyRc {
+            ptr: self.ptr?,
+            _owns: PhantomData,
+        })
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.inner().map_or(0, |inner| inner.strong.get())
+    }
+
+    /// 值已经释放时返回 0，和标准库一致
+    pub fn weak_count(&self) -> usize {
+        match self.inner() {
+            Some(inner) if inner.strong.get() > 0 => inner.weak.get() - 1,
+            _ => 0,
+        }
+    }
+}
+
+impl<T> Clone for MyWeak<T> {
+    fn clone(&self) -> Self {
+        if let Some(inner) = self.inner() {
+            inner.weak.set(inner.weak.get() + 1);
+        }
+        MyWeak { ptr: self.ptr }
+    }
+}
+
+impl<T> Drop for MyWeak<T> {
+    fn drop(&mut self) {
+        let Some(inner) = self.inner() else {
+            return;
+        };
+        inner.weak.set(inner.weak.get() - 1);
+        if inner.weak.get() == 0 {
+            // SAFETY: 强弱引用都没了；value 已经在 strong 归零时 drop 过，
+            // ManuallyDrop 保证释放盒子时不会再 drop 一次
+            drop(unsafe { Box::from_raw(self.ptr.unwrap().as_ptr()) });
+        }
+    }
+}
+
+impl<T> Default for MyWeak<T> {
+    fn default() -> Self {
+        MyWeak::new()
+    }
+}
+
+impl<T> fmt::Debug for MyWeak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("(Weak)")
+    }
+}
+
+// ==================== 多线程：MyArc / MyArcWeak ====================
+
+struct ArcBox<T> {
+    strong: AtomicUsize,
+    weak: AtomicUsize,
+    value: ManuallyDrop<T>,
+}
+
+/// 计数超过这个值就直接中止进程，防止溢出后回绕到 0 造成释放后使用
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+/// 线程安全的引用计数指针，对应 `std::sync::Arc`
+pub struct MyArc<T> {
+    ptr: NonNull<ArcBox<T>>,
+    _owns: PhantomData<ArcBox<T>>,
+}
+
+/// `MyArc` 的弱引用，对应 `std::sync::Weak`
+pub struct MyArcWeak<T> {
+    ptr: Option<NonNull<ArcBox<T>>>,
+}
+
+// 和 Arc 一样：值要能在线程间共享（Sync）也要能在别的线程被 drop（Send）
+unsafe impl<T: Send + Sync> Send for MyArc<T> {}
+unsafe impl<T: Send + Sync> Sync for MyArc<T> {}
+unsafe impl<T: Send + Sync> Send for MyArcWeak<T> {}
+unsafe impl<T: Send + Sync> Sync for MyArcWeak<T> {}
+
+impl<T> MyArc<T> {
+    pub fn new(value: T) -> MyArc<T> {
+        let boxed = Box::new(ArcBox {
+            strong: AtomicUsize::new(1),
+            weak: AtomicUsize::new(1),
+            value: ManuallyDrop::new(value),
+        });
+        MyArc {
+            ptr: NonNull::from(Box::leak(boxed)),
+            _owns: PhantomData,
+        }
+    }
+
+    fn inner(&self) -> &ArcBox<T> {
+        // SAFETY: 只要还有一个 MyArc，盒子就不会被释放
+        unsafe { self.ptr.as_ref() }
+    }
+
+    /// 其他线程随时可能改变计数，返回值只是一个快照
+    pub fn strong_count(this: &MyArc<T>) -> usize {
+        this.inner().strong.load(Ordering::Acquire)
+    }
+
+    pub fn weak_count(this: &MyArc<T>) -> usize {
+        let weak = this.inner().weak.load(Ordering::Acquire);
+        // get_mut 检查期间 weak 被临时"锁"成 usize::MAX，这时没有弱引用
+        if weak == usize::MAX {
+            0
+        } else {
+            weak - 1
+        }
+    }
+
+    pub fn downgrade(this: &MyArc<T>) -> MyArcWeak<T> {
+        let weak = &this.inner().weak;
+        let mut current = weak.load(Ordering::Relaxed);
+        loop {
+            // get_mut 正在检查唯一性，等它检查完
+            if current == usize::MAX {
+                std::hint::spin_loop();
+                current = weak.load(Ordering::Relaxed);
+                continue;
+            }
+            match weak.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return MyArcWeak {
+                        ptr: Some(this.ptr),
+                    }
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    pub fn ptr_eq(a: &MyArc<T>, b: &MyArc<T>) -> bool {
+        a.ptr == b.ptr
+    }
+
+    /// 只有一个强引用、也没有弱引用时，才能拿到 `&mut T`
+    pub fn get_mut(this: &mut MyArc<T>) -> Option<&mut T> {
+        let inner = this.inner();
+        // 先把 weak 从 1 "锁"成 usize::MAX：检查期间谁也不能 downgrade 出新的弱引用，
+        // 否则可能刚确认完没有弱引用，别的线程就通过另一个强引用 downgrade 再 upgrade
+        if inner
+            .weak
+            .compare_exchange(1, usize::MAX, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return None;
+        }
+        let unique = inner.strong.load(Ordering::Acquire) == 1;
+        inner.weak.store(1, Ordering::Release);
+        if unique {
+            // SAFETY: 唯一的强引用，也没有弱引用
+            Some(unsafe { &mut (*this.ptr.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    /// 只剩这一个强引用时把值拿出来，否则原样退回
+    pub fn try_unwrap(this: MyArc<T>) -> Result<T, MyArc<T>> {
+        if this
+            .inner()
+            .strong
+            .compare_exchange(1, 0, Ordering::Relaxed, Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(this);
+        }
+        // 和 Drop 里一样：看到其他线程在 drop 之前对值做的所有修改
+        atomic::fence(Ordering::Acquire);
+        let this = ManuallyDrop::new(this);
+        // SAFETY: strong 已经从 1 变成 0，没有人能再访问这个值
+        let value = unsafe { ptr::read(&*this.inner().value) };
+        drop(MyArcWeak {
+            ptr: Some(this.ptr),
+        });
+        Ok(value)
+    }
+}
+
+impl<T: Clone> MyArc<T> {
+    /// 写时复制，规则和 [`MyRc::make_mut`] 相同
+    pub fn make_mut(this: &mut MyArc<T>) -> &mut T {
+        if MyArc::get_mut(this).is_none() {
+            *this = MyArc::new((**this).clone());
+        }
+        MyArc::get_mut(this).unwrap()
+    }
+}
+
+impl<T> Clone for MyArc<T> {
+    fn clone(&self) -> Self {
+        // 增加计数不需要同步任何数据：拿到 &self 就说明值还活着
+        let old = self.inner().strong.fetch_add(1, Ordering::Relaxed);
+        if old > MAX_REFCOUNT {
+            std::process::abort();
+        }
+        MyArc {
+            ptr: self.ptr,
+            _owns: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for MyArc<T> {
+    fn drop(&mut self) {
+        // Release：本线程对值的修改要在减计数之前对别的线程可见
+        if self.inner().strong.fetch_sub(1, Ordering::Release) != 1 {
+            return;
+        }
+        // Acquire：最后一个线程要看到其他线程的所有修改，之后才能 drop
+        atomic::fence(Ordering::Acquire);
+        // SAFETY: 最后一个强引用；通过裸指针 drop，别的线程的弱引用可能还在读计数
+        unsafe { ptr::drop_in_place((&raw mut (*self.ptr.as_ptr()).value).cast::<T>()) };
+        drop(MyArcWeak {
+            ptr: Some(self.ptr),
+        });
+    }
+}
+
+impl<T> Deref for MyArc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MyArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for MyArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        (**self).fmt(f)
+    }
+}
+
+impl<T> MyArcWeak<T> {
+    pub fn new() -> MyArcWeak<T> {
+        MyArcWeak { ptr: None }
+    }
+
+    /// 和 `MyWeak::inner` 一样只借出计数
+    fn inner(&self) -> Option<Counts<'_, AtomicUsize>> {
+        // SAFETY: 只要还有一个 MyArcWeak，盒子就不会被释放
+        self.ptr.map(|ptr| unsafe {
+            let ptr = ptr.as_ptr();
+            Counts {
+                strong: &(*ptr).strong,
+                weak: &(*ptr).weak,
+            }
+        })
+    }
+
+    /// 值还活着就得到一个新的强引用
+    ///
+    /// 不能先读再加：读到 1 之后、加一之前，最后一个强引用可能正好被 drop。
+    /// 所以用 CAS 循环，只有在 strong 不为 0 时才把它加一。
+    pub fn upgrade(&self) -> Option<MyArc<T>> {
+        let strong = &self.inner()?.strong;
+        let mut current = strong.load(Ordering::Relaxed);
+        loop {
+            if current == 0 {
+                return None;
+            }
+            if current > MAX_REFCOUNT {
+                std::process::abort();
+            }
+            match strong.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Some(MyArc {
+                        ptr: self.ptr?,
+                        _owns: PhantomData,
+                    })
+                }
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    pub fn strong_count(&self) -> usize {
+        self.inner()
+            .map_or(0, |inner| inner.strong.load(Ordering::Acquire))
+    }
+
+    pub fn weak_count(&self) -> usize {
+        match self.inner() {
+            Some(inner) if inner.strong.load(Ordering::Acquire) > 0 => {
+                inner.weak.load(Ordering::Acquire) - 1
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl<T> Clone for MyArcWeak<T> {
+    fn clone(&self) -> Self {
+        if let Some(inner) = self.inner() {
+            inner.weak.fetch_add(1, Ordering::Relaxed);
+        }
+        MyArcWeak { ptr: self.ptr }
+    }
+}
+
+impl<T> Drop for MyArcWeak<T> {
+    fn drop(&mut self) {
+        let Some(inner) = self.inner() else {
+            return;
+        };
+        if inner.weak.fetch_sub(1, Ordering::Release) == 1 {
+            atomic::fence(Ordering::Acquire);
+            // SAFETY: 强弱引用都没了，value 已经 drop 过
+            drop(unsafe { Box::from_raw(self.ptr.unwrap().as_ptr()) });
+        }
+    }
+}
+
+impl<T> Default for MyArcWeak<T> {
+    fn default() -> Self {
+        MyArcWeak::new()
+    }
+}
+
+impl<T> fmt::Debug for MyArcWeak<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("(Weak)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::{Rc, Weak};
+    use std::sync::{self, Arc};
+    use std::thread;
+
+    /// 同一步操作之后，两边的 (strong, weak) 必须一样
+    fn assert_same<T, U>(std_rc: &Rc<T>, my_rc: &MyRc<U>) {
+        assert_eq!(
+            (Rc::strong_count(std_rc), Rc::weak_count(std_rc)),
+            (MyRc::strong_count(my_rc), MyRc::weak_count(my_rc)),
+        );
+    }
+
+    fn assert_same_weak<T, U>(std_weak: &Weak<T>, my_weak: &MyWeak<U>) {
+        assert_eq!(
+            (std_weak.strong_count(), std_weak.weak_count()),
+            (my_weak.strong_count(), my_weak.weak_count()),
+        );
+        assert_eq!(std_weak.upgrade().is_some(), my_weak.upgrade().is_some());
+    }
+
+    #[test]
+    fn test_rc_count_transitions_match_std() {
+        let std_a = Rc::new(String::from("a"));
+        let my_a = MyRc::new(String::from("a"));
+        assert_same(&std_a, &my_a);
+
+        let (std_b, my_b) = (Rc::clone(&std_a), MyRc::clone(&my_a));
+        assert_same(&std_a, &my_a);
+
+        let (std_w, my_w) = (Rc::downgrade(&std_a), MyRc::downgrade(&my_a));
+        let (std_w2, my_w2) = (std_w.clone(), my_w.clone());
+        assert_same(&std_a, &my_a);
+        assert_same_weak(&std_w, &my_w);
+
+        let std_c = std_w.upgrade().unwrap();
+        let my_c = my_w.upgrade().unwrap();
+        assert_same(&std_c, &my_c);
+
+        drop((std_b, std_c, my_b, my_c));
+        assert_same(&std_a, &my_a);
+
+        drop((std_w2, my_w2));
+        assert_same(&std_a, &my_a);
+
+        drop((std_a, my_a));
+        assert_same_weak(&std_w, &my_w);
+
+        let (std_empty, my_empty) = (Weak::<i32>::new(), MyWeak::<i32>::new());
+        assert_same_weak(&std_empty, &my_empty);
+    }
+
+    #[test]
+    fn test_get_mut_and_make_mut_match_std() {
+        let mut std_a = Rc::new(vec![1]);
+        let mut my_a = MyRc::new(vec![1]);
+        assert!(Rc::get_mut(&mut std_a).is_some());
+        assert!(MyRc::get_mut(&mut my_a).is_some());
+
+        let (std_b, my_b) = (Rc::clone(&std_a), MyRc::clone(&my_a));
+        assert!(Rc::get_mut(&mut std_a).is_none());
+        assert!(MyRc::get_mut(&mut my_a).is_none());
+
+        // 被共享时 make_mut 先复制，b 看到的还是旧值
+        Rc::make_mut(&mut std_a).push(2);
+        MyRc::make_mut(&mut my_a).push(2);
+        assert_eq!((&*std_a, &*std_b), (&*my_a, &*my_b));
+        assert_eq!(*my_b, [1]);
+        assert_same(&std_a, &my_a);
+        assert_same(&std_b, &my_b);
+
+        // 只剩弱引用时：make_mut 之后弱引用失效
+        let (std_w, my_w) = (Rc::downgrade(&std_a), MyRc::downgrade(&my_a));
+        assert!(Rc::get_mut(&mut std_a).is_none());
+        assert!(MyRc::get_mut(&mut my_a).is_none());
+        Rc::make_mut(&mut std_a).push(3);
+        MyRc::make_mut(&mut my_a).push(3);
+        assert_same_weak(&std_w, &my_w);
+        assert_same(&std_a, &my_a);
+        assert_eq!(*my_a, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_try_unwrap_matches_std() {
+        let std_a = Rc::new(String::from("x"));
+        let my_a = MyRc::new(String::from("x"));
+        let (std_b, my_b) = (Rc::clone(&std_a), MyRc::clone(&my_a));
+
+        let std_a = Rc::try_unwrap(std_a).unwrap_err();
+        let my_a = MyRc::try_unwrap(my_a).unwrap_err();
+        assert_same(&std_a, &my_a);
+
+        let (std_w, my_w) = (Rc::downgrade(&std_a), MyRc::downgrade(&my_a));
+        drop((std_b, my_b));
+        assert_eq!(Rc::try_unwrap(std_a).unwrap(), "x");
+        assert_eq!(MyRc::try_unwrap(my_a).unwrap(), "x");
+        assert_same_weak(&std_w, &my_w);
+    }
+
+    #[test]
+    fn test_value_dropped_once_and_cycles_broken_by_weak() {
+        struct Node {
+            parent: RefCell<MyWeak<Node>>,
+            children: RefCell<Vec<MyRc<Node>>>,
+            dropped: Rc<RefCell<u32>>,
+        }
+        impl Drop for Node {
+            fn drop(&mut self) {
+                *self.dropped.borrow_mut() += 1;
+            }
+        }
+
+        let dropped = Rc::new(RefCell::new(0));
+        let node = || {
+            MyRc::new(Node {
+                parent: RefCell::new(MyWeak::new()),
+                children: RefCell::new(vec![]),
+                dropped: Rc::clone(&dropped),
+            })
+        };
+        let parent = node();
+        let child = node();
+        *child.parent.borrow_mut() = MyRc::downgrade(&parent);
+        parent.children.borrow_mut().push(MyRc::clone(&child));
+        let weak_child = MyRc::downgrade(&child);
+        drop(child);
+
+        assert!(weak_child
+            .upgrade()
+            .unwrap()
+            .parent
+            .borrow()
+            .upgrade()
+            .is_some());
+        drop(parent);
+        assert_eq!(*dropped.borrow(), 2);
+        assert!(weak_child.upgrade().is_none());
+        assert_eq!(weak_child.weak_count(), 0);
+    }
+
+    #[test]
+    fn test_arc_count_transitions_match_std() {
+        let std_a = Arc::new(5);
+        let mut my_a = MyArc::new(5);
+        let (std_w, my_w) = (Arc::downgrade(&std_a), MyArc::downgrade(&my_a));
+        assert_eq!(
+            (Arc::strong_count(&std_a), Arc::weak_count(&std_a)),
+            (MyArc::strong_count(&my_a), MyArc::weak_count(&my_a)),
+        );
+        assert!(MyArc::get_mut(&mut my_a).is_none());
+        drop(std_a);
+        *MyArc::make_mut(&mut my_a) += 1;
+        assert_eq!(std_w.upgrade().is_none(), my_w.upgrade().is_none());
+        assert_eq!(
+            (std_w.strong_count(), std_w.weak_count()),
+            (my_w.strong_count(), my_w.weak_count()),
+        );
+        assert_eq!(MyArc::try_unwrap(my_a).unwrap(), 6);
+        assert_eq!(
+            sync::Weak::<i32>::new().weak_count(),
+            MyArcWeak::<i32>::new().weak_count()
+        );
+    }
+
+    #[test]
+    fn test_arc_across_threads() {
+        let shared = MyArc::new(vec![1, 2, 3]);
+        let weak = MyArc::downgrade(&shared);
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let local = MyArc::clone(&shared);
+                let weak = weak.clone();
+                thread::spawn(move || {
+                    for _ in 0..50 {
+                        let again = weak.upgrade().unwrap();
+                        assert_eq!(again.len(), 3);
+                        drop(MyArc::clone(&local));
+                    }
+                    local.iter().sum::<i32>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 6);
+        }
+        assert_eq!(MyArc::strong_count(&shared), 1);
+        assert_eq!(MyArc::weak_count(&shared), 1);
+        drop(shared);
+        assert!(weak.upgrade().is_none());
+    }
+}
@@ -0,0 +1,382 @@
+// ============================================
+// MyRefCell - 手写 RefCell，冲突时告诉你是谁借走了
+// ============================================
+//
+// RefCell 把借用检查从编译期挪到了运行期：内部一个计数记录当前的借用状态
+//
+//   0    没有借用
+//   n>0  有 n 个不可变借用（Ref）
+//   -1   有一个可变借用（RefMut）
+//
+// `borrow` / `borrow_mut` 检查计数，冲突就 panic；返回的守卫（guard）drop 时把计数改回去。
+//
+// 标准库的 panic 信息只说 "already borrowed"，不说是谁借走的。
+// 这里在 debug 构建（`debug_assertions`）下借助 `#[track_caller]` 记下每个还没归还的借用的源码位置，
+// 冲突时把它们写进 panic 信息；release 构建不记录，守卫也不会变大。
+
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+#[cfg(debug_assertions)]
+use std::panic::Location;
+
+const UNUSED: isize = 0;
+const WRITING: isize = -1;
+
+/// 运行时检查借用规则的可变容器，对应 `std::cell::RefCell`
+pub struct MyRefCell<T> {
+    borrow: Cell<isize>,
+    /// 还没归还的借用是在哪里借出的，按借出顺序排列
+    #[cfg(debug_assertions)]
+    borrowed_at: UnsafeCell<Vec<&'static Location<'static>>>,
+    value: UnsafeCell<T>,
+}
+
+/// `try_borrow` 失败：已经有一个可变借用
+#[derive(Debug)]
+pub struct BorrowError {
+    #[cfg(debug_assertions)]
+    borrowed_at: Vec<&'static Location<'static>>,
+}
+
+/// `try_borrow_mut` 失败：已经有借用（可变或不可变）
+#[derive(Debug)]
+pub struct BorrowMutError {
+    #[cfg(debug_assertions)]
+    borrowed_at: Vec<&'static Location<'static>>,
+}
+
+impl<T> MyRefCell<T> {
+    pub fn new(value: T) -> MyRefCell<T> {
+        MyRefCell {
+            borrow: Cell::new(UNUSED),
+            #[cfg(debug_assertions)]
+            borrowed_at: UnsafeCell::new(Vec::new()),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// 不可变借用
+    ///
+    /// # Panics
+    ///
+    /// 已经有可变借用时 panic，debug 构建下信息里带着那个借用的位置。
+    #[track_caller]
+    pub fn borrow(&self) -> Ref<'_, T> {
+        match self.try_borrow() {
+            Ok(guard) => guard,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[track_caller]
+    pub fn try_borrow(&self) -> Result<Ref<'_, T>, BorrowError> {
+        let count = self.borrow.get();
+        if count == WRITING {
+            return Err(BorrowError {
+                #[cfg(debug_assertions)]
+                borrowed_at: self.outstanding(),
+            });
+        }
+        self.borrow.set(count + 1);
+        Ok(Ref {
+            cell: self,
+            #[cfg(debug_assertions)]
+            location: self.record(Location::caller()),
+        })
+    }
+
+    /// 可变借用
+    ///
+    /// # Panics
+    ///
+    /// 已经有任何借用时 panic，debug 构建下信息里列出所有还没归还的借用的位置。
+    #[track_caller]
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        match self.try_borrow_mut() {
+            Ok(guard) => guard,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    #[track_caller]
+    pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, BorrowMutError> {
+        if self.borrow.get() != UNUSED {
+            return Err(BorrowMutError {
+                #[cfg(debug_assertions)]
+                borrowed_at: self.outstanding(),
+            });
+        }
+        self.borrow.set(WRITING);
+        Ok(RefMut {
+            cell: self,
+            #[cfg(debug_assertions)]
+            location: self.record(Location::caller()),
+        })
+    }
+
+    /// 换进一个新值，返回旧值；和 `borrow_mut` 一样会检查借用
+    #[track_caller]
+    pub fn replace(&self, value: T) -> T {
+        std::mem::replace(&mut *self.borrow_mut(), value)
+    }
+
+    /// 独占了容器就不需要运行时检查
+    pub fn get_mut(&mut self) -> &mut T {
+        self.value.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    #[cfg(debug_assertions)]
+    fn record(&self, location: &'static Location<'static>) -> &'static Location<'static> {
+        // SAFETY: MyRefCell 不是 Sync，这个 Vec 只在 record/forget/outstanding 里短暂地访问，
+        // 这些函数不会互相调用，也不会调用外部代码
+        unsafe { (*self.borrowed_at.get()).push(location) };
+        location
+    }
+
+    #[cfg(debug_assertions)]
+    fn forget(&self, location: &'static Location<'static>) {
+        // SAFETY: 同 record
+        let borrowed_at = unsafe { &mut *self.borrowed_at.get() };
+        // 同一个位置可能借出了好几次（比如在循环里），去掉哪一个都一样
+        if let Some(index) = borrowed_at.iter().position(|&l| l == location) {
+            borrowed_at.remove(index);
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    fn outstanding(&self) -> Vec<&'static Location<'static>> {
+        // SAFETY: 同 record
+        unsafe { (*self.borrowed_at.get()).clone() }
+    }
+}
+
+impl<T: Default> Default for MyRefCell<T> {
+    fn default() -> Self {
+        MyRefCell::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MyRefCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.try_borrow() {
+            Ok(value) => f.debug_struct("MyRefCell").field("value", &*value).finish(),
+            Err(_) => f.write_str("MyRefCell { <borrowed> }"),
+        }
+    }
+}
+
+/// 把借用位置写成 `（借用位置: a.rs:1:2, b.rs:3:4）`，release 构建下什么也不写
+#[cfg(debug_assertions)]
+fn write_locations(f: &mut fmt::Formatter, locations: &[&Location]) -> fmt::Result {
+    if locations.is_empty() {
+        return Ok(());
+    }
+    let joined: Vec<String> = locations.iter().map(|l| l.to_string()).collect();
+    write!(f, "（借用位置: {}）", joined.join(", "))
+}
+
+impl fmt::Display for BorrowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("MyRefCell 已经被可变借用")?;
+        #[cfg(debug_assertions)]
+        write_locations(f, &self.borrowed_at)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for BorrowMutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("MyRefCell 已经被借用")?;
+        #[cfg(debug_assertions)]
+        write_locations(f, &self.borrowed_at)?;
+        Ok(())
+    }
+}
+
+impl std::error::Error for BorrowError {}
+impl std::error::Error for BorrowMutError {}
+
+/// `borrow` 返回的守卫，drop 时归还借用
+pub struct Ref<'b, T> {
+    cell: &'b MyRefCell<T>,
+    #[cfg(debug_assertions)]
+    location: &'static Location<'static>,
+}
+
+impl<T> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: 守卫活着期间计数大于 0，不会有可变借用
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T> Drop for Ref<'_, T> {
+    fn drop(&mut self) {
+        self.cell.borrow.set(self.cell.borrow.get() - 1);
+        #[cfg(debug_assertions)]
+        self.cell.forget(self.location);
+    }
+}
+
+/// `borrow_mut` 返回的守卫，drop 时归还借用
+pub struct RefMut<'b, T> {
+    cell: &'b MyRefCell<T>,
+    #[cfg(debug_assertions)]
+    location: &'static Location<'static>,
+}
+
+impl<T> Deref for RefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: 守卫活着期间计数是 WRITING，只有这一个借用
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T> DerefMut for RefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: 同上，而且 &mut self 保证不会同时借出两次
+        unsafe { &mut *self.cell.value.get() }
+    }
+}
+
+impl<T> Drop for RefMut<'_, T> {
+    fn drop(&mut self) {
+        self.cell.borrow.set(UNUSED);
+        #[cfg(debug_assertions)]
+        self.cell.forget(self.location);
+    }
+}
+
+macro_rules! forward_fmt {
+    ($($guard:ident),*) => {$(
+        impl<T: fmt::Debug> fmt::Debug for $guard<'_, T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                (**self).fmt(f)
+            }
+        }
+
+        impl<T: fmt::Display> fmt::Display for $guard<'_, T> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                (**self).fmt(f)
+            }
+        }
+    )*};
+}
+
+forward_fmt!(Ref, RefMut);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+
+    /// 运行 `f`，返回它 panic 时的信息
+    fn panic_message(f: impl FnOnce()) -> String {
+        let payload = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => payload.downcast::<&str>().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_shared_borrows_coexist_and_exclude_writers() {
+        let cell = MyRefCell::new(vec![1, 2]);
+        let a = cell.borrow();
+        let b = cell.borrow();
+        assert_eq!((a.len(), b.len()), (2, 2));
+        assert!(cell.try_borrow_mut().is_err());
+        drop(a);
+        assert!(cell.try_borrow_mut().is_err());
+        drop(b);
+
+        cell.borrow_mut().push(3);
+        assert_eq!(*cell.borrow(), [1, 2, 3]);
+        assert_eq!(format!("{:?}", cell), "MyRefCell { value: [1, 2, 3] }");
+    }
+
+    #[test]
+    fn test_writer_excludes_everyone() {
+        let cell = MyRefCell::new(String::from("a"));
+        {
+            let mut writer = cell.borrow_mut();
+            writer.push('b');
+            assert!(cell.try_borrow().is_err());
+            assert!(cell.try_borrow_mut().is_err());
+            assert_eq!(format!("{:?}", cell), "MyRefCell { <borrowed> }");
+        }
+        assert_eq!(cell.replace(String::from("c")), "ab");
+        assert_eq!(cell.into_inner(), "c");
+    }
+
+    #[test]
+    fn test_get_mut_skips_the_check() {
+        let mut cell = MyRefCell::new(1);
+        *cell.get_mut() += 1;
+        assert_eq!(*cell.borrow(), 2);
+    }
+
+    #[test]
+    fn test_panic_message_points_at_the_outstanding_borrow() {
+        let cell = MyRefCell::new(0);
+        let line = line!() + 1;
+        let _reader = cell.borrow();
+        let message = panic_message(|| {
+            let _writer = cell.borrow_mut();
+        });
+        assert!(message.starts_with("MyRefCell 已经被借用"), "{}", message);
+        if cfg!(debug_assertions) {
+            assert!(
+                message.contains(&format!("（借用位置: {}:{}:", file!(), line)),
+                "{}",
+                message
+            );
+        }
+
+        drop(_reader);
+        let line = line!() + 1;
+        let _writer = cell.borrow_mut();
+        let message = panic_message(|| {
+            let _ = cell.borrow();
+        });
+        assert!(
+            message.starts_with("MyRefCell 已经被可变借用"),
+            "{}",
+            message
+        );
+        if cfg!(debug_assertions) {
+            assert!(
+                message.contains(&format!("{}:{}:", file!(), line)),
+                "{}",
+                message
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_returned_borrows_are_forgotten() {
+        let cell = MyRefCell::new(0);
+        let first = cell.borrow();
+        let second_line = line!() + 1;
+        let second = cell.borrow();
+        drop(first);
+
+        let err = cell.try_borrow_mut().unwrap_err();
+        assert_eq!(err.borrowed_at.len(), 1);
+        assert_eq!(err.borrowed_at[0].line(), second_line);
+
+        drop(second);
+        assert!(cell.try_borrow_mut().is_ok());
+        assert!(cell.outstanding().is_empty());
+    }
+}
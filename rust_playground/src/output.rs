@@ -0,0 +1,159 @@
+// ============================================
+// 示例输出通道 - 可替换的输出目标（sink）
+// ============================================
+//
+// 示例函数不直接用 `println!`，而是用本模块的 `outln!` / `out!`：
+// - 平时没有安装 sink，输出直接写到终端（stdout）
+// - 测试里用 `capture` 临时装上一个内存缓冲区，拿到示例打印的全部文本
+//
+// sink 是全局的而不是线程局部的，这样并发示例里 spawn 出来的线程
+// 打印的内容也会被一起收集。
+
+use std::fmt;
+use std::io::{self, Write};
+#[cfg(test)]
+use std::sync::Arc;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// 输出目标：任何可以跨线程使用的 `Write`
+pub type Sink = Box<dyn Write + Send>;
+
+static SINK: Mutex<Option<Sink>> = Mutex::new(None);
+
+/// `capture` 互斥执行，避免并行运行的测试把输出写进彼此的缓冲区
+#[cfg(test)]
+static CAPTURE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 类似 `print!`，但写到当前的 sink
+macro_rules! out {
+    ($($arg:tt)*) => {
+        $crate::output::write_fmt(format_args!($($arg)*))
+    };
+}
+
+/// 类似 `println!`，但写到当前的 sink
+macro_rules! outln {
+    () => {
+        $crate::output::write_fmt(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::output::write_fmt(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    // 示例里可能故意 panic，锁被污染后仍然继续使用
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// `out!` / `outln!` 的实现：有 sink 就写 sink，否则写 stdout
+pub fn write_fmt(args: fmt::Arguments) {
+    let mut sink = lock(&SINK);
+    let result = match sink.as_mut() {
+        Some(sink) => sink.write_fmt(args),
+        None => io::stdout().write_fmt(args),
+    };
+    if let Err(e) = result {
+        drop(sink);
+        panic!("写入示例输出失败: {}", e);
+    }
+}
+
+/// 退出作用域时换回原来的 sink（包括 panic 展开时）
+#[cfg(test)]
+struct RestoreSink(Option<Sink>);
+
+#[cfg(test)]
+impl Drop for RestoreSink {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        let mut sink = lock(&SINK);
+        if let Some(current) = sink.as_mut() {
+            let _ = current.flush();
+        }
+        *sink = previous;
+    }
+}
+
+/// 在 `f` 运行期间把示例输出写到 `sink`，结束后恢复原来的输出目标
+#[cfg(test)]
+pub fn with_sink<F, R>(sink: Sink, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let previous = lock(&SINK).replace(sink);
+    let _restore = RestoreSink(previous);
+    f()
+}
+
+/// 可以克隆、跨线程共享的内存缓冲区
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+#[cfg(test)]
+impl SharedBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 目前为止写入的全部文本
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&lock(&self.0)).into_owned()
+    }
+}
+
+#[cfg(test)]
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        lock(&self.0).extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 运行 `f` 并返回它通过 `out!` / `outln!` 打印的全部文本
+///
+/// 同一时刻只有一个 `capture` 在运行，所以不能嵌套调用。
+#[cfg(test)]
+pub fn capture<F>(f: F) -> String
+where
+    F: FnOnce(),
+{
+    let _serial = lock(&CAPTURE_LOCK);
+    let buffer = SharedBuffer::new();
+    with_sink(Box::new(buffer.clone()), f);
+    buffer.contents()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_capture_collects_output_from_all_threads() {
+        let text = capture(|| {
+            out!("a");
+            outln!("b{}", 1);
+            thread::spawn(|| outln!("from thread")).join().unwrap();
+            outln!();
+        });
+        assert_eq!(text, "ab1\nfrom thread\n\n");
+    }
+
+    #[test]
+    fn test_sink_is_restored_after_panic() {
+        let text = capture(|| {
+            let result = std::panic::catch_unwind(|| {
+                with_sink(Box::new(SharedBuffer::new()), || panic!("boom"))
+            });
+            assert!(result.is_err());
+            outln!("back to outer");
+        });
+        assert_eq!(text, "back to outer\n");
+    }
+}
@@ -2,35 +2,37 @@
 // Rust 所有权系统 - 深度示例
 // ============================================
 
+use crate::lesson::{self, Difficulty, Registry};
+
 /// 演示所有权的基本规则
 pub fn basic_ownership() {
-    println!("\n=== 1. 基本所有权 ===");
+    outln!("\n=== 1. 基本所有权 ===");
 
     // 所有权规则1: 每个值都有一个所有者
     let s1 = String::from("hello");
-    println!("s1 = {}", s1);
+    outln!("s1 = {}", s1);
 
     // 所有权规则2: 值被移动后，原变量不可用
     let s2 = s1; // s1 的所有权移动到 s2
-    println!("s2 = {}", s2);
-    // println!("s1 = {}", s1); // ❌ 编译错误！s1 已经失效
+    outln!("s2 = {}", s2);
+    // outln!("s1 = {}", s1); // ❌ 编译错误！s1 已经失效
 
     // 所有权规则3: 作用域结束时，值被释放
     {
         let s3 = String::from("temporary");
-        println!("s3 = {}", s3);
+        outln!("s3 = {}", s3);
     } // s3 在这里被释放
-    // println!("{}", s3); // ❌ s3 已经不存在
+    // outln!("{}", s3); // ❌ s3 已经不存在
 }
 
 /// Copy trait vs Move 语义
 pub fn copy_vs_move() {
-    println!("\n=== 2. Copy vs Move ===");
+    outln!("\n=== 2. Copy vs Move ===");
 
     // 实现了 Copy trait 的类型（栈上的数据）
     let x = 5;
     let y = x; // 这是复制，不是移动
-    println!("x = {}, y = {}", x, y); // ✅ x 和 y 都可用
+    outln!("x = {}, y = {}", x, y); // ✅ x 和 y 都可用
 
     // 常见的 Copy 类型：
     // - 所有整数类型：i32, u32, i64 等
@@ -40,67 +42,67 @@ pub fn copy_vs_move() {
     // - 元组（如果所有元素都是 Copy）
     let tuple = (1, 2.5, 'a');
     let tuple2 = tuple;
-    println!("tuple = {:?}, tuple2 = {:?}", tuple, tuple2);
+    outln!("tuple = {:?}, tuple2 = {:?}", tuple, tuple2);
 
     // 没有实现 Copy trait 的类型（堆上的数据）
     let s1 = String::from("hello");
     let s2 = s1; // 这是移动
-    // println!("s1 = {}", s1); // ❌ s1 已失效
-    println!("s2 = {}", s2);
+    // outln!("s1 = {}", s1); // ❌ s1 已失效
+    outln!("s2 = {}", s2);
 
     // Vec 也是堆数据
     let v1 = vec![1, 2, 3];
     let v2 = v1; // 移动
-    // println!("{:?}", v1); // ❌ v1 已失效
-    println!("v2 = {:?}", v2);
+    // outln!("{:?}", v1); // ❌ v1 已失效
+    outln!("v2 = {:?}", v2);
 }
 
 /// Clone: 深拷贝
 pub fn clone_example() {
-    println!("\n=== 3. 使用 Clone 进行深拷贝 ===");
+    outln!("\n=== 3. 使用 Clone 进行深拷贝 ===");
 
     let s1 = String::from("hello");
     let s2 = s1.clone(); // 显式深拷贝
 
-    println!("s1 = {}, s2 = {}", s1, s2); // ✅ 两个都可用
+    outln!("s1 = {}, s2 = {}", s1, s2); // ✅ 两个都可用
 
     // Clone 的代价
     let large_vec = vec![1; 1000000]; // 100万个元素
     let cloned = large_vec.clone(); // 这会复制100万个元素！
-    println!("Original length: {}, Cloned length: {}",
+    outln!("Original length: {}, Cloned length: {}",
              large_vec.len(), cloned.len());
 }
 
 /// 函数和所有权
 pub fn functions_and_ownership() {
-    println!("\n=== 4. 函数和所有权 ===");
+    outln!("\n=== 4. 函数和所有权 ===");
 
     let s = String::from("hello");
 
     // 将 s 传递给函数，所有权被移动
     takes_ownership(s);
-    // println!("{}", s); // ❌ s 已经被移动
+    // outln!("{}", s); // ❌ s 已经被移动
 
     let x = 5;
     makes_copy(x); // x 是 i32，实现了 Copy
-    println!("x = {}", x); // ✅ x 仍然可用
+    outln!("x = {}", x); // ✅ x 仍然可用
 
     // 函数返回所有权
     let s1 = gives_ownership();
-    println!("s1 from function: {}", s1);
+    outln!("s1 from function: {}", s1);
 
     let s2 = String::from("hello");
     let s3 = takes_and_gives_back(s2);
-    // println!("{}", s2); // ❌ s2 已被移动
-    println!("s3 = {}", s3);
+    // outln!("{}", s2); // ❌ s2 已被移动
+    outln!("s3 = {}", s3);
 }
 
 fn takes_ownership(some_string: String) {
-    println!("takes_ownership: {}", some_string);
+    outln!("takes_ownership: {}", some_string);
 } // some_string 在这里被释放
 
 fn makes_copy(some_integer: i32) {
-    println!("makes_copy: {}", some_integer);
+    outln!("makes_copy: {}", some_integer);
 }
 
 fn gives_ownership() -> String {
@@ -114,14 +116,14 @@ fn takes_and_gives_back(a_string: String) -> String {
 
 /// 借用（Borrowing）- 不转移所有权
 pub fn borrowing_basics() {
-    println!("\n=== 5. 借用（Borrowing）===");
+    outln!("\n=== 5. 借用（Borrowing）===");
 
     let s1 = String::from("hello");
 
     // & 创建引用，不获取所有权
     let len = calculate_length(&s1);
 
-    println!("'{}' 的长度是 {}", s1, len); // ✅ s1 仍然可用
+    outln!("'{}' 的长度是 {}", s1, len); // ✅ s1 仍然可用
 }
 
 fn calculate_length(s: &String) -> usize {
@@ -130,14 +132,14 @@ fn calculate_length(s: &String) -> usize {
 
 /// 可变引用
 pub fn mutable_references() {
-    println!("\n=== 6. 可变引用 ===");
+    outln!("\n=== 6. 可变引用 ===");
 
     let mut s = String::from("hello");
 
     // 可变引用
     change(&mut s);
 
-    println!("s = {}", s); // "hello, world"
+    outln!("s = {}", s); // "hello, world"
 }
 
 fn change(some_string: &mut String) {
@@ -146,60 +148,60 @@ fn change(some_string: &mut String) {
 
 /// 借用规则
 pub fn borrowing_rules() {
-    println!("\n=== 7. 借用规则 ===");
+    outln!("\n=== 7. 借用规则 ===");
 
     let mut s = String::from("hello");
 
     // 规则1: 可以有多个不可变引用
     let r1 = &s;
     let r2 = &s;
-    println!("r1 = {}, r2 = {}", r1, r2);
+    outln!("r1 = {}, r2 = {}", r1, r2);
     // r1 和 r2 的作用域结束
 
     // 规则2: 只能有一个可变引用
     let r3 = &mut s;
     r3.push_str(" world");
     // let r4 = &mut s; // ❌ 不能同时有两个可变引用
-    println!("r3 = {}", r3);
+    outln!("r3 = {}", r3);
 
     // 规则3: 不能同时有可变和不可变引用
     let mut s2 = String::from("hello");
     let r1 = &s2; // 不可变引用
     let r2 = &s2; // 不可变引用
-    println!("{} and {}", r1, r2);
+    outln!("{} and {}", r1, r2);
     // r1 和 r2 不再使用
 
     let r3 = &mut s2; // ✅ 可变引用
     r3.push_str(" world");
-    println!("{}", r3);
+    outln!("{}", r3);
 }
 
 /// 非词法作用域生命周期 (NLL)
 pub fn non_lexical_lifetimes() {
-    println!("\n=== 8. 非词法作用域生命周期 (NLL) ===");
+    outln!("\n=== 8. 非词法作用域生命周期 (NLL) ===");
 
     let mut s = String::from("hello");
 
     let r1 = &s;
     let r2 = &s;
-    println!("{} and {}", r1, r2);
+    outln!("{} and {}", r1, r2);
     // r1 和 r2 在这里最后一次使用，之后就失效了
 
     let r3 = &mut s; // ✅ 没问题！r1 和 r2 已经不再使用
     r3.push_str(" world");
-    println!("{}", r3);
+    outln!("{}", r3);
 }
 
 /// 悬垂引用（Dangling References）
 pub fn dangling_reference_example() {
-    println!("\n=== 9. 防止悬垂引用 ===");
+    outln!("\n=== 9. 防止悬垂引用 ===");
 
     // 下面的代码会编译失败
     // let reference_to_nothing = dangle();
 
     // 正确的做法：返回所有权
     let s = no_dangle();
-    println!("no_dangle: {}", s);
+    outln!("no_dangle: {}", s);
 }
 
 // fn dangle() -> &String { // ❌ 返回悬垂引用
@@ -214,7 +216,7 @@ fn no_dangle() -> String {
 
 /// 切片（Slice）- 特殊的引用
 pub fn slice_examples() {
-    println!("\n=== 10. 切片（Slice）===");
+    outln!("\n=== 10. 切片（Slice）===");
 
     let s = String::from("hello world");
 
@@ -223,7 +225,7 @@ pub fn slice_examples() {
     let world = &s[6..11]; // 或 &s[6..]
     let whole = &s[..];    // 整个字符串
 
-    println!("hello: {}, world: {}, whole: {}", hello, world, whole);
+    outln!("hello: {}, world: {}, whole: {}", hello, world, whole);
 
     // 字符串字面量就是切片
     let s: &str = "hello"; // 类型是 &str
@@ -231,12 +233,12 @@ pub fn slice_examples() {
     // 数组切片
     let a = [1, 2, 3, 4, 5];
     let slice = &a[1..3]; // [2, 3]
-    println!("array slice: {:?}", slice);
+    outln!("array slice: {:?}", slice);
 
     // 实用函数：获取第一个单词
     let s = String::from("hello world");
     let word = first_word(&s);
-    println!("first word: {}", word);
+    outln!("first word: {}", word);
 }
 
 fn first_word(s: &str) -> &str {
@@ -253,7 +255,7 @@ fn first_word(s: &str) -> &str {
 
 /// 所有权与数据结构
 pub fn ownership_with_structs() {
-    println!("\n=== 11. 结构体中的所有权 ===");
+    outln!("\n=== 11. 结构体中的所有权 ===");
 
     #[derive(Debug)]
     struct User {
@@ -270,33 +272,86 @@ pub fn ownership_with_structs() {
         sign_in_count: 1,
     };
 
-    println!("user1: {:?}", user1);
+    outln!("user1: {:?}", user1);
 
     // 部分移动
     let email = user1.email; // email 被移动
-    // println!("{}", user1.email); // ❌ 不能使用
-    println!("username: {}", user1.username); // ✅ username 仍然可用
-    println!("moved email: {}", email);
+    // outln!("{}", user1.email); // ❌ 不能使用
+    outln!("username: {}", user1.username); // ✅ username 仍然可用
+    outln!("moved email: {}", email);
+}
+
+/// 登记本模块的示例
+pub fn register(registry: &mut Registry) {
+    let mut topic = registry.topic("ownership", "所有权系统", run_all_ownership_examples);
+
+    topic
+        .lesson("basic_ownership", "基本所有权", basic_ownership)
+        .minutes(5)
+        .tags(&["move", "scope"]);
+    topic
+        .lesson("copy_vs_move", "Copy vs Move", copy_vs_move)
+        .minutes(5)
+        .tags(&["copy", "move"])
+        .requires(&["ownership::basic_ownership"]);
+    topic
+        .lesson("clone_example", "使用 Clone 进行深拷贝", clone_example)
+        .minutes(3)
+        .tags(&["clone"])
+        .requires(&["ownership::copy_vs_move"]);
+    topic
+        .lesson("functions_and_ownership", "函数和所有权", functions_and_ownership)
+        .minutes(5)
+        .tags(&["move", "fn"])
+        .requires(&["ownership::basic_ownership"]);
+    topic
+        .lesson("borrowing_basics", "借用", borrowing_basics)
+        .minutes(5)
+        .tags(&["borrow"])
+        .requires(&["ownership::functions_and_ownership"]);
+    topic
+        .lesson("mutable_references", "可变引用", mutable_references)
+        .minutes(5)
+        .tags(&["borrow", "mut"])
+        .requires(&["ownership::borrowing_basics"]);
+    topic
+        .lesson("borrowing_rules", "借用规则", borrowing_rules)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["borrow"])
+        .requires(&["ownership::mutable_references"]);
+    topic
+        .lesson("non_lexical_lifetimes", "非词法作用域生命周期", non_lexical_lifetimes)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["borrow", "nll"])
+        .requires(&["ownership::borrowing_rules"]);
+    topic
+        .lesson("dangling_reference_example", "防止悬垂引用", dangling_reference_example)
+        .minutes(5)
+        .tags(&["borrow"])
+        .requires(&["ownership::borrowing_basics"]);
+    topic
+        .lesson("slice_examples", "切片", slice_examples)
+        .minutes(8)
+        .tags(&["slice", "str"])
+        .requires(&["ownership::borrowing_basics"]);
+    topic
+        .lesson("ownership_with_structs", "结构体中的所有权", ownership_with_structs)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["struct", "move"])
+        .requires(&["ownership::copy_vs_move"]);
 }
 
 /// 运行所有示例
 pub fn run_all_ownership_examples() {
-    println!("\n");
-    println!("╔════════════════════════════════════════╗");
-    println!("║   Rust 所有权系统 - 完整示例          ║");
-    println!("╚════════════════════════════════════════╝");
-
-    basic_ownership();
-    copy_vs_move();
-    clone_example();
-    functions_and_ownership();
-    borrowing_basics();
-    mutable_references();
-    borrowing_rules();
-    non_lexical_lifetimes();
-    dangling_reference_example();
-    slice_examples();
-    ownership_with_structs();
-
-    println!("\n=== 所有权示例完成 ===\n");
+    outln!("\n");
+    outln!("╔════════════════════════════════════════╗");
+    outln!("║   Rust 所有权系统 - 完整示例          ║");
+    outln!("╚════════════════════════════════════════╝");
+
+    lesson::registry().run_topic("ownership");
+
+    outln!("\n=== 所有权示例完成 ===\n");
 }
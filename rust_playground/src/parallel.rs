@@ -0,0 +1,195 @@
+// ============================================
+// 并行 map / reduce - 基于作用域线程（scoped threads）
+// ============================================
+//
+// `parallel_computation` 最早的写法需要 `Arc<Vec<_>>` 才能把数据交给线程，
+// 而且 `len / chunk_size` 会把除不尽的尾部元素丢掉。
+// `thread::scope` 保证所有线程在作用域结束前 join，所以线程可以直接借用切片；
+// `chunks` 会把剩下的元素放进最后一块（可能更短），不会漏掉任何元素。
+
+use std::num::NonZeroUsize;
+use std::thread;
+
+/// 并行度配置：决定把切片分成几块、用几个线程处理
+#[derive(Debug, Clone, Copy)]
+pub struct Par {
+    threads: usize,
+}
+
+impl Par {
+    /// 使用机器的可用并行度（`available_parallelism`），获取失败时退化为 1
+    pub fn auto() -> Par {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        Par { threads }
+    }
+
+    /// 固定使用 `threads` 个线程（至少 1 个）
+    pub fn new(threads: usize) -> Par {
+        Par {
+            threads: threads.max(1),
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// 长度为 `len` 的切片每块的大小：向上取整，保证块数不超过线程数
+    pub fn chunk_size(&self, len: usize) -> usize {
+        len.div_ceil(self.threads).max(1)
+    }
+
+    /// 对每一块在单独的线程里运行 `work`，按块的顺序返回结果
+    fn run_chunks<T, R, W>(&self, data: &[T], work: W) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        W: Fn(&[T]) -> R + Sync,
+    {
+        if self.threads == 1 || data.len() <= 1 {
+            return vec![work(data)];
+        }
+
+        let work = &work;
+        thread::scope(|scope| {
+            let handles: Vec<_> = data
+                .chunks(self.chunk_size(data.len()))
+                .map(|chunk| scope.spawn(move || work(chunk)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+
+    /// 并行地对每个元素调用 `f`
+    pub fn for_each<T, F>(&self, data: &[T], f: F)
+    where
+        T: Sync,
+        F: Fn(&T) + Sync,
+    {
+        self.run_chunks(data, |chunk| chunk.iter().for_each(&f));
+    }
+
+    /// 并行地把每个元素映射为新值，结果保持原来的顺序
+    pub fn map<T, U, F>(&self, data: &[T], f: F) -> Vec<U>
+    where
+        T: Sync,
+        U: Send,
+        F: Fn(&T) -> U + Sync,
+    {
+        self.run_chunks(data, |chunk| chunk.iter().map(&f).collect::<Vec<U>>())
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    /// 先把每个元素 `map` 成 `U`，再用 `reduce` 合并
+    ///
+    /// 每块从 `identity` 开始合并，最后再合并各块的结果，所以 `identity`
+    /// 必须是 `reduce` 的单位元（加法的 0、乘法的 1），`reduce` 必须满足结合律。
+    pub fn reduce<T, U, M, R>(&self, data: &[T], identity: U, map: M, reduce: R) -> U
+    where
+        T: Sync,
+        U: Clone + Send + Sync,
+        M: Fn(&T) -> U + Sync,
+        R: Fn(U, U) -> U + Sync,
+    {
+        let partials = self.run_chunks(data, |chunk| {
+            chunk
+                .iter()
+                .fold(identity.clone(), |acc, item| reduce(acc, map(item)))
+        });
+        partials.into_iter().fold(identity.clone(), &reduce)
+    }
+}
+
+/// 用可用并行度执行 [`Par::for_each`]
+pub fn par_for_each<T, F>(data: &[T], f: F)
+where
+    T: Sync,
+    F: Fn(&T) + Sync,
+{
+    Par::auto().for_each(data, f)
+}
+
+/// 用可用并行度执行 [`Par::map`]
+pub fn par_map<T, U, F>(data: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    Par::auto().map(data, f)
+}
+
+/// 用可用并行度执行 [`Par::reduce`]
+pub fn par_reduce<T, U, M, R>(data: &[T], identity: U, map: M, reduce: R) -> U
+where
+    T: Sync,
+    U: Clone + Send + Sync,
+    M: Fn(&T) -> U + Sync,
+    R: Fn(U, U) -> U + Sync,
+{
+    Par::auto().reduce(data, identity, map, reduce)
+}
+
+/// 并行计算平均值，对应 `math_utils::average` 的顺序版本
+pub fn par_average(numbers: &[i32]) -> f64 {
+    if numbers.is_empty() {
+        return 0.0;
+    }
+    // 用 i64 累加，大数组也不会像 i32 那样溢出
+    let sum = par_reduce(numbers, 0i64, |&x| x as i64, |a, b| a + b);
+    sum as f64 / numbers.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::math_utils;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_chunking_keeps_the_remainder() {
+        let par = Par::new(4);
+        assert_eq!(par.chunk_size(11), 3); // 3 + 3 + 3 + 2
+        assert_eq!(par.chunk_size(3), 1);
+        assert_eq!(par.chunk_size(0), 1);
+
+        for len in 0..50 {
+            let data: Vec<usize> = (0..len).collect();
+            let sum = par.reduce(&data, 0, |&x| x, |a, b| a + b);
+            assert_eq!(sum, data.iter().sum::<usize>(), "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_map_preserves_order() {
+        let data: Vec<i32> = (0..1000).collect();
+        for threads in [1, 2, 3, 7, 64] {
+            let squares = Par::new(threads).map(&data, |&x| x * x);
+            let expected: Vec<i32> = data.iter().map(|&x| x * x).collect();
+            assert_eq!(squares, expected);
+        }
+        assert!(par_map(&[] as &[i32], |&x| x).is_empty());
+    }
+
+    #[test]
+    fn test_for_each_visits_every_element_once() {
+        let visits: Vec<AtomicUsize> = (0..101).map(|_| AtomicUsize::new(0)).collect();
+        let indices: Vec<usize> = (0..101).collect();
+        Par::new(8).for_each(&indices, |&i| {
+            visits[i].fetch_add(1, Ordering::SeqCst);
+        });
+        assert!(visits.iter().all(|v| v.load(Ordering::SeqCst) == 1));
+    }
+
+    #[test]
+    fn test_par_average_matches_sequential() {
+        let numbers: Vec<i32> = (0..10_001).map(|i| i % 97 - 40).collect();
+        assert_eq!(par_average(&numbers), math_utils::average(&numbers));
+        assert_eq!(par_average(&[]), 0.0);
+    }
+}
@@ -0,0 +1,256 @@
+// ============================================
+// 持久化链表 - 用 Rc 共享尾部的不可变 cons 列表
+// ============================================
+//
+// "持久化"指的是修改不会改变原来的列表：`push_front` 返回一个新列表，
+// 新节点的 `next` 指向旧列表的头节点（`Rc::clone`，只加计数不拷贝数据）。
+// 于是多个列表可以共享同一段尾部：
+//
+//   a:        5 → 10 → Nil
+//   b:   3 ──┘
+//   c:   4 ──┘
+//
+// 节点一旦创建就不会再被修改，所以这种结构不可能形成引用循环。
+//
+// 默认的 drop 是递归的：drop 头节点 → drop 它的 next → ……
+// 一百万个节点就是一百万层调用，会把栈撑爆。所以这里手写了一个循环版本的 `Drop`。
+
+use std::fmt;
+
+use crate::my_rc::rc::Rc;
+
+struct Node<T> {
+    value: T,
+    next: Option<Rc<Node<T>>>,
+}
+
+/// 不可变的单链表，克隆和 `push_front` 都是 O(1)
+pub struct PersistentList<T> {
+    head: Option<Rc<Node<T>>>,
+    len: usize,
+}
+
+impl<T> PersistentList<T> {
+    pub fn new() -> PersistentList<T> {
+        PersistentList { head: None, len: 0 }
+    }
+
+    /// 返回一个在前面多了 `value` 的新列表，原列表不变，两者共享尾部
+    pub fn push_front(&self, value: T) -> PersistentList<T> {
+        PersistentList {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+            len: self.len + 1,
+        }
+    }
+
+    /// 第一个元素
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    /// 去掉第一个元素后的列表（和原列表共享节点）；空列表的 tail 还是空列表
+    pub fn tail(&self) -> PersistentList<T> {
+        match &self.head {
+            Some(node) => PersistentList {
+                head: node.next.clone(),
+                len: self.len - 1,
+            },
+            None => PersistentList::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    /// 两个列表是否从头节点开始就是同一段内存
+    pub fn ptr_eq(&self, other: &PersistentList<T>) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// 头节点被多少个列表（或者节点）引用着；空列表返回 0
+    pub fn head_ref_count(&self) -> usize {
+        self.head.as_ref().map_or(0, Rc::strong_count)
+    }
+}
+
+impl<T: Clone> PersistentList<T> {
+    /// 反转：新列表的节点都是新建的，元素需要克隆
+    pub fn reverse(&self) -> PersistentList<T> {
+        self.iter().fold(PersistentList::new(), |list, value| {
+            list.push_front(value.clone())
+        })
+    }
+}
+
+impl<T> Drop for PersistentList<T> {
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            // 只有这个列表独占的节点才需要拆；遇到共享的节点就停下，
+            // 剩下的部分还属于别的列表，减一次计数就好
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<T> Clone for PersistentList<T> {
+    fn clone(&self) -> Self {
+        PersistentList {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for PersistentList<T> {
+    fn default() -> Self {
+        PersistentList::new()
+    }
+}
+
+/// 第一个元素成为列表的头：`[1, 2, 3]` 收集成 `1 → 2 → 3 → Nil`
+impl<T> FromIterator<T> for PersistentList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let items: Vec<T> = iter.into_iter().collect();
+        items
+            .into_iter()
+            .rev()
+            .fold(PersistentList::new(), |list, value| list.push_front(value))
+    }
+}
+
+/// 借用迭代器，见 [`PersistentList::iter`]
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PersistentList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T: PartialEq> PartialEq for PersistentList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// 写成 cons 列表的样子：`1 → 2 → 3 → Nil`
+impl<T: fmt::Display> fmt::Display for PersistentList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for value in self.iter() {
+            write!(f, "{} → ", value)?;
+        }
+        f.write_str("Nil")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_front_shares_the_tail() {
+        let a = PersistentList::new().push_front(10).push_front(5);
+        let b = a.push_front(3);
+        let c = a.push_front(4);
+
+        assert_eq!(a.iter().copied().collect::<Vec<_>>(), [5, 10]);
+        assert_eq!(b.iter().copied().collect::<Vec<_>>(), [3, 5, 10]);
+        assert_eq!(c.len(), 3);
+        assert!(b.tail().ptr_eq(&a));
+        assert!(c.tail().ptr_eq(&b.tail()));
+        assert_eq!(a.head_ref_count(), 3);
+
+        drop((b, c));
+        assert_eq!(a.head_ref_count(), 1);
+        assert_eq!(a.head(), Some(&5));
+    }
+
+    #[test]
+    fn test_collect_reverse_and_display() {
+        let list: PersistentList<i32> = (1..=3).collect();
+        assert_eq!(list.to_string(), "1 → 2 → 3 → Nil");
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+        assert_eq!(list.reverse().to_string(), "3 → 2 → 1 → Nil");
+        assert_eq!(list.reverse().reverse(), list);
+
+        let empty: PersistentList<i32> = PersistentList::new();
+        assert_eq!(empty.to_string(), "Nil");
+        assert_eq!(empty.reverse(), empty);
+        assert!(empty.tail().is_empty());
+        assert_eq!(empty.head_ref_count(), 0);
+    }
+
+    #[test]
+    fn test_generic_elements() {
+        let words: PersistentList<String> = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        let longer = words.push_front(String::from("z"));
+        let joined: Vec<&str> = longer.iter().map(String::as_str).collect();
+        assert_eq!(joined, ["z", "a", "b"]);
+    }
+
+    #[test]
+    fn test_dropping_a_million_elements_does_not_overflow() {
+        let list: PersistentList<u32> = (0..1_000_000).collect();
+        assert_eq!(list.len(), 1_000_000);
+        let shared = list.tail().tail();
+        drop(list);
+        // 被别的列表共享的部分不会被拆掉
+        assert_eq!(shared.len(), 999_998);
+        assert_eq!(shared.head(), Some(&2));
+        drop(shared);
+    }
+
+    #[test]
+    fn test_drop_stops_at_shared_nodes() {
+        let base: PersistentList<Rc<()>> = PersistentList::new().push_front(Rc::new(()));
+        let marker = Rc::clone(base.head().unwrap());
+        let extended = base.push_front(Rc::new(()));
+        drop(extended);
+        assert_eq!(Rc::strong_count(&marker), 2);
+        drop(base);
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+}
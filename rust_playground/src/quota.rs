@@ -0,0 +1,611 @@
+// ============================================
+// 配额追踪 - 把 LimitTracker 做成能用的组件
+// ============================================
+//
+// `interior_mutability_pattern` 里的 `LimitTracker` 是《Rust 程序设计语言》里的例子，
+// 拿来讲 `RefCell` 很合适，真要用就有几个问题：
+// - 75% / 90% / 100% 三个阈值写死在代码里
+// - 每次 `set_value` 都会发消息，用量在 80% 附近来回变动时，同一条警告会刷屏
+// - 一个 tracker 只管一个配额
+//
+// 这里的 `QuotaTracker` 改成：
+// - 阈值可以配置（`QuotaConfig`），每个配额可以用不同的配置
+// - 边沿触发：只有跨过阈值、级别真的变了才通知
+// - 回差（hysteresis）：升级看阈值本身，降级要低于"阈值 - 回差"，
+//   避免用量在阈值附近抖动时反复升降
+// - 按名字管理多个配额
+// - 通知发给 `Messenger`，可以换成打印、写文件或者测试用的 `MockMessenger`
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// 级别变化的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    Raised,
+    Lowered,
+}
+
+/// 某个配额的级别变了
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub quota: String,
+    pub used: u64,
+    pub limit: u64,
+    pub change: Change,
+    /// 变化后的级别名，`None` 表示回到了所有阈值以下
+    pub level: Option<String>,
+}
+
+impl Notification {
+    pub fn ratio(&self) -> f64 {
+        self.used as f64 / self.limit as f64
+    }
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let level = self.level.as_deref().unwrap_or("正常");
+        match self.change {
+            Change::Raised => write!(f, "[{}] {}", self.quota, level)?,
+            Change::Lowered => write!(f, "[{}] 回落到{}", self.quota, level)?,
+        }
+        write!(
+            f,
+            ": 已使用 {:.0}%（{}/{}）",
+            self.ratio() * 100.0,
+            self.used,
+            self.limit
+        )
+    }
+}
+
+/// 接收通知的一方
+pub trait Messenger {
+    fn send(&self, notification: &Notification);
+}
+
+impl<M: Messenger + ?Sized> Messenger for &M {
+    fn send(&self, notification: &Notification) {
+        (**self).send(notification)
+    }
+}
+
+impl<M: Messenger + ?Sized> Messenger for Box<M> {
+    fn send(&self, notification: &Notification) {
+        (**self).send(notification)
+    }
+}
+
+/// 打印到标准输出（经过 `outln!`，示例的快照测试能捕获到）
+pub struct StdoutMessenger;
+
+impl Messenger for StdoutMessenger {
+    fn send(&self, notification: &Notification) {
+        outln!("{}", notification);
+    }
+}
+
+/// 每条通知追加一行到日志文件
+///
+/// `send` 没有返回值，写入失败时把错误留下来，用 `take_error` 取走。
+pub struct FileMessenger {
+    file: RefCell<File>,
+    error: RefCell<Option<io::Error>>,
+}
+
+impl FileMessenger {
+    /// 以追加模式打开，文件不存在就创建
+    pub fn open(path: impl AsRef<Path>) -> io::Result<FileMessenger> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(FileMessenger {
+            file: RefCell::new(file),
+            error: RefCell::new(None),
+        })
+    }
+
+    /// 最近一次写入失败的错误
+    pub fn take_error(&self) -> Option<io::Error> {
+        self.error.borrow_mut().take()
+    }
+}
+
+impl Messenger for FileMessenger {
+    fn send(&self, notification: &Notification) {
+        if let Err(error) = writeln!(self.file.borrow_mut(), "{}", notification) {
+            *self.error.borrow_mut() = Some(error);
+        }
+    }
+}
+
+/// 把通知记在内存里，测试用
+#[derive(Default)]
+pub struct MockMessenger {
+    sent: RefCell<Vec<Notification>>,
+}
+
+impl MockMessenger {
+    pub fn new() -> MockMessenger {
+        MockMessenger::default()
+    }
+
+    pub fn sent(&self) -> Vec<Notification> {
+        self.sent.borrow().clone()
+    }
+
+    /// 收到的通知，格式化成字符串
+    pub fn messages(&self) -> Vec<String> {
+        self.sent.borrow().iter().map(|n| n.to_string()).collect()
+    }
+
+    pub fn clear(&self) {
+        self.sent.borrow_mut().clear();
+    }
+}
+
+impl Messenger for MockMessenger {
+    fn send(&self, notification: &Notification) {
+        // 即使 &self 是不可变的，也可以修改内部数据
+        self.sent.borrow_mut().push(notification.clone());
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum QuotaError {
+    /// 阈值必须是正数，并且严格递增
+    InvalidThresholds,
+    /// 回差必须是有限的非负数
+    InvalidHysteresis,
+    ZeroLimit,
+    DuplicateQuota(String),
+    UnknownQuota(String),
+}
+
+impl fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QuotaError::InvalidThresholds => f.write_str("阈值必须是正数并且严格递增"),
+            QuotaError::InvalidHysteresis => f.write_str("回差必须是有限的非负数"),
+            QuotaError::ZeroLimit => f.write_str("配额上限不能为 0"),
+            QuotaError::DuplicateQuota(name) => write!(f, "配额 {} 已经存在", name),
+            QuotaError::UnknownQuota(name) => write!(f, "没有名为 {} 的配额", name),
+        }
+    }
+}
+
+impl std::error::Error for QuotaError {}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Threshold {
+    name: String,
+    ratio: f64,
+}
+
+/// 一组阈值和回差
+///
+/// 级别用下标表示：0 是所有阈值以下，`i` 表示已经跨过第 `i` 个阈值。
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotaConfig {
+    thresholds: Vec<Threshold>,
+    hysteresis: f64,
+}
+
+impl QuotaConfig {
+    /// `thresholds` 是 (级别名, 占上限的比例)，比例要严格递增；回差默认为 0
+    pub fn new<S: Into<String>>(
+        thresholds: impl IntoIterator<Item = (S, f64)>,
+    ) -> Result<QuotaConfig, QuotaError> {
+        let thresholds: Vec<Threshold> = thresholds
+            .into_iter()
+            .map(|(name, ratio)| Threshold {
+                name: name.into(),
+                ratio,
+            })
+            .collect();
+        let positive = thresholds
+            .iter()
+            .all(|t| t.ratio.is_finite() && t.ratio > 0.0);
+        let ascending = thresholds.windows(2).all(|w| w[0].ratio < w[1].ratio);
+        if !positive || !ascending {
+            return Err(QuotaError::InvalidThresholds);
+        }
+        Ok(QuotaConfig {
+            thresholds,
+            hysteresis: 0.0,
+        })
+    }
+
+    /// 降级时要低于"阈值 - 回差"，例如 0.05 表示多让出 5 个百分点
+    pub fn with_hysteresis(mut self, hysteresis: f64) -> Result<QuotaConfig, QuotaError> {
+        if !hysteresis.is_finite() || hysteresis < 0.0 {
+            return Err(QuotaError::InvalidHysteresis);
+        }
+        self.hysteresis = hysteresis;
+        Ok(self)
+    }
+
+    /// 从当前级别出发，按用量比例算出新的级别
+    fn next_level(&self, current: usize, ratio: f64) -> usize {
+        let mut level = current;
+        while level < self.thresholds.len() && ratio >= self.thresholds[level].ratio {
+            level += 1;
+        }
+        while level > 0 && ratio < self.thresholds[level - 1].ratio - self.hysteresis {
+            level -= 1;
+        }
+        level
+    }
+
+    fn level_name(&self, level: usize) -> Option<&str> {
+        level
+            .checked_sub(1)
+            .map(|index| self.thresholds[index].name.as_str())
+    }
+}
+
+/// 和原来的 `LimitTracker` 一样：75% 警告，90% 严重，100% 超额，回差 5%
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        QuotaConfig::new([("警告", 0.75), ("严重", 0.9), ("超额", 1.0)])
+            .and_then(|config| config.with_hysteresis(0.05))
+            .unwrap()
+    }
+}
+
+/// 某个配额当前的状态
+#[derive(Debug, Clone, PartialEq)]
+pub struct Usage<'a> {
+    pub used: u64,
+    pub limit: u64,
+    pub level: Option<&'a str>,
+}
+
+struct Quota {
+    used: u64,
+    limit: u64,
+    level: usize,
+    config: QuotaConfig,
+}
+
+/// 按名字管理多个配额，级别变化时通知 `Messenger`
+pub struct QuotaTracker<M: Messenger> {
+    messenger: M,
+    default_config: QuotaConfig,
+    quotas: BTreeMap<String, Quota>,
+}
+
+impl<M: Messenger> QuotaTracker<M> {
+    /// 新加的配额默认用 `QuotaConfig::default()`
+    pub fn new(messenger: M) -> QuotaTracker<M> {
+        QuotaTracker::with_config(messenger, QuotaConfig::default())
+    }
+
+    pub fn with_config(messenger: M, default_config: QuotaConfig) -> QuotaTracker<M> {
+        QuotaTracker {
+            messenger,
+            default_config,
+            quotas: BTreeMap::new(),
+        }
+    }
+
+    pub fn messenger(&self) -> &M {
+        &self.messenger
+    }
+
+    /// 加一个用默认配置的配额，初始用量为 0
+    pub fn add_quota(&mut self, name: impl Into<String>, limit: u64) -> Result<(), QuotaError> {
+        let config = self.default_config.clone();
+        self.add_quota_with(name, limit, config)
+    }
+
+    pub fn add_quota_with(
+        &mut self,
+        name: impl Into<String>,
+        limit: u64,
+        config: QuotaConfig,
+    ) -> Result<(), QuotaError> {
+        let name = name.into();
+        if limit == 0 {
+            return Err(QuotaError::ZeroLimit);
+        }
+        if self.quotas.contains_key(&name) {
+            return Err(QuotaError::DuplicateQuota(name));
+        }
+        let quota = Quota {
+            used: 0,
+            limit,
+            level: 0,
+            config,
+        };
+        self.quotas.insert(name, quota);
+        Ok(())
+    }
+
+    pub fn remove_quota(&mut self, name: &str) -> Result<(), QuotaError> {
+        match self.quotas.remove(name) {
+            Some(_) => Ok(()),
+            None => Err(QuotaError::UnknownQuota(name.to_string())),
+        }
+    }
+
+    /// 设置用量；级别变了就通知，并把发出的通知返回
+    pub fn set_used(&mut self, name: &str, used: u64) -> Result<Option<Notification>, QuotaError> {
+        let quota = self
+            .quotas
+            .get_mut(name)
+            .ok_or_else(|| QuotaError::UnknownQuota(name.to_string()))?;
+        quota.used = used;
+        Ok(Self::update_level(&self.messenger, name, quota))
+    }
+
+    /// 增加用量
+    pub fn consume(&mut self, name: &str, amount: u64) -> Result<Option<Notification>, QuotaError> {
+        let used = self.usage(name)?.used.saturating_add(amount);
+        self.set_used(name, used)
+    }
+
+    /// 减少用量，最少减到 0
+    pub fn release(&mut self, name: &str, amount: u64) -> Result<Option<Notification>, QuotaError> {
+        let used = self.usage(name)?.used.saturating_sub(amount);
+        self.set_used(name, used)
+    }
+
+    /// 调整上限，级别按新的比例重新计算
+    pub fn set_limit(
+        &mut self,
+        name: &str,
+        limit: u64,
+    ) -> Result<Option<Notification>, QuotaError> {
+        if limit == 0 {
+            return Err(QuotaError::ZeroLimit);
+        }
+        let quota = self
+            .quotas
+            .get_mut(name)
+            .ok_or_else(|| QuotaError::UnknownQuota(name.to_string()))?;
+        quota.limit = limit;
+        Ok(Self::update_level(&self.messenger, name, quota))
+    }
+
+    pub fn usage(&self, name: &str) -> Result<Usage<'_>, QuotaError> {
+        let quota = self
+            .quotas
+            .get(name)
+            .ok_or_else(|| QuotaError::UnknownQuota(name.to_string()))?;
+        Ok(Usage {
+            used: quota.used,
+            limit: quota.limit,
+            level: quota.config.level_name(quota.level),
+        })
+    }
+
+    /// 所有配额的名字，按字母顺序
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.quotas.keys().map(String::as_str)
+    }
+
+    fn update_level(messenger: &M, name: &str, quota: &mut Quota) -> Option<Notification> {
+        let ratio = quota.used as f64 / quota.limit as f64;
+        let level = quota.config.next_level(quota.level, ratio);
+        if level == quota.level {
+            return None;
+        }
+        let change = if level > quota.level {
+            Change::Raised
+        } else {
+            Change::Lowered
+        };
+        quota.level = level;
+        let notification = Notification {
+            quota: name.to_string(),
+            used: quota.used,
+            limit: quota.limit,
+            change,
+            level: quota.config.level_name(level).map(str::to_string),
+        };
+        messenger.send(&notification);
+        Some(notification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(mock: &MockMessenger) -> Vec<(Change, Option<String>)> {
+        mock.sent()
+            .into_iter()
+            .map(|n| (n.change, n.level))
+            .collect()
+    }
+
+    #[test]
+    fn test_notifies_only_when_level_changes() {
+        let mock = MockMessenger::new();
+        let mut tracker = QuotaTracker::new(&mock);
+        tracker.add_quota("api", 100).unwrap();
+
+        for used in [10, 76, 80, 85, 89] {
+            tracker.set_used("api", used).unwrap();
+        }
+        // 原来的 LimitTracker 在这里会发 4 条一样的警告
+        assert_eq!(mock.messages(), ["[api] 警告: 已使用 76%（76/100）"]);
+        assert_eq!(tracker.usage("api").unwrap().level, Some("警告"));
+    }
+
+    #[test]
+    fn test_jumping_several_levels_sends_one_notification() {
+        let mock = MockMessenger::new();
+        let mut tracker = QuotaTracker::new(&mock);
+        tracker.add_quota("api", 100).unwrap();
+
+        let sent = tracker.set_used("api", 150).unwrap().unwrap();
+        assert_eq!(sent.level.as_deref(), Some("超额"));
+        let sent = tracker.set_used("api", 0).unwrap().unwrap();
+        assert_eq!((sent.change, sent.level), (Change::Lowered, None));
+        assert_eq!(mock.sent().len(), 2);
+        assert_eq!(mock.messages()[1], "[api] 回落到正常: 已使用 0%（0/100）");
+    }
+
+    #[test]
+    fn test_hysteresis_suppresses_flapping() {
+        let mock = MockMessenger::new();
+        let config = QuotaConfig::new([("警告", 0.8)])
+            .unwrap()
+            .with_hysteresis(0.1)
+            .unwrap();
+        let mut tracker = QuotaTracker::with_config(&mock, config);
+        tracker.add_quota("disk", 100).unwrap();
+
+        // 在 80% 附近来回抖动，只升级一次
+        for used in [80, 79, 81, 75, 80, 71] {
+            tracker.set_used("disk", used).unwrap();
+        }
+        assert_eq!(levels(&mock), [(Change::Raised, Some("警告".to_string()))]);
+
+        // 低于 80% - 10% 才算回落
+        tracker.set_used("disk", 69).unwrap();
+        assert_eq!(levels(&mock)[1], (Change::Lowered, None));
+        // 回落之后要重新到达 80% 才再次升级
+        tracker.set_used("disk", 79).unwrap();
+        assert_eq!(mock.sent().len(), 2);
+    }
+
+    #[test]
+    fn test_lowering_steps_through_levels_with_hysteresis() {
+        let mock = MockMessenger::new();
+        let mut tracker = QuotaTracker::new(&mock);
+        tracker.add_quota("api", 100).unwrap();
+        tracker.set_used("api", 100).unwrap();
+
+        // 默认回差 5%：从超额回落到 95% 还不算降级，94% 才降到严重
+        assert!(tracker.set_used("api", 95).unwrap().is_none());
+        let sent = tracker.set_used("api", 94).unwrap().unwrap();
+        assert_eq!(
+            (sent.change, sent.level.as_deref()),
+            (Change::Lowered, Some("严重"))
+        );
+        // 一次降到 80%：低于 90%-5%，但没有低于 75%-5%，停在警告
+        let sent = tracker.set_used("api", 80).unwrap().unwrap();
+        assert_eq!(sent.level.as_deref(), Some("警告"));
+    }
+
+    #[test]
+    fn test_multiple_named_quotas_are_independent() {
+        let mock = MockMessenger::new();
+        let mut tracker = QuotaTracker::new(&mock);
+        tracker.add_quota("cpu", 10).unwrap();
+        tracker.add_quota("memory", 1000).unwrap();
+        tracker
+            .add_quota_with("requests", 4, QuotaConfig::new([("满了", 1.0)]).unwrap())
+            .unwrap();
+
+        tracker.consume("cpu", 8).unwrap();
+        tracker.consume("memory", 500).unwrap();
+        for _ in 0..4 {
+            tracker.consume("requests", 1).unwrap();
+        }
+        assert_eq!(
+            mock.messages(),
+            [
+                "[cpu] 警告: 已使用 80%（8/10）",
+                "[requests] 满了: 已使用 100%（4/4）"
+            ]
+        );
+        assert_eq!(tracker.usage("memory").unwrap().level, None);
+        assert_eq!(
+            tracker.names().collect::<Vec<_>>(),
+            ["cpu", "memory", "requests"]
+        );
+
+        mock.clear();
+        tracker.release("cpu", 100).unwrap();
+        assert_eq!(tracker.usage("cpu").unwrap().used, 0);
+        assert_eq!(levels(&mock), [(Change::Lowered, None)]);
+    }
+
+    #[test]
+    fn test_changing_limit_recomputes_level() {
+        let mock = MockMessenger::new();
+        let mut tracker = QuotaTracker::new(&mock);
+        tracker.add_quota("api", 100).unwrap();
+        tracker.set_used("api", 60).unwrap();
+
+        let sent = tracker.set_limit("api", 60).unwrap().unwrap();
+        assert_eq!(sent.level.as_deref(), Some("超额"));
+        assert!(tracker.set_limit("api", 100).unwrap().is_some());
+        assert_eq!(tracker.usage("api").unwrap().level, None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut tracker = QuotaTracker::new(MockMessenger::new());
+        assert_eq!(tracker.add_quota("api", 0), Err(QuotaError::ZeroLimit));
+        tracker.add_quota("api", 1).unwrap();
+        assert_eq!(
+            tracker.add_quota("api", 1),
+            Err(QuotaError::DuplicateQuota("api".to_string()))
+        );
+        assert_eq!(
+            tracker.set_used("db", 1),
+            Err(QuotaError::UnknownQuota("db".to_string()))
+        );
+        assert_eq!(tracker.set_limit("api", 0), Err(QuotaError::ZeroLimit));
+        tracker.remove_quota("api").unwrap();
+        assert!(tracker.usage("api").is_err());
+
+        assert_eq!(
+            QuotaConfig::new([("a", 0.9), ("b", 0.5)]),
+            Err(QuotaError::InvalidThresholds)
+        );
+        assert_eq!(
+            QuotaConfig::new([("a", 0.0)]),
+            Err(QuotaError::InvalidThresholds)
+        );
+        assert_eq!(
+            QuotaConfig::default().with_hysteresis(-0.1),
+            Err(QuotaError::InvalidHysteresis)
+        );
+        assert_eq!(
+            QuotaError::UnknownQuota("db".into()).to_string(),
+            "没有名为 db 的配额"
+        );
+    }
+
+    #[test]
+    fn test_boxed_messengers_are_interchangeable() {
+        let output = crate::output::capture(|| {
+            let backends: Vec<Box<dyn Messenger>> =
+                vec![Box::new(MockMessenger::new()), Box::new(StdoutMessenger)];
+            for messenger in backends {
+                let mut tracker = QuotaTracker::new(messenger);
+                tracker.add_quota("api", 4).unwrap();
+                assert!(tracker.set_used("api", 3).unwrap().is_some());
+            }
+        });
+        assert_eq!(output, "[api] 警告: 已使用 75%（3/4）\n");
+    }
+
+    #[test]
+    fn test_file_messenger_appends_lines() {
+        let path = std::env::temp_dir().join(format!("quota-test-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let mut tracker = QuotaTracker::new(FileMessenger::open(&path).unwrap());
+            tracker.add_quota("api", 10).unwrap();
+            tracker.set_used("api", 9).unwrap();
+            tracker.set_used("api", 0).unwrap();
+            assert!(tracker.messenger().take_error().is_none());
+        }
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            log,
+            "[api] 严重: 已使用 90%（9/10）\n[api] 回落到正常: 已使用 0%（0/10）\n"
+        );
+    }
+}
@@ -0,0 +1,551 @@
+// ============================================
+// 双向链表（unsafe 版）- NonNull 裸指针
+// ============================================
+//
+// 和 `rc_list.rs` 同样的接口，节点之间改用裸指针连接：
+// - 链表拥有所有节点（`Box::into_raw` 放出去，删除时 `Box::from_raw` 收回来），
+//   `prev`/`next` 只是不带所有权的 `NonNull`
+// - 没有引用计数也没有 `RefCell`，`iter()` 可以直接返回 `&T`，`iter_mut()` 返回 `&mut T`
+// - 取出值就是 `Box::from_raw(node).value`，不需要 `try_unwrap`
+//
+// 代价是每个 `unsafe` 块都要自己证明指针有效、没有别名冲突。
+// 下面的测试在 Miri 下跑过：`cargo +nightly miri test raw_list`。
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+/// 用裸指针实现的双向链表，结构和 `std::collections::LinkedList` 相同
+pub struct RawList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    /// 告诉编译器链表拥有 `Box<Node<T>>`：影响 drop 检查和型变
+    _owns: PhantomData<Box<Node<T>>>,
+}
+
+// 和 Box<T> 一样：只要 T 能跨线程，整个链表就能跨线程
+unsafe impl<T: Send> Send for RawList<T> {}
+unsafe impl<T: Sync> Sync for RawList<T> {}
+
+impl<T> RawList<T> {
+    pub fn new() -> RawList<T> {
+        RawList {
+            head: None,
+            tail: None,
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        // SAFETY: head 是这个链表的节点（或者 None）
+        unsafe { self.insert_between(None, self.head, value) };
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        // SAFETY: tail 是这个链表的节点（或者 None）
+        unsafe { self.insert_between(self.tail, None, value) };
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: head 是这个链表的节点
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: tail 是这个链表的节点
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    /// 第一个元素，直接返回引用
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: 节点活得和链表一样久，&self 保证期间没有人修改
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: 同 front
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: &mut self 保证这是唯一的访问
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.head,
+            back: self.tail,
+            remaining: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.head,
+            back: self.tail,
+            remaining: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    /// 指向第一个元素的游标；空链表时指向"幽灵位置"
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// 从第 `at` 个元素处断开，返回后半段
+    ///
+    /// # Panics
+    ///
+    /// `at > len` 时 panic。
+    pub fn split_off(&mut self, at: usize) -> RawList<T> {
+        assert!(
+            at <= self.len,
+            "split_off 的位置 {} 超出了长度 {}",
+            at,
+            self.len
+        );
+        if at == 0 {
+            return std::mem::take(self);
+        }
+        if at == self.len {
+            return RawList::new();
+        }
+        let mut node = self.head.unwrap();
+        // SAFETY: at < len，沿着 next 走 at 步还在链表里；
+        // 断开时只改写这两个节点的指针，&mut self 保证没有别的访问
+        unsafe {
+            for _ in 0..at {
+                node = (*node.as_ptr()).next.unwrap();
+            }
+            let prev = (*node.as_ptr()).prev.take().unwrap();
+            (*prev.as_ptr()).next = None;
+            let back = RawList {
+                head: Some(node),
+                tail: self.tail.replace(prev),
+                len: self.len - at,
+                _owns: PhantomData,
+            };
+            self.len = at;
+            back
+        }
+    }
+
+    /// 把 `other` 的所有元素接到后面，`other` 变成空链表
+    pub fn append(&mut self, other: &mut RawList<T>) {
+        let Some(tail) = self.tail else {
+            std::mem::swap(self, other);
+            return;
+        };
+        if let Some(other_head) = other.head.take() {
+            // SAFETY: 两个节点分别属于两个链表，我们对两个链表都有 &mut
+            unsafe {
+                (*other_head.as_ptr()).prev = Some(tail);
+                (*tail.as_ptr()).next = Some(other_head);
+            }
+            self.tail = other.tail.take();
+            self.len += std::mem::take(&mut other.len);
+        }
+    }
+
+    /// 在 `prev` 和 `next` 之间插入新节点，`None` 表示链表的一端
+    ///
+    /// # Safety
+    ///
+    /// `prev`、`next` 必须是这个链表里相邻的两个节点（或者对应的一端）。
+    unsafe fn insert_between(&mut self, prev: Link<T>, next: Link<T>, value: T) {
+        let node = NonNull::from(Box::leak(Box::new(Node { value, prev, next })));
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(node),
+            None => self.head = Some(node),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(node),
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+    }
+
+    /// 把节点从链表上摘下来，释放节点并取出值
+    ///
+    /// # Safety
+    ///
+    /// `node` 必须是这个链表的节点，之后不能再使用它。
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let boxed = Box::from_raw(node.as_ptr());
+        match boxed.prev {
+            Some(prev) => (*prev.as_ptr()).next = boxed.next,
+            None => self.head = boxed.next,
+        }
+        match boxed.next {
+            Some(next) => (*next.as_ptr()).prev = boxed.prev,
+            None => self.tail = boxed.prev,
+        }
+        self.len -= 1;
+        boxed.value
+    }
+}
+
+impl<T> Drop for RawList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> Default for RawList<T> {
+    fn default() -> Self {
+        RawList::new()
+    }
+}
+
+impl<T> FromIterator<T> for RawList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = RawList::new();
+        for value in iter {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RawList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut RawList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RawList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// 见 [`RawList::iter`]
+pub struct Iter<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    /// 两端相遇时停下
+    remaining: usize,
+    _list: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.front.map(|node| {
+            // SAFETY: 迭代器借着链表，节点在 'a 内有效且不会被修改
+            let node = unsafe { &*node.as_ptr() };
+            self.front = node.next;
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.back.map(|node| {
+            // SAFETY: 同 next
+            let node = unsafe { &*node.as_ptr() };
+            self.back = node.prev;
+            &node.value
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// 见 [`RawList::iter_mut`]
+pub struct IterMut<'a, T> {
+    front: Link<T>,
+    back: Link<T>,
+    remaining: usize,
+    _list: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.front.map(|node| {
+            // SAFETY: 迭代器独占地借着链表；remaining 保证两端不会交出同一个节点，
+            // 所以每个 &mut T 只给出一次。只借出 value 字段，不碰邻居会读的指针
+            unsafe {
+                self.front = (*node.as_ptr()).next;
+                &mut (*node.as_ptr()).value
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.back.map(|node| {
+            // SAFETY: 同 next
+            unsafe {
+                self.back = (*node.as_ptr()).prev;
+                &mut (*node.as_ptr()).value
+            }
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// 可以在链表里来回移动、就地插入和删除的游标，语义同 `rc_list::CursorMut`
+pub struct CursorMut<'a, T> {
+    list: &'a mut RawList<T>,
+    /// `None` 表示幽灵位置
+    current: Link<T>,
+    /// 幽灵位置的下标等于 `len`
+    index: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// 当前元素的下标，幽灵位置返回 `None`
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    /// 当前元素，直接是 `&mut T`
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: 游标独占地借着链表，返回的引用借着游标
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn move_next(&mut self) {
+        let next = match self.current {
+            // SAFETY: current 是链表里的节点
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        };
+        self.index = match (self.current, next) {
+            (_, None) => self.list.len,
+            (None, Some(_)) => 0,
+            (Some(_), Some(_)) => self.index + 1,
+        };
+        self.current = next;
+    }
+
+    pub fn move_prev(&mut self) {
+        let prev = match self.current {
+            // SAFETY: current 是链表里的节点
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        };
+        self.index = match (self.current, prev) {
+            (_, None) => self.list.len,
+            (None, Some(_)) => self.list.len - 1,
+            (Some(_), Some(_)) => self.index - 1,
+        };
+        self.current = prev;
+    }
+
+    /// 插到当前元素后面；在幽灵位置时插到头部
+    pub fn insert_after(&mut self, value: T) {
+        match self.current {
+            // SAFETY: node 和它的 next 在链表里相邻
+            Some(node) => unsafe {
+                let next = (*node.as_ptr()).next;
+                self.list.insert_between(Some(node), next, value);
+            },
+            None => {
+                self.list.push_front(value);
+                self.index = self.list.len;
+            }
+        }
+    }
+
+    /// 插到当前元素前面；在幽灵位置时插到尾部
+    pub fn insert_before(&mut self, value: T) {
+        match self.current {
+            // SAFETY: node 的 prev 和它在链表里相邻
+            Some(node) => unsafe {
+                let prev = (*node.as_ptr()).prev;
+                self.list.insert_between(prev, Some(node), value);
+            },
+            None => self.list.push_back(value),
+        }
+        self.index += 1;
+    }
+
+    /// 删除当前元素并返回它，游标移到下一个元素
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // SAFETY: node 是链表里的节点，先记下 next，unlink 之后 node 就不能再用了
+        unsafe {
+            self.current = (*node.as_ptr()).next;
+            Some(self.list.unlink(node))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn values(list: &RawList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_push_and_pop_at_both_ends() {
+        let mut list = RawList::new();
+        list.push_back(2);
+        list.push_back(3);
+        list.push_front(1);
+        assert_eq!(values(&list), [1, 2, 3]);
+        assert_eq!((list.front(), list.back()), (Some(&1), Some(&3)));
+        *list.front_mut().unwrap() = 0;
+
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_back(), None);
+        assert!(list.is_empty() && list.front().is_none());
+    }
+
+    #[test]
+    fn test_iter_both_directions_and_iter_mut() {
+        let mut list: RawList<i32> = (1..=4).collect();
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), [4, 3, 2, 1]);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.len(), 2);
+
+        // 从两端同时拿 &mut，Miri 会检查它们没有互相踩到
+        let mut iter = list.iter_mut();
+        let (first, last) = (iter.next().unwrap(), iter.next_back().unwrap());
+        std::mem::swap(first, last);
+        for value in &mut list {
+            *value *= 10;
+        }
+        assert_eq!(format!("{:?}", list), "[40, 20, 30, 10]");
+    }
+
+    #[test]
+    fn test_cursor_insert_and_remove() {
+        let mut list: RawList<i32> = (1..=3).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(1));
+        *cursor.current().unwrap() = 20;
+        cursor.insert_before(15);
+        cursor.insert_after(25);
+        assert_eq!(cursor.index(), Some(2));
+
+        assert_eq!(cursor.remove_current(), Some(20));
+        assert_eq!(cursor.current(), Some(&mut 25));
+        cursor.move_next();
+        cursor.move_next();
+        // 幽灵位置：插到后面就是插到头部，插到前面就是插到尾部
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(0);
+        cursor.insert_before(4);
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(5));
+        assert_eq!(values(&list), [0, 1, 15, 25, 3, 4]);
+    }
+
+    #[test]
+    fn test_split_off_and_append() {
+        let mut list: RawList<i32> = (1..=5).collect();
+        let mut back = list.split_off(2);
+        assert_eq!((values(&list), values(&back)), (vec![1, 2], vec![3, 4, 5]));
+        assert_eq!(list.back(), Some(&2));
+        assert_eq!(back.iter().rev().copied().collect::<Vec<_>>(), [5, 4, 3]);
+
+        list.append(&mut back);
+        assert!(back.is_empty());
+        assert_eq!(values(&list), [1, 2, 3, 4, 5]);
+        assert_eq!(list.pop_back(), Some(5));
+
+        assert_eq!(values(&list.split_off(4)), []);
+        let mut all = list.split_off(0);
+        assert!(list.is_empty());
+        list.append(&mut all);
+        assert_eq!(values(&list), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_values_are_dropped_exactly_once() {
+        let marker = Rc::new(());
+        let mut list: RawList<Rc<()>> = (0..3).map(|_| Rc::clone(&marker)).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.remove_current();
+        assert_eq!(Rc::strong_count(&marker), 3);
+        drop(list);
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn test_long_list_drops_without_overflow() {
+        let len = if cfg!(miri) { 1_000 } else { 100_000 };
+        let list: RawList<u32> = (0..len).collect();
+        assert_eq!(list.len(), len as usize);
+    }
+}
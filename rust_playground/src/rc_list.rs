@@ -0,0 +1,586 @@
+// ============================================
+// 双向链表（安全版）- Rc<RefCell<Node>> + Weak
+// ============================================
+//
+// 只用安全代码写双向链表：
+// - `next` 用 `Rc` 拥有下一个节点，`prev` 用 `Weak` 指回上一个节点，避免引用循环
+// - 节点要被前后两边同时修改，所以还得包一层 `RefCell`
+//
+// 能写出来，但处处别扭，这正是这个文件想展示的：
+// - `iter()` 没法返回 `&T`：值在 `RefCell` 里，引用活不过 `borrow()` 返回的守卫。
+//   只能返回持有节点的句柄 `Elem`，用的时候再 `elem.borrow()`
+// - 每走一步都要 `borrow()` + `Rc::clone`，往回走还要 `Weak::upgrade`
+// - 取出节点里的值要先 `Rc::try_unwrap`，任何地方多留了一个 `Rc` 就会失败
+// - 默认的 drop 会沿着 `next` 递归，长链表会栈溢出，还得手写 `Drop`
+//
+// 同样的接口用裸指针实现见 `raw_list.rs`。
+
+use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
+
+type NodeRef<T> = Rc<RefCell<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    prev: Option<Weak<RefCell<Node<T>>>>,
+    next: Option<NodeRef<T>>,
+}
+
+/// 用 `Rc`/`Weak` 实现的双向链表
+pub struct RcList<T> {
+    head: Option<NodeRef<T>>,
+    /// 尾节点被两个 `Rc` 指着：这里一个，前一个节点的 `next` 一个
+    tail: Option<NodeRef<T>>,
+    len: usize,
+}
+
+impl<T> RcList<T> {
+    pub fn new() -> RcList<T> {
+        RcList {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, value: T) {
+        let head = self.head.clone();
+        self.insert_between(None, head, value);
+    }
+
+    pub fn push_back(&mut self, value: T) {
+        let tail = self.tail.clone();
+        self.insert_between(tail, None, value);
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head.clone()?;
+        Some(self.unlink(head))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail.clone()?;
+        Some(self.unlink(tail))
+    }
+
+    /// 第一个元素；返回的是 `Ref` 守卫而不是 `&T`
+    pub fn front(&self) -> Option<Ref<'_, T>> {
+        self.head
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.value))
+    }
+
+    pub fn back(&self) -> Option<Ref<'_, T>> {
+        self.tail
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.value))
+    }
+
+    /// 从头到尾遍历；每个元素是一个 [`Elem`] 句柄，要 `borrow()` 才能看到值
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            nodes: Nodes::new(self),
+            _list: PhantomData,
+        }
+    }
+
+    /// 和 `iter` 一样，只是句柄还能 `borrow_mut()`
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            nodes: Nodes::new(self),
+            _list: PhantomData,
+        }
+    }
+
+    /// 指向第一个元素的游标；空链表时指向"幽灵位置"
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head.clone(),
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// 从第 `at` 个元素处断开，返回后半段
+    ///
+    /// # Panics
+    ///
+    /// `at > len` 时 panic。
+    pub fn split_off(&mut self, at: usize) -> RcList<T> {
+        assert!(
+            at <= self.len,
+            "split_off 的位置 {} 超出了长度 {}",
+            at,
+            self.len
+        );
+        if at == 0 {
+            return std::mem::take(self);
+        }
+        if at == self.len {
+            return RcList::new();
+        }
+        let mut node = self.head.clone().unwrap();
+        for _ in 0..at {
+            let next = node.borrow().next.clone().unwrap();
+            node = next;
+        }
+        let prev = node
+            .borrow_mut()
+            .prev
+            .take()
+            .and_then(|prev| prev.upgrade());
+        let prev = prev.unwrap();
+        prev.borrow_mut().next = None;
+        let back = RcList {
+            head: Some(node),
+            tail: self.tail.replace(prev),
+            len: self.len - at,
+        };
+        self.len = at;
+        back
+    }
+
+    /// 把 `other` 的所有元素接到后面，`other` 变成空链表
+    pub fn append(&mut self, other: &mut RcList<T>) {
+        let Some(tail) = self.tail.clone() else {
+            std::mem::swap(self, other);
+            return;
+        };
+        if let Some(other_head) = other.head.take() {
+            other_head.borrow_mut().prev = Some(Rc::downgrade(&tail));
+            tail.borrow_mut().next = Some(other_head);
+            self.tail = other.tail.take();
+            self.len += std::mem::take(&mut other.len);
+        }
+    }
+
+    /// 在 `prev` 和 `next` 之间插入新节点，`None` 表示链表的一端
+    fn insert_between(&mut self, prev: Option<NodeRef<T>>, next: Option<NodeRef<T>>, value: T) {
+        let node = Rc::new(RefCell::new(Node {
+            value,
+            prev: prev.as_ref().map(Rc::downgrade),
+            next: next.clone(),
+        }));
+        match &prev {
+            Some(prev) => prev.borrow_mut().next = Some(Rc::clone(&node)),
+            None => self.head = Some(Rc::clone(&node)),
+        }
+        match &next {
+            Some(next) => next.borrow_mut().prev = Some(Rc::downgrade(&node)),
+            None => self.tail = Some(node),
+        }
+        self.len += 1;
+    }
+
+    /// 把节点从链表上摘下来，取出里面的值
+    fn unlink(&mut self, node: NodeRef<T>) -> T {
+        let prev = node
+            .borrow_mut()
+            .prev
+            .take()
+            .and_then(|prev| prev.upgrade());
+        let next = node.borrow_mut().next.take();
+        match &prev {
+            Some(prev) => prev.borrow_mut().next = next.clone(),
+            None => self.head = next.clone(),
+        }
+        match &next {
+            Some(next) => next.borrow_mut().prev = prev.as_ref().map(Rc::downgrade),
+            None => self.tail = prev,
+        }
+        self.len -= 1;
+        // 链表里指向它的 Rc 都已经换掉了，只剩参数这一个
+        match Rc::try_unwrap(node) {
+            Ok(node) => node.into_inner().value,
+            Err(_) => unreachable!("被摘下的节点还被别处持有"),
+        }
+    }
+}
+
+impl<T> Drop for RcList<T> {
+    fn drop(&mut self) {
+        // 默认的 drop 沿着 next 递归，长链表会把栈撑爆；这里逐个摘下来
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T> Default for RcList<T> {
+    fn default() -> Self {
+        RcList::new()
+    }
+}
+
+impl<T> FromIterator<T> for RcList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = RcList::new();
+        for value in iter {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut list = f.debug_list();
+        for elem in self.iter() {
+            list.entry(&*elem.borrow());
+        }
+        list.finish()
+    }
+}
+
+/// `iter()` 给出的元素句柄：持有节点的 `Rc`，生命周期绑在链表上
+pub struct Elem<'a, T> {
+    node: NodeRef<T>,
+    _list: PhantomData<&'a RcList<T>>,
+}
+
+impl<T> Elem<'_, T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.value)
+    }
+}
+
+/// `iter_mut()` 给出的元素句柄
+pub struct ElemMut<'a, T> {
+    node: NodeRef<T>,
+    _list: PhantomData<&'a mut RcList<T>>,
+}
+
+impl<T> ElemMut<'_, T> {
+    pub fn borrow(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.value)
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.node.borrow_mut(), |node| &mut node.value)
+    }
+}
+
+/// 从两端往中间走的节点序列，`Iter` 和 `IterMut` 共用
+struct Nodes<T> {
+    front: Option<NodeRef<T>>,
+    back: Option<NodeRef<T>>,
+    /// 两端相遇时停下
+    remaining: usize,
+}
+
+impl<T> Nodes<T> {
+    fn new(list: &RcList<T>) -> Nodes<T> {
+        Nodes {
+            front: list.head.clone(),
+            back: list.tail.clone(),
+            remaining: list.len,
+        }
+    }
+
+    fn next(&mut self) -> Option<NodeRef<T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.front.take()?;
+        self.front = node.borrow().next.clone();
+        Some(node)
+    }
+
+    fn next_back(&mut self) -> Option<NodeRef<T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let node = self.back.take()?;
+        self.back = node.borrow().prev.as_ref().and_then(Weak::upgrade);
+        Some(node)
+    }
+}
+
+/// 见 [`RcList::iter`]
+pub struct Iter<'a, T> {
+    nodes: Nodes<T>,
+    _list: PhantomData<&'a RcList<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = Elem<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.next()?;
+        Some(Elem {
+            node,
+            _list: PhantomData,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.nodes.remaining, Some(self.nodes.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.next_back()?;
+        Some(Elem {
+            node,
+            _list: PhantomData,
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// 见 [`RcList::iter_mut`]
+pub struct IterMut<'a, T> {
+    nodes: Nodes<T>,
+    _list: PhantomData<&'a mut RcList<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = ElemMut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.next()?;
+        Some(ElemMut {
+            node,
+            _list: PhantomData,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.nodes.remaining, Some(self.nodes.remaining))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.next_back()?;
+        Some(ElemMut {
+            node,
+            _list: PhantomData,
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+// 句柄、迭代器和游标都攥着节点的 `Rc`。它们本身不需要在 drop 时做什么，
+// 但没有 `Drop` 的类型，编译器允许它在借用结束之后才被 drop：
+// 链表已经 `pop_front` 了，某个句柄却还拿着那个节点，`try_unwrap` 就会失败。
+// 写一个空的 `Drop`，编译器就会认为 drop 时还要用到 `'a`，借用必须一直持续到它们被 drop。
+macro_rules! hold_borrow_until_drop {
+    ($($handle:ident),*) => {$(
+        impl<T> Drop for $handle<'_, T> {
+            fn drop(&mut self) {}
+        }
+    )*};
+}
+
+hold_borrow_until_drop!(Elem, ElemMut, Iter, IterMut, CursorMut);
+
+/// 可以在链表里来回移动、就地插入和删除的游标
+///
+/// 和 `std::collections::linked_list::CursorMut` 一样，尾部之后有一个不对应任何元素的
+/// "幽灵位置"，从它往后走回到头部，往前走到尾部。
+pub struct CursorMut<'a, T> {
+    list: &'a mut RcList<T>,
+    /// `None` 表示幽灵位置
+    current: Option<NodeRef<T>>,
+    /// 幽灵位置的下标等于 `len`
+    index: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// 当前元素的下标，幽灵位置返回 `None`
+    pub fn index(&self) -> Option<usize> {
+        self.current.as_ref().map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<RefMut<'_, T>> {
+        self.current
+            .as_ref()
+            .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.value))
+    }
+
+    pub fn move_next(&mut self) {
+        let next = match &self.current {
+            Some(node) => node.borrow().next.clone(),
+            None => self.list.head.clone(),
+        };
+        self.index = match (&self.current, &next) {
+            (_, None) => self.list.len,
+            (None, Some(_)) => 0,
+            (Some(_), Some(_)) => self.index + 1,
+        };
+        self.current = next;
+    }
+
+    pub fn move_prev(&mut self) {
+        let prev = match &self.current {
+            Some(node) => node.borrow().prev.as_ref().and_then(Weak::upgrade),
+            None => self.list.tail.clone(),
+        };
+        self.index = match (&self.current, &prev) {
+            (_, None) => self.list.len,
+            (None, Some(_)) => self.list.len - 1,
+            (Some(_), Some(_)) => self.index - 1,
+        };
+        self.current = prev;
+    }
+
+    /// 插到当前元素后面；在幽灵位置时插到头部
+    pub fn insert_after(&mut self, value: T) {
+        match self.current.clone() {
+            Some(node) => {
+                let next = node.borrow().next.clone();
+                self.list.insert_between(Some(node), next, value);
+            }
+            None => {
+                self.list.push_front(value);
+                self.index = self.list.len;
+            }
+        }
+    }
+
+    /// 插到当前元素前面；在幽灵位置时插到尾部
+    pub fn insert_before(&mut self, value: T) {
+        match self.current.clone() {
+            Some(node) => {
+                let prev = node.borrow().prev.as_ref().and_then(Weak::upgrade);
+                self.list.insert_between(prev, Some(node), value);
+            }
+            None => self.list.push_back(value),
+        }
+        self.index += 1;
+    }
+
+    /// 删除当前元素并返回它，游标移到下一个元素
+    pub fn remove_current(&mut self) -> Option<T> {
+        // 先把游标自己的 Rc 交出去，否则 unlink 里的 try_unwrap 会失败
+        let node = self.current.take()?;
+        self.current = node.borrow().next.clone();
+        Some(self.list.unlink(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(list: &RcList<i32>) -> Vec<i32> {
+        list.iter().map(|elem| *elem.borrow()).collect()
+    }
+
+    #[test]
+    fn test_push_and_pop_at_both_ends() {
+        let mut list = RcList::new();
+        list.push_back(2);
+        list.push_back(3);
+        list.push_front(1);
+        assert_eq!(values(&list), [1, 2, 3]);
+        assert_eq!((*list.front().unwrap(), *list.back().unwrap()), (1, 3));
+
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_front(), Some(2));
+        assert_eq!(list.pop_back(), None);
+        assert!(list.is_empty() && list.front().is_none());
+    }
+
+    #[test]
+    fn test_iter_both_directions_and_iter_mut() {
+        let mut list: RcList<i32> = (1..=4).collect();
+        let backwards: Vec<i32> = list.iter().rev().map(|e| *e.borrow()).collect();
+        assert_eq!(backwards, [4, 3, 2, 1]);
+
+        let mut iter = list.iter();
+        assert_eq!(*iter.next().unwrap().borrow(), 1);
+        assert_eq!(*iter.next_back().unwrap().borrow(), 4);
+        assert_eq!(iter.len(), 2);
+        // 迭代器拿着节点的 Rc，要先 drop 掉才能再可变地借用链表
+        drop(iter);
+
+        for elem in list.iter_mut() {
+            *elem.borrow_mut() *= 10;
+        }
+        assert_eq!(format!("{:?}", list), "[10, 20, 30, 40]");
+    }
+
+    #[test]
+    fn test_cursor_insert_and_remove() {
+        let mut list: RcList<i32> = (1..=3).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(1));
+        *cursor.current().unwrap() = 20;
+        cursor.insert_before(15);
+        cursor.insert_after(25);
+        assert_eq!(cursor.index(), Some(2));
+
+        assert_eq!(cursor.remove_current(), Some(20));
+        assert_eq!(cursor.current().as_deref(), Some(&25));
+        cursor.move_next();
+        cursor.move_next();
+        // 幽灵位置：插到后面就是插到头部，插到前面就是插到尾部
+        assert_eq!(cursor.index(), None);
+        cursor.insert_after(0);
+        cursor.insert_before(4);
+        cursor.move_prev();
+        assert_eq!(cursor.index(), Some(5));
+        drop(cursor);
+        assert_eq!(values(&list), [0, 1, 15, 25, 3, 4]);
+    }
+
+    #[test]
+    fn test_split_off_and_append() {
+        let mut list: RcList<i32> = (1..=5).collect();
+        let mut back = list.split_off(2);
+        assert_eq!((values(&list), values(&back)), (vec![1, 2], vec![3, 4, 5]));
+        assert_eq!(*list.back().unwrap(), 2);
+        assert_eq!(
+            back.iter().rev().map(|e| *e.borrow()).collect::<Vec<_>>(),
+            [5, 4, 3]
+        );
+
+        list.append(&mut back);
+        assert!(back.is_empty());
+        assert_eq!(values(&list), [1, 2, 3, 4, 5]);
+        assert_eq!(list.pop_back(), Some(5));
+
+        assert_eq!(values(&list.split_off(4)), []);
+        let mut all = list.split_off(0);
+        assert!(list.is_empty());
+        list.append(&mut all);
+        assert_eq!(values(&list), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_values_are_dropped_exactly_once() {
+        let marker = Rc::new(());
+        let mut list: RcList<Rc<()>> = (0..3).map(|_| Rc::clone(&marker)).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.remove_current();
+        drop(cursor);
+        assert_eq!(Rc::strong_count(&marker), 3);
+        drop(list);
+        assert_eq!(Rc::strong_count(&marker), 1);
+    }
+
+    #[test]
+    fn test_long_list_drops_without_overflow() {
+        // Miri 跑得很慢，只用短一些的链表检查内存安全
+        let len = if cfg!(miri) { 1_000 } else { 100_000 };
+        let list: RcList<u32> = (0..len).collect();
+        assert_eq!(list.len(), len as usize);
+    }
+}
@@ -0,0 +1,389 @@
+// ============================================
+// 确定性调度器 - 让并发示例每次输出同一种交错
+// ============================================
+//
+// 真实的线程由操作系统调度，两次运行的打印顺序几乎不可能相同。
+// 这里的调度器让参与的线程"轮流"执行：任意时刻只有一个线程在跑，
+// 只有在 sleep / 等待（recv、join、屏障）这些本来就会让出 CPU 的地方才切换。
+//
+// - 逻辑时钟：`sleep(d)` 不真的睡眠，而是把线程的唤醒时间设为 now + d，
+//   调度器总是挑唤醒时间最早的线程，所以 sleep 时间短的线程先醒
+// - 种子：唤醒时间相同的线程之间用带种子的伪随机数决定先后
+//
+// 同一个种子得到同一条执行轨迹；换一个种子就是另一条轨迹。
+// 因为切换只发生在真实程序也可能切换的位置，每条轨迹都是真实运行时
+// 可能出现的一种执行顺序，适合拿来在课堂上逐行讨论。
+
+use std::sync::mpsc::{Receiver, RecvError, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// 全局种子：设置后，支持确定性模式的示例改用调度器运行
+static SEED: Mutex<Option<u64>> = Mutex::new(None);
+
+/// 设置（或清除）确定性模式的种子
+pub fn set_seed(seed: Option<u64>) {
+    *SEED.lock().unwrap_or_else(PoisonError::into_inner) = seed;
+}
+
+/// 当前的种子；`None` 表示使用真实的操作系统调度
+pub fn seed() -> Option<u64> {
+    *SEED.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// xorshift64*：足够做调度决策的小型伪随机数生成器
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // splitmix64 打散种子，避免 0 或相近的种子产生相近的序列
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Rng((z ^ (z >> 31)) | 1)
+    }
+
+    /// 返回 `0..n` 之间的数
+    pub fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) % n as u64) as usize
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Status {
+    /// 可以运行（到了唤醒时间后）
+    Runnable,
+    /// 等待的条件还不满足，直到别的线程执行过才会重新检查
+    Blocked,
+    Finished,
+}
+
+struct Slot {
+    status: Status,
+    /// 逻辑时钟上的唤醒时间（纳秒）
+    wake_at: u64,
+}
+
+struct State {
+    rng: Rng,
+    /// 逻辑时钟（纳秒）
+    now: u64,
+    /// 当前持有执行权的线程
+    current: usize,
+    threads: Vec<Slot>,
+    /// 每次切换后运行的线程编号，可以用来复盘执行过程
+    trace: Vec<usize>,
+}
+
+/// 确定性调度器，由 [`Scheduler::run`] 创建
+pub struct Scheduler {
+    state: Mutex<State>,
+    turn: Condvar,
+}
+
+/// 在调度器下运行的一个线程的句柄，只能在该线程里使用
+pub struct ThreadCtx {
+    scheduler: Arc<Scheduler>,
+    id: usize,
+}
+
+/// [`ThreadCtx::spawn`] 返回的句柄
+pub struct DetJoinHandle<T> {
+    id: usize,
+    handle: JoinHandle<T>,
+}
+
+impl Scheduler {
+    /// 用给定种子运行 `main`，返回执行轨迹（每次切换后运行的线程编号）
+    ///
+    /// `main` 自己是 0 号线程；返回前会等待它 spawn 的所有线程结束。
+    pub fn run<F>(seed: u64, main: F) -> Vec<usize>
+    where
+        F: FnOnce(&ThreadCtx),
+    {
+        let scheduler = Arc::new(Scheduler {
+            state: Mutex::new(State {
+                rng: Rng::new(seed),
+                now: 0,
+                current: 0,
+                threads: vec![Slot {
+                    status: Status::Runnable,
+                    wake_at: 0,
+                }],
+                trace: vec![0],
+            }),
+            turn: Condvar::new(),
+        });
+        let ctx = ThreadCtx {
+            scheduler: Arc::clone(&scheduler),
+            id: 0,
+        };
+
+        main(&ctx);
+        ctx.wait_until(|| {
+            let state = scheduler.lock();
+            state.threads[1..]
+                .iter()
+                .all(|slot| slot.status == Status::Finished)
+        });
+        scheduler.finish(0);
+
+        let state = scheduler.lock();
+        state.trace.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 选出下一个运行的线程并把执行权交给它；返回 `false` 表示没有可运行的线程
+    fn pick_next(&self, state: &mut State) -> bool {
+        let candidates: Vec<usize> = (0..state.threads.len())
+            .filter(|&i| state.threads[i].status == Status::Runnable)
+            .collect();
+        let earliest = match candidates.iter().map(|&i| state.threads[i].wake_at).min() {
+            Some(earliest) => earliest,
+            None => return false,
+        };
+        let ready: Vec<usize> = candidates
+            .into_iter()
+            .filter(|&i| state.threads[i].wake_at == earliest)
+            .collect();
+
+        let next = ready[state.rng.below(ready.len())];
+        state.now = state.now.max(earliest);
+        state.current = next;
+        state.trace.push(next);
+        self.turn.notify_all();
+        true
+    }
+
+    /// 让出执行权，并等到再次轮到 `me`
+    fn switch(&self, me: usize, mut state: MutexGuard<'_, State>) {
+        if !self.pick_next(&mut state) {
+            let blocked = state
+                .threads
+                .iter()
+                .filter(|slot| slot.status == Status::Blocked)
+                .count();
+            drop(state);
+            panic!("确定性调度: {} 个线程都在等待彼此，发生死锁", blocked);
+        }
+        while state.current != me {
+            state = self
+                .turn
+                .wait(state)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+    }
+
+    /// 有线程运行过代码，共享状态可能变了：让所有等待中的线程重新检查条件
+    fn wake_blocked(state: &mut State) {
+        let now = state.now;
+        for slot in &mut state.threads {
+            if slot.status == Status::Blocked {
+                slot.status = Status::Runnable;
+                slot.wake_at = now;
+            }
+        }
+    }
+
+    fn finish(&self, me: usize) {
+        let mut state = self.lock();
+        state.threads[me].status = Status::Finished;
+        Self::wake_blocked(&mut state);
+        // 所有线程都结束时没有下一个可以运行，这是正常退出
+        self.pick_next(&mut state);
+    }
+}
+
+/// 线程结束（包括 panic）时交出执行权
+struct FinishGuard<'a>(&'a ThreadCtx);
+
+impl Drop for FinishGuard<'_> {
+    fn drop(&mut self) {
+        self.0.scheduler.finish(self.0.id);
+    }
+}
+
+impl ThreadCtx {
+    /// 创建一个受调度器控制的线程，它会等到轮到自己时才开始执行
+    pub fn spawn<F, T>(&self, f: F) -> DetJoinHandle<T>
+    where
+        F: FnOnce(&ThreadCtx) -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let id = {
+            let mut state = self.scheduler.lock();
+            let now = state.now;
+            state.threads.push(Slot {
+                status: Status::Runnable,
+                wake_at: now,
+            });
+            state.threads.len() - 1
+        };
+
+        let ctx = ThreadCtx {
+            scheduler: Arc::clone(&self.scheduler),
+            id,
+        };
+        let handle = thread::spawn(move || {
+            {
+                let mut state = ctx.scheduler.lock();
+                while state.current != ctx.id {
+                    state = ctx
+                        .scheduler
+                        .turn
+                        .wait(state)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+            }
+            let _finish = FinishGuard(&ctx);
+            f(&ctx)
+        });
+
+        DetJoinHandle { id, handle }
+    }
+
+    /// 逻辑上睡眠 `duration`：让出执行权，唤醒时间到了才可能再被选中
+    pub fn sleep(&self, duration: Duration) {
+        let mut state = self.scheduler.lock();
+        let wake_at = state.now + duration.as_nanos() as u64;
+        state.threads[self.id].wake_at = wake_at;
+        Scheduler::wake_blocked(&mut state);
+        self.scheduler.switch(self.id, state);
+    }
+
+    /// 阻塞直到 `condition` 成立；条件不成立时让别的线程先运行
+    pub fn wait_until<F>(&self, mut condition: F)
+    where
+        F: FnMut() -> bool,
+    {
+        let mut ran_since_switch = true;
+        while !condition() {
+            let mut state = self.scheduler.lock();
+            if ran_since_switch {
+                // 检查条件之前这个线程可能已经改了共享状态
+                Scheduler::wake_blocked(&mut state);
+            }
+            state.threads[self.id].status = Status::Blocked;
+            self.scheduler.switch(self.id, state);
+            ran_since_switch = false;
+        }
+    }
+
+    /// 相当于 `rx.recv()`：没有消息时让出执行权，所有发送端都释放后返回 `Err`
+    pub fn recv<T>(&self, rx: &Receiver<T>) -> Result<T, RecvError> {
+        let mut result = None;
+        self.wait_until(|| match rx.try_recv() {
+            Ok(value) => {
+                result = Some(Ok(value));
+                true
+            }
+            Err(TryRecvError::Disconnected) => {
+                result = Some(Err(RecvError));
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+        });
+        result.unwrap()
+    }
+
+    /// 等待线程结束并取回它的返回值
+    pub fn join<T>(&self, handle: DetJoinHandle<T>) -> thread::Result<T> {
+        let scheduler = &self.scheduler;
+        self.wait_until(|| scheduler.lock().threads[handle.id].status == Status::Finished);
+        handle.handle.join()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    /// 两个线程交替记录事件，返回事件顺序
+    fn interleaving(seed: u64) -> Vec<String> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let main_log = Arc::clone(&log);
+        Scheduler::run(seed, move |ctx| {
+            let child_log = Arc::clone(&main_log);
+            let handle = ctx.spawn(move |ctx| {
+                for i in 0..5 {
+                    child_log.lock().unwrap().push(format!("child {}", i));
+                    ctx.sleep(Duration::from_millis(1));
+                }
+            });
+            for i in 0..5 {
+                main_log.lock().unwrap().push(format!("main {}", i));
+                ctx.sleep(Duration::from_millis(1));
+            }
+            ctx.join(handle).unwrap();
+        });
+        let log = log.lock().unwrap().clone();
+        log
+    }
+
+    #[test]
+    fn test_same_seed_same_interleaving() {
+        for seed in 0..10 {
+            assert_eq!(interleaving(seed), interleaving(seed));
+        }
+        let distinct: std::collections::HashSet<Vec<String>> = (0..10).map(interleaving).collect();
+        assert!(distinct.len() > 1, "不同的种子应该能产生不同的交错");
+    }
+
+    #[test]
+    fn test_logical_clock_orders_sleepers() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let main_log = Arc::clone(&log);
+        Scheduler::run(7, move |ctx| {
+            for i in (0..4u64).rev() {
+                let log = Arc::clone(&main_log);
+                ctx.spawn(move |ctx| {
+                    ctx.sleep(Duration::from_millis(10 * i));
+                    log.lock().unwrap().push(i);
+                });
+            }
+        });
+        assert_eq!(*log.lock().unwrap(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_recv_until_disconnected() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let main_received = Arc::clone(&received);
+        Scheduler::run(3, move |ctx| {
+            let (tx, rx) = mpsc::channel();
+            for p in 0..2 {
+                let tx = tx.clone();
+                ctx.spawn(move |ctx| {
+                    for i in 0..3 {
+                        tx.send(p * 10 + i).unwrap();
+                        ctx.sleep(Duration::ZERO);
+                    }
+                });
+            }
+            drop(tx);
+            while let Ok(value) = ctx.recv(&rx) {
+                main_received.lock().unwrap().push(value);
+            }
+        });
+        let mut received = received.lock().unwrap().clone();
+        received.sort_unstable();
+        assert_eq!(received, vec![0, 1, 2, 10, 11, 12]);
+    }
+
+    #[test]
+    #[should_panic(expected = "死锁")]
+    fn test_deadlock_is_reported() {
+        Scheduler::run(0, |ctx| {
+            let (_tx, rx) = mpsc::channel::<()>();
+            let _ = ctx.recv(&rx);
+        });
+    }
+}
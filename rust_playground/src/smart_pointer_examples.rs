@@ -3,23 +3,42 @@
 // ============================================
 
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::arena_graph::Graph;
+use crate::arena_tree::{ArenaTree, TreeId};
+use crate::bench;
+use crate::event_bus::{EventBus, Subscriber};
+use crate::graph_printer::{self, GraphNode};
+use crate::leak_tracker::{LeakScope, Tracked};
+use crate::lesson::{self, Difficulty, Registry, NONDETERMINISTIC, SLOW};
+use crate::my_box::{unsize, MyBox};
+use crate::my_rc::{rc, MyArc, MyRc, MyWeak};
+use crate::my_refcell::MyRefCell;
+use crate::persistent_list::PersistentList;
+use crate::quota::{FileMessenger, MockMessenger, QuotaConfig, QuotaTracker, StdoutMessenger};
+use crate::raw_list::RawList;
+use crate::rc_list::RcList;
+use crate::tree::TreeNode;
+use crate::weak_cache::{LruCache, ManualClock, SyncLruCache};
 
 /// Box<T> - 堆分配
 pub fn box_basics() {
-    println!("\n=== 1. Box<T> 基础 ===");
+    outln!("\n=== 1. Box<T> 基础 ===");
 
     // 在堆上存储数据
     let b = Box::new(5);
-    println!("b = {}", b);
+    outln!("b = {}", b);
 
     // Box 的主要用途：
     // 1. 当有一个在编译时未知大小的类型
     // 2. 当有大量数据并希望转移所有权但确保数据不被拷贝
     // 3. 当希望拥有一个值并只关心它实现了特定 trait
 
-    // 用途1: 递归类型
+    // 用途1: 递归类型（泛型、可共享尾部的版本见 persistent_list.rs）
     #[derive(Debug)]
     enum List {
         Cons(i32, Box<List>),
@@ -29,16 +48,16 @@ pub fn box_basics() {
     use List::{Cons, Nil};
 
     let list = Cons(1, Box::new(Cons(2, Box::new(Cons(3, Box::new(Nil))))));
-    println!("list: {:?}", list);
+    outln!("list: {:?}", list);
 
     // 用途2: 避免栈溢出
     let large_data = Box::new([0; 1_000_000]); // 1MB 的数据在堆上
-    println!("大数据已分配在堆上，长度: {}", large_data.len());
+    outln!("大数据已分配在堆上，长度: {}", large_data.len());
 }
 
 /// Deref trait - 解引用
 pub fn deref_trait() {
-    println!("\n=== 2. Deref Trait ===");
+    outln!("\n=== 2. Deref Trait ===");
 
     // Box 实现了 Deref，可以像引用一样使用
     let x = 5;
@@ -47,7 +66,7 @@ pub fn deref_trait() {
     assert_eq!(5, x);
     assert_eq!(5, *y); // 解引用 Box
 
-    println!("*y = {}", *y);
+    outln!("*y = {}", *y);
 
     // 自定义智能指针
     struct MyBox<T>(T);
@@ -68,15 +87,16 @@ pub fn deref_trait() {
 
     let y = MyBox::new(x);
     assert_eq!(5, *y); // 现在可以解引用了
-    println!("MyBox: *y = {}", *y);
+    outln!("MyBox: *y = {}", *y);
+    outln!("💡 这个 MyBox 只是包了一层，值还在栈上；真正分配堆内存的版本见 my_box 示例");
 }
 
 /// Deref 强制转换
 pub fn deref_coercion() {
-    println!("\n=== 3. Deref 强制转换 ===");
+    outln!("\n=== 3. Deref 强制转换 ===");
 
     fn hello(name: &str) {
-        println!("Hello, {}!", name);
+        outln!("Hello, {}!", name);
     }
 
     let m = Box::new(String::from("Rust"));
@@ -91,7 +111,7 @@ pub fn deref_coercion() {
 
 /// Drop trait - 清理代码
 pub fn drop_trait() {
-    println!("\n=== 4. Drop Trait ===");
+    outln!("\n=== 4. Drop Trait ===");
 
     struct CustomSmartPointer {
         data: String,
@@ -99,7 +119,7 @@ pub fn drop_trait() {
 
     impl Drop for CustomSmartPointer {
         fn drop(&mut self) {
-            println!("释放 CustomSmartPointer: `{}`!", self.data);
+            outln!("释放 CustomSmartPointer: `{}`!", self.data);
         }
     }
 
@@ -110,44 +130,38 @@ pub fn drop_trait() {
         let d = CustomSmartPointer {
             data: String::from("other stuff"),
         };
-        println!("CustomSmartPointers 已创建");
+        outln!("CustomSmartPointers 已创建");
     } // c 和 d 离开作用域，drop 被调用
 
-    println!("作用域结束");
+    outln!("作用域结束");
 
     // 提前释放值
     let c = CustomSmartPointer {
         data: String::from("some data"),
     };
-    println!("CustomSmartPointer 已创建");
+    outln!("CustomSmartPointer 已创建");
     drop(c); // 显式调用 drop
-    println!("CustomSmartPointer 已在作用域结束前释放");
+    outln!("CustomSmartPointer 已在作用域结束前释放");
 }
 
 /// Rc<T> - 引用计数
 pub fn rc_basics() {
-    println!("\n=== 5. Rc<T> 引用计数智能指针 ===");
+    outln!("\n=== 5. Rc<T> 引用计数智能指针 ===");
 
-    #[derive(Debug)]
-    enum List {
-        Cons(i32, Rc<List>),
-        Nil,
-    }
+    // PersistentList 的每个节点都放在 Rc 里，push_front 让新节点共享旧列表
+    let a = PersistentList::new().push_front(10).push_front(5);
+    outln!("创建 a 后引用计数 = {}", a.head_ref_count());
 
-    use List::{Cons, Nil};
-
-    let a = Rc::new(Cons(5, Rc::new(Cons(10, Rc::new(Nil)))));
-    println!("创建 a 后引用计数 = {}", Rc::strong_count(&a));
-
-    let b = Cons(3, Rc::clone(&a)); // 增加引用计数
-    println!("创建 b 后引用计数 = {}", Rc::strong_count(&a));
+    let b = a.push_front(3); // 增加引用计数
+    outln!("创建 b 后引用计数 = {}", a.head_ref_count());
 
     {
-        let c = Cons(4, Rc::clone(&a)); // 增加引用计数
-        println!("创建 c 后引用计数 = {}", Rc::strong_count(&a));
+        let c = a.push_front(4); // 增加引用计数
+        outln!("创建 c 后引用计数 = {}", a.head_ref_count());
     } // c 离开作用域，引用计数减1
 
-    println!("c 离开作用域后引用计数 = {}", Rc::strong_count(&a));
+    outln!("c 离开作用域后引用计数 = {}", a.head_ref_count());
+    outln!("a = {}, b = {}", a, b);
 
     // Rc 只能用于单线程场景
     // Rc 只允许不可变引用
@@ -155,7 +169,7 @@ pub fn rc_basics() {
 
 /// RefCell<T> - 内部可变性
 pub fn refcell_basics() {
-    println!("\n=== 6. RefCell<T> 内部可变性 ===");
+    outln!("\n=== 6. RefCell<T> 内部可变性 ===");
 
     // 借用规则：
     // - 编译时检查 vs 运行时检查
@@ -166,54 +180,92 @@ pub fn refcell_basics() {
 
     // 不可变借用
     let a = x.borrow();
-    println!("a = {}", a);
+    outln!("a = {}", a);
     drop(a); // 释放借用
 
     // 可变借用
     *x.borrow_mut() += 10;
-    println!("x after mutation = {}", x.borrow());
+    outln!("x after mutation = {}", x.borrow());
+
+    // 演示运行时借用检查：不能同时有可变和不可变借用
+    use std::panic::{self, AssertUnwindSafe};
 
-    // 演示运行时借用检查
     let y = RefCell::new(5);
     let _a = y.borrow();
-    // let _b = y.borrow_mut(); // ⚠️  运行时 panic！不能同时有可变和不可变借用
+    outln!("\n已经有不可变借用时 try_borrow_mut: {:?}", y.try_borrow_mut().map(|_| ()));
+
+    // 直接 borrow_mut 会 panic（panic 信息打印在 stderr），这里用 catch_unwind 接住
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _b = y.borrow_mut(); // ⚠️  运行时 panic！
+    }));
+    // panic 的原文随工具链版本变化，这里只报告有没有 panic
+    outln!(
+        "已经有不可变借用时 borrow_mut: {}",
+        if result.is_err() { "panic 了" } else { "没有 panic" }
+    );
+
+    // 标准库只说"已经被借用"，手写的 MyRefCell 在 debug 构建下还会指出是在哪里借走的
+    let z = MyRefCell::new(5);
+    let _reader = z.borrow();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _writer = z.borrow_mut();
+    }));
+    // 借用位置形如 `（借用位置: 文件:行:列）`，行号随代码变化，只输出前面的部分
+    let message = panic_message(result.unwrap_err());
+    let (summary, has_location) = match message.split_once('（') {
+        Some((summary, _)) => (summary, true),
+        None => (message.as_str(), false),
+    };
+    outln!("MyRefCell 的 panic: {}", summary);
+    outln!(
+        "panic 信息里有借用位置 ⇔ debug 构建: {}",
+        has_location == cfg!(debug_assertions)
+    );
+
+    // 借用都结束之后，replace / get_mut / into_inner 的用法和 std 一样
+    drop(_reader);
+    let mut z = z;
+    outln!("replace 换出旧值: {}", z.replace(6));
+    *z.get_mut() += 1; // 独占了 MyRefCell，不需要运行时检查
+    outln!("into_inner: {}", z.into_inner());
 }
 
-/// Rc<T> + RefCell<T> 组合
-pub fn rc_refcell_combination() {
-    println!("\n=== 7. Rc<RefCell<T>> 组合使用 ===");
-
-    #[derive(Debug)]
-    enum List {
-        Cons(Rc<RefCell<i32>>, Rc<List>),
-        Nil,
+/// 取出 catch_unwind 接住的 panic 信息
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload.downcast_ref::<&str>().map_or("<非字符串的 panic>", |s| s).to_string(),
     }
+}
 
-    use List::{Cons, Nil};
+/// Rc<T> + RefCell<T> 组合
+pub fn rc_refcell_combination() {
+    outln!("\n=== 7. Rc<RefCell<T>> 组合使用 ===");
 
     let value = Rc::new(RefCell::new(5));
 
-    let a = Rc::new(Cons(Rc::clone(&value), Rc::new(Nil)));
-    let b = Cons(Rc::new(RefCell::new(3)), Rc::clone(&a));
-    let c = Cons(Rc::new(RefCell::new(4)), Rc::clone(&a));
+    // 列表本身不可变，但元素是 Rc<RefCell<i32>>，可以通过共享的 Rc 修改
+    let a = PersistentList::new().push_front(Rc::clone(&value));
+    let b = a.push_front(Rc::new(RefCell::new(3)));
+    let c = a.push_front(Rc::new(RefCell::new(4)));
 
-    println!("修改前:");
-    println!("a = {:?}", a);
-    println!("b = {:?}", b);
-    println!("c = {:?}", c);
+    outln!("修改前:");
+    outln!("a = {:?}", a);
+    outln!("b = {:?}", b);
+    outln!("c = {:?}", c);
 
     // 修改共享值
     *value.borrow_mut() += 10;
 
-    println!("\n修改后:");
-    println!("a = {:?}", a);
-    println!("b = {:?}", b);
-    println!("c = {:?}", c);
+    outln!("\n修改后:");
+    outln!("a = {:?}", a);
+    outln!("b = {:?}", b);
+    outln!("c = {:?}", c);
 }
 
 /// 内部可变性模式
 pub fn interior_mutability_pattern() {
-    println!("\n=== 8. 内部可变性模式 ===");
+    outln!("\n=== 8. 内部可变性模式 ===");
 
     // Mock 对象示例
     trait Messenger {
@@ -278,16 +330,18 @@ pub fn interior_mutability_pattern() {
 
     limit_tracker.set_value(80);
 
-    println!("发送的消息: {:?}", mock_messenger.sent_messages.borrow());
+    outln!("发送的消息: {:?}", mock_messenger.sent_messages.borrow());
+    outln!("📝 可配置阈值、只在级别变化时通知的版本参见 quota_tracker 示例");
 }
 
 /// 引用循环和内存泄漏
 pub fn reference_cycles() {
-    println!("\n=== 9. 引用循环（内存泄漏）===");
+    outln!("\n=== 9. 引用循环（内存泄漏）===");
 
     #[derive(Debug)]
     enum List {
-        Cons(i32, RefCell<Rc<List>>),
+        // Tracked 会把自己登记到泄漏追踪表里，drop 时注销
+        Cons(Tracked<i32>, RefCell<Rc<List>>),
         Nil,
     }
 
@@ -302,165 +356,1035 @@ pub fn reference_cycles() {
         }
     }
 
-    let a = Rc::new(Cons(5, RefCell::new(Rc::new(Nil))));
-    println!("a 初始引用计数 = {}", Rc::strong_count(&a));
+    // 告诉环安全的打印器：节点显示成什么、子节点在哪
+    impl GraphNode for List {
+        type Child = Rc<List>;
+
+        fn label(&self) -> String {
+            match self {
+                Cons(value, _) => format!("Cons({})", **value),
+                Nil => String::from("Nil"),
+            }
+        }
+
+        fn children(&self) -> Vec<Rc<List>> {
+            self.tail().map(|next| Rc::clone(&next.borrow())).into_iter().collect()
+        }
+    }
 
-    let b = Rc::new(Cons(10, RefCell::new(Rc::clone(&a))));
-    println!("创建 b 后 a 引用计数 = {}", Rc::strong_count(&a));
-    println!("b 初始引用计数 = {}", Rc::strong_count(&b));
+    let scope = LeakScope::new();
+
+    let a = Rc::new(Cons(Tracked::new("a (5)", 5), RefCell::new(Rc::new(Nil))));
+    outln!("a 初始引用计数 = {}", Rc::strong_count(&a));
+
+    let b = Rc::new(Cons(Tracked::new("b (10)", 10), RefCell::new(Rc::clone(&a))));
+    outln!("创建 b 后 a 引用计数 = {}", Rc::strong_count(&a));
+    outln!("b 初始引用计数 = {}", Rc::strong_count(&b));
+    outln!("b = {}", graph_printer::render(&*b));
 
     if let Some(link) = a.tail() {
         *link.borrow_mut() = Rc::clone(&b);
     }
 
-    println!("创建循环后 b 引用计数 = {}", Rc::strong_count(&b));
-    println!("创建循环后 a 引用计数 = {}", Rc::strong_count(&a));
+    outln!("创建循环后 b 引用计数 = {}", Rc::strong_count(&b));
+    outln!("创建循环后 a 引用计数 = {}", Rc::strong_count(&a));
+
+    // ⚠️ 如果取消注释会导致栈溢出：derive(Debug) 会沿着环一直递归下去
+    // outln!("a next item = {:?}", a.tail());
+
+    // 环安全的打印器记得走过的路径，绕回来时打印 <cycle #n> 就停下
+    outln!("\n环安全打印 a = {}", graph_printer::render(&*a));
+    outln!("环安全打印 b = {}", graph_printer::render(&*b));
+    outln!("Graphviz DOT（红色虚线是闭合环的那条边）:");
+    outln!("{}", graph_printer::to_dot(&*a, "cycle"));
 
-    // ⚠️ 如果取消注释会导致栈溢出
-    // println!("a next item = {:?}", a.tail());
+    // 离开作用域时 a、b 各自的计数只减到 1：它们互相持有对方，谁也不会被释放
+    drop(a);
+    drop(b);
+    outln!("\n变量 a、b 都已 drop，泄漏检查: {}", scope.report());
+    outln!("💡 修复方法：让其中一个方向用 Weak，见 weak_references");
 }
 
 /// Weak<T> - 弱引用
 pub fn weak_references() {
-    println!("\n=== 10. Weak<T> 弱引用 ===");
+    outln!("\n=== 10. Weak<T> 弱引用 ===");
 
     use std::rc::Weak;
 
     #[derive(Debug)]
     struct Node {
-        value: i32,
+        value: Tracked<i32>,
         parent: RefCell<Weak<Node>>,
         children: RefCell<Vec<Rc<Node>>>,
     }
 
+    let scope = LeakScope::new();
+
     let leaf = Rc::new(Node {
-        value: 3,
+        value: Tracked::new("leaf (3)", 3),
         parent: RefCell::new(Weak::new()),
         children: RefCell::new(vec![]),
     });
 
-    println!("leaf strong = {}, weak = {}",
+    outln!("leaf strong = {}, weak = {}",
              Rc::strong_count(&leaf),
              Rc::weak_count(&leaf));
 
     {
         let branch = Rc::new(Node {
-            value: 5,
+            value: Tracked::new("branch (5)", 5),
             parent: RefCell::new(Weak::new()),
             children: RefCell::new(vec![Rc::clone(&leaf)]),
         });
 
         *leaf.parent.borrow_mut() = Rc::downgrade(&branch);
 
-        println!("branch strong = {}, weak = {}",
+        outln!("branch strong = {}, weak = {}",
                  Rc::strong_count(&branch),
                  Rc::weak_count(&branch));
 
-        println!("leaf strong = {}, weak = {}",
+        outln!("leaf strong = {}, weak = {}",
                  Rc::strong_count(&leaf),
                  Rc::weak_count(&leaf));
     }
 
-    println!("leaf parent = {:?}", leaf.parent.borrow().upgrade());
-    println!("leaf strong = {}, weak = {}",
+    outln!("leaf parent = {:?}", leaf.parent.borrow().upgrade());
+    outln!("leaf strong = {}, weak = {}",
              Rc::strong_count(&leaf),
              Rc::weak_count(&leaf));
+
+    // 父子之间一强一弱，没有循环：两个节点都能被回收
+    drop(leaf);
+    outln!("\nleaf 也 drop 之后，泄漏检查: {}", scope.report());
 }
 
 /// 智能指针选择指南
 pub fn smart_pointer_guide() {
-    println!("\n=== 11. 智能指针选择指南 ===");
-
-    println!("\n📦 Box<T>:");
-    println!("  ✓ 在堆上分配值");
-    println!("  ✓ 递归类型");
-    println!("  ✓ 大量数据的所有权转移");
-    println!("  ✓ trait 对象");
-
-    println!("\n📊 Rc<T>:");
-    println!("  ✓ 多个所有者（单线程）");
-    println!("  ✓ 只读数据共享");
-    println!("  ✗ 不可变引用");
-
-    println!("\n🔄 RefCell<T>:");
-    println!("  ✓ 运行时借用检查");
-    println!("  ✓ 内部可变性");
-    println!("  ✓ 单线程场景");
-    println!("  ⚠️  运行时 panic 风险");
-
-    println!("\n🔗 Rc<RefCell<T>>:");
-    println!("  ✓ 多个所有者 + 可变性");
-    println!("  ✓ 单线程复杂数据结构");
-
-    println!("\n🪶 Weak<T>:");
-    println!("  ✓ 避免引用循环");
-    println!("  ✓ 父子关系");
-    println!("  ✓ 缓存");
-
-    println!("\n🧵 Arc<T> + Mutex<T>:");
-    println!("  ✓ 多线程多个所有者");
-    println!("  ✓ 线程间共享可变数据");
-    println!("  📝 参见并发示例");
+    outln!("\n=== 11. 智能指针选择指南 ===");
+
+    outln!("\n📦 Box<T>:");
+    outln!("  ✓ 在堆上分配值");
+    outln!("  ✓ 递归类型");
+    outln!("  ✓ 大量数据的所有权转移");
+    outln!("  ✓ trait 对象");
+
+    outln!("\n📊 Rc<T>:");
+    outln!("  ✓ 多个所有者（单线程）");
+    outln!("  ✓ 只读数据共享");
+    outln!("  ✗ 不可变引用");
+
+    outln!("\n🔄 RefCell<T>:");
+    outln!("  ✓ 运行时借用检查");
+    outln!("  ✓ 内部可变性");
+    outln!("  ✓ 单线程场景");
+    outln!("  ⚠️  运行时 panic 风险");
+
+    outln!("\n🔗 Rc<RefCell<T>>:");
+    outln!("  ✓ 多个所有者 + 可变性");
+    outln!("  ✓ 单线程复杂数据结构");
+
+    outln!("\n🪶 Weak<T>:");
+    outln!("  ✓ 避免引用循环");
+    outln!("  ✓ 父子关系");
+    outln!("  ✓ 缓存（参见 weak_cache 示例）");
+
+    outln!("\n🧵 Arc<T> + Mutex<T>:");
+    outln!("  ✓ 多线程多个所有者");
+    outln!("  ✓ 线程间共享可变数据");
+    outln!("  📝 参见并发示例");
 }
 
 /// 实用示例：树形数据结构
 pub fn tree_example() {
-    println!("\n=== 12. 实用示例：树形结构 ===");
+    outln!("\n=== 12. 实用示例：树形结构 ===");
 
-    use std::rc::Weak;
+    // 和 tree.rs 用同一个 Rc：加上 `--cfg my_rc` 时是手写的 MyRc
+    use crate::my_rc::rc::Rc;
 
-    #[derive(Debug)]
-    struct TreeNode {
-        value: i32,
-        parent: RefCell<Weak<TreeNode>>,
-        children: RefCell<Vec<Rc<TreeNode>>>,
-    }
-
-    impl TreeNode {
-        fn new(value: i32) -> Rc<TreeNode> {
-            Rc::new(TreeNode {
-                value,
-                parent: RefCell::new(Weak::new()),
-                children: RefCell::new(vec![]),
+    // TreeNode 定义在 tree.rs：父节点用 Rc 拥有子节点，子节点用 Weak 指回父节点
+    let root = TreeNode::new(1);
+    let child1 = TreeNode::new(2);
+    let child2 = TreeNode::new(3);
+
+    TreeNode::add_child(&root, Rc::clone(&child1));
+    TreeNode::add_child(&root, Rc::clone(&child2));
+    TreeNode::add_child(&child1, TreeNode::new(4));
+    TreeNode::add_child(&child1, TreeNode::new(5));
+    TreeNode::add_child(&child2, TreeNode::new(6));
+
+    outln!("根节点: {:?}", root.value);
+    outln!("子节点数量: {}", root.child_count());
+    outln!("整棵树 {} 个节点，高度 {}", TreeNode::size(&root), root.height());
+    out!("{}", root);
+
+    outln!("\n先序遍历: {:?}", values(TreeNode::pre_order(&root)));
+    outln!("后序遍历: {:?}", values(TreeNode::post_order(&root)));
+    outln!("层序遍历: {:?}", values(TreeNode::level_order(&root)));
+
+    let leaf = TreeNode::pre_order(&root).find(|node| node.value == 5).unwrap();
+    outln!("\n节点 5 的深度: {}，是叶子: {}", leaf.depth(), leaf.is_leaf());
+    outln!("节点 5 所在树的根: {}", TreeNode::root(&leaf).value);
+    outln!("节点 5 到根的路径: {:?}", values(TreeNode::path_to_root(&leaf)));
+
+    // 换父节点：整棵子树跟着搬过去
+    TreeNode::add_child(&child2, Rc::clone(&child1));
+    outln!("\n把节点 2 挂到节点 3 下面:");
+    out!("{}", root);
+    outln!("节点 5 的深度变成: {}", leaf.depth());
+
+    // 摘下子树：父节点不再持有它，外面也没有别的 Rc，它就被释放了
+    let weak = Rc::downgrade(&child1);
+    drop(child1);
+    TreeNode::detach(&weak.upgrade().unwrap());
+    outln!("\n摘下节点 2 之后:");
+    out!("{}", root);
+    outln!("节点 2 的 strong = {}，已释放: {}", weak.strong_count(), weak.upgrade().is_none());
+    // 节点 5 还被 leaf 持有，所以还活着，但它指向父节点的 Weak 已经失效
+    outln!("节点 5 还在，它的父节点: {:?}", leaf.parent().map(|node| node.value));
+
+    // 按位置摘：返回的子树成了一棵独立的树
+    let six = TreeNode::remove_child(&child2, 0).unwrap();
+    outln!("\nremove_child 摘下节点 {}，它现在是根: {}", six.value, six.is_root());
+    out!("{}", root);
+
+    outln!("\n💡 子节点指向父节点用的是 Weak，摘下来的子树没人持有就会被立刻释放");
+}
+
+fn values(nodes: impl IntoIterator<Item = rc::Rc<TreeNode<i32>>>) -> Vec<i32> {
+    nodes.into_iter().map(|node| node.value).collect()
+}
+
+/// 实用示例：持久化链表
+pub fn persistent_list() {
+    outln!("\n=== 13. 实用示例：持久化链表 ===");
+
+    // 前面各个示例里的 List 枚举只能放 i32；PersistentList<T> 是泛型的
+    let a: PersistentList<i32> = [5, 10].into_iter().collect();
+    let b = a.push_front(3);
+    let c = a.push_front(4);
+    outln!("a = {}", a);
+    outln!("b = {}", b);
+    outln!("c = {}", c);
+
+    // push_front 不会改动 a，b 和 c 的尾部就是 a 本身
+    outln!("\nb 的尾部和 a 是同一段内存: {}", b.tail().ptr_eq(&a));
+    outln!("c 的尾部和 a 是同一段内存: {}", c.tail().ptr_eq(&a));
+    outln!("a 的头节点被 {} 处引用（a、b、c 各一处）", a.head_ref_count());
+
+    let words: PersistentList<&str> = "persistent lists share".split(' ').collect();
+    outln!("\n泛型元素: {}", words);
+    outln!("反转: {}", words.reverse());
+    outln!("长度: {}，第一个: {:?}", words.len(), words.head());
+    let total: usize = words.iter().map(|word| word.len()).sum();
+    outln!("iter 统计字母数: {}", total);
+
+    // 默认的递归 drop 在这里会栈溢出，PersistentList 的 Drop 用循环逐个拆节点
+    let long: PersistentList<u32> = (0..1_000_000).collect();
+    outln!("\n创建了 {} 个节点的列表", long.len());
+    drop(long);
+    outln!("drop 完成，没有栈溢出");
+
+    outln!("\n💡 节点创建后就不再修改，所以持久化链表不会形成引用循环");
+}
+
+/// 手写 Box：用 std::alloc 分配和释放
+pub fn my_box() {
+    outln!("\n=== 14. 手写 Box：std::alloc ===");
+
+    use std::fmt::Display;
+    use std::mem::size_of;
+
+    struct Noisy(&'static str);
+
+    impl Drop for Noisy {
+        fn drop(&mut self) {
+            outln!("  drop Noisy({})", self.0);
+        }
+    }
+
+    // MyBox::new 向分配器要一块内存，把值写进去
+    let mut b = MyBox::new(vec![1, 2]);
+    b.push(3); // DerefMut
+    outln!("MyBox<Vec<i32>> = {:?}，长度 {}", b, b.len());
+
+    // clone 会重新分配一块内存，两份数据互不影响
+    let mut copy = b.clone();
+    copy.push(4);
+    outln!("clone 后修改副本: 原来 {:?}，副本 {:?}", b, copy);
+
+    outln!("\ndrop 的顺序：先 drop 里面的值，再释放内存");
+    {
+        let _noisy = MyBox::new(Noisy("a"));
+        outln!("  离开作用域...");
+    }
+    let inner = MyBox::into_inner(MyBox::from(Noisy("b")));
+    outln!("  into_inner 把值移出来，内存已释放，值还活着: Noisy({})", inner.0);
+    drop(inner);
+
+    // 胖指针：unsized 类型的 MyBox 除了地址，还带着长度或 vtable
+    outln!("\n指针大小:");
+    outln!("  MyBox<i32>          = {} 字节", size_of::<MyBox<i32>>());
+    outln!("  MyBox<[i32]>        = {} 字节（地址 + 长度）", size_of::<MyBox<[i32]>>());
+    outln!("  MyBox<dyn Display>  = {} 字节（地址 + vtable）", size_of::<MyBox<dyn Display>>());
+
+    let slice: MyBox<[i32]> = MyBox::from(vec![3, 1, 2]);
+    outln!("\nMyBox<[i32]> = {:?}", slice);
+
+    let items: Vec<MyBox<dyn Display>> = vec![
+        unsize!(MyBox::new(42) => dyn Display),
+        unsize!(MyBox::new("hello") => dyn Display),
+        unsize!(MyBox::new(1.5) => dyn Display),
+    ];
+    let shown: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+    outln!("Vec<MyBox<dyn Display>> = {:?}", shown);
+
+    outln!("\n💡 std 的 Box 能自动从 Box<i32> 变成 Box<dyn Display>，");
+    outln!("   靠的是还没稳定的 CoerceUnsized，所以这里用 unsize! 宏手动转换");
+}
+
+/// 手写 Rc 和 Weak：计数怎么变化
+pub fn my_rc() {
+    outln!("\n=== 15. 手写 Rc、Weak 和 Arc ===");
+
+    use std::thread;
+
+    // 同样的操作分别作用在标准库的 Rc 和手写的 MyRc 上，计数应该一模一样
+    let std_a = Rc::new(String::from("hi"));
+    let my_a = MyRc::new(String::from("hi"));
+    let show = |step: &str, std_rc: &Rc<String>, my: &MyRc<String>| {
+        outln!(
+            "  {:<14} std: strong={} weak={}   my: strong={} weak={}",
+            step,
+            Rc::strong_count(std_rc),
+            Rc::weak_count(std_rc),
+            MyRc::strong_count(my),
+            MyRc::weak_count(my)
+        );
+    };
+    outln!("计数变化（std::rc::Rc 对照 MyRc）:");
+    show("new", &std_a, &my_a);
+    let (std_b, my_b) = (Rc::clone(&std_a), MyRc::clone(&my_a));
+    show("clone", &std_a, &my_a);
+    let (std_weak, my_weak) = (Rc::downgrade(&std_a), MyRc::downgrade(&my_a));
+    show("downgrade", &std_a, &my_a);
+    drop((std_b, my_b));
+    show("drop(clone)", &std_a, &my_a);
+
+    // 写时复制：共享时 make_mut 会先克隆一份，独占时直接改
+    outln!("\nmake_mut 写时复制:");
+    let mut shared = MyRc::clone(&my_a);
+    MyRc::make_mut(&mut shared).push_str(" there");
+    outln!("  共享时修改: 原来 {:?}，新的 {:?}，指向同一块内存: {}", my_a, shared, MyRc::ptr_eq(&my_a, &shared));
+    MyRc::make_mut(&mut shared).push('!');
+    let unique = MyRc::get_mut(&mut shared).is_some();
+    outln!("  独占时修改: {:?}，get_mut 也能拿到: {}", shared, unique);
+
+    // 值被释放以后，Weak 还在，但已经升级不了了
+    outln!("\n释放值以后:");
+    let other = MyRc::clone(&my_a);
+    let my_a = MyRc::try_unwrap(my_a).unwrap_err();
+    outln!("  还有别的强引用时 try_unwrap 失败，MyRc 原样还回来: {:?}", my_a);
+    drop(other);
+    outln!("  只剩一个强引用时成功: {:?}", MyRc::try_unwrap(my_a));
+    outln!("  std Weak 升级: {:?}，strong={}", std_weak.upgrade(), std_weak.strong_count());
+    drop(std_a);
+    outln!("  std Weak 升级: {:?}", std_weak.upgrade());
+    outln!("  my  Weak 升级: {:?}，strong={} weak={}", my_weak.upgrade(), my_weak.strong_count(), my_weak.weak_count());
+    outln!("  MyWeak::new() 升级: {:?}", MyWeak::<String>::new().upgrade());
+
+    // MyArc：计数换成原子类型，可以在线程之间共享
+    outln!("\nMyArc 跨线程:");
+    let total = MyArc::new((1..=100).collect::<Vec<u64>>());
+    let watcher = MyArc::downgrade(&total);
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let numbers = MyArc::clone(&total);
+            thread::spawn(move || numbers[i * 25..(i + 1) * 25].iter().sum::<u64>())
+        })
+        .collect();
+    let sums: Vec<u64> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    outln!("  4 个线程分段求和: {:?}，合计 {}", sums, sums.iter().sum::<u64>());
+    outln!("  线程结束后: strong={} weak={}", MyArc::strong_count(&total), MyArc::weak_count(&total));
+    outln!("  从 MyArcWeak 这边看: strong={} weak={}", watcher.strong_count(), watcher.weak_count());
+
+    // 还有别的引用时不能原地修改，make_mut 会复制出一份新的
+    let mut copy = MyArc::clone(&total);
+    outln!("  有其他引用时 get_mut: {:?}", MyArc::get_mut(&mut copy).map(|v| v.len()));
+    MyArc::make_mut(&mut copy).push(101);
+    outln!("  make_mut 之后还指向同一块内存: {}", MyArc::ptr_eq(&copy, &total));
+    outln!("  新的那份只有一个引用，try_unwrap 拿回 {} 个元素", MyArc::try_unwrap(copy).map_or(0, |v| v.len()));
+    drop(total);
+    outln!("  最后一个 MyArc 释放后 MyArcWeak 升级: {:?}", watcher.upgrade().map(|v| v.len()));
+
+    outln!("\n💡 盒子里的 weak 计数比 Weak 的个数多 1：所有强引用共同持有一个\"隐式弱引用\"，");
+    outln!("   值被 drop 之后盒子还留着，剩下的 Weak 才能读到 strong == 0（weak_count() 会把这个 1 减掉）");
+    outln!("   RUSTFLAGS=\"--cfg my_rc\" cargo test 会让树和持久化链表改用 MyRc");
+}
+
+/// Arena 树：用 NodeId 代替 Rc/Weak
+pub fn arena_tree() {
+    outln!("\n=== 16. Arena 树：用下标代替 Rc/Weak ===");
+
+    // 和第 12 节同一棵树，节点都放在 ArenaTree 里，父子之间只记 id
+    let mut tree = ArenaTree::new();
+    let root = tree.add_node(1);
+    let child1 = tree.add_node(2);
+    let child2 = tree.add_node(3);
+
+    tree.add_child(root, child1);
+    tree.add_child(root, child2);
+    let (n4, n5, n6) = (tree.add_node(4), tree.add_node(5), tree.add_node(6));
+    tree.add_child(child1, n4);
+    tree.add_child(child1, n5);
+    tree.add_child(child2, n6);
+
+    outln!("根节点: {:?}，id = {:?}", tree[root], root);
+    outln!("子节点数量: {}", tree.children(root).len());
+    outln!("整棵树 {} 个节点，高度 {}", tree.size(root), tree.height(root));
+    out!("{}", tree.display(root));
+
+    let values = |ids: Vec<TreeId<i32>>| -> Vec<i32> { ids.into_iter().map(|id| tree[id]).collect() };
+    outln!("\n先序遍历: {:?}", values(tree.pre_order(root).collect()));
+    outln!("后序遍历: {:?}", values(tree.post_order(root).collect()));
+    outln!("层序遍历: {:?}", values(tree.level_order(root).collect()));
+
+    let leaf = tree.pre_order(root).find(|&id| tree[id] == 5).unwrap();
+    outln!("\n节点 5 的深度: {}，是叶子: {}", tree.depth(leaf), tree.is_leaf(leaf));
+    outln!("节点 5 所在树的根: {}", tree[tree.root(leaf)]);
+    outln!("节点 5 到根的路径: {:?}", values(tree.path_to_root(leaf)));
+
+    // 换父节点：不需要 Rc::clone，id 本身就是 Copy 的
+    tree.add_child(child2, child1);
+    outln!("\n把节点 2 挂到节点 3 下面:");
+    out!("{}", tree.display(root));
+    outln!("节点 5 的深度变成: {}", tree.depth(leaf));
+
+    // 摘下子树只是断开父子关系，节点还在 Arena 里；要释放得显式删除
+    tree.detach(child1);
+    outln!("\n摘下节点 2 之后:");
+    out!("{}", tree.display(root));
+    outln!("Arena 里还有 {} 个节点，节点 2 成了另一棵树的根: {}", tree.len(), tree.is_root(child1));
+    outln!("删除这棵子树: {:?}", tree.remove_subtree(child1));
+    outln!("Arena 里剩 {} 个节点，是空的: {}", tree.len(), tree.is_empty());
+
+    // 删掉的节点的 id 过期了；槽位被重用后，代数不同，旧 id 也不会指向新节点
+    let reused = tree.add_node(7);
+    outln!("\n旧 id {:?} 还有效: {}，get_mut: {:?}", leaf, tree.contains(leaf), tree.get_mut(leaf));
+    outln!(
+        "新节点 {:?} 重用了槽位 {}（第 {} 代），查询: {:?}",
+        reused,
+        reused.index(),
+        reused.generation(),
+        tree.get(reused)
+    );
+
+    outln!("\n💡 没有 Rc、Weak、RefCell：修改要 &mut tree，借用冲突在编译期就能发现");
+}
+
+/// Arena 有向图：环不再是问题
+pub fn arena_graph() {
+    outln!("\n=== 17. Arena 有向图：BFS、DFS、找环、拓扑排序 ===");
+
+    // 第 9 节里互相指向的 a、b 会泄漏；放在 Arena 里，边只是 id，图 drop 时全部释放
+    let scope = LeakScope::new();
+    {
+        let mut graph = Graph::new();
+        let a = graph.add_vertex(Tracked::new("a (5)", 5));
+        let b = graph.add_vertex(Tracked::new("b (10)", 10));
+        graph.add_edge(a, b);
+        graph.add_edge(b, a);
+        outln!("a → b → a 成环: {}，环上的值: {:?}", graph.has_cycle(), graph.values(graph.find_cycle().unwrap()));
+    }
+    outln!("图 drop 之后，泄漏检查: {}", scope.report());
+
+    // 构建依赖图：a → b 表示 a 要先完成
+    let mut graph = Graph::new();
+    let fetch = graph.add_vertex("fetch");
+    let config = graph.add_vertex("config");
+    let compile = graph.add_vertex("compile");
+    let test = graph.add_vertex("test");
+    let docs = graph.add_vertex("docs");
+    let release = graph.add_vertex("release");
+    for (from, to) in [(fetch, compile), (config, compile), (compile, test), (compile, docs), (test, release), (docs, release)] {
+        graph.add_edge(from, to);
+    }
+    outln!("\n依赖图（{} 个顶点，{} 条边）:", graph.vertex_count(), graph.edge_count());
+    out!("{}", graph);
+
+    outln!("\n从 fetch 出发 BFS: {:?}", graph.values(graph.bfs(fetch)));
+    outln!("从 fetch 出发 DFS: {:?}", graph.values(graph.dfs(fetch)));
+    match graph.topo_sort() {
+        Ok(order) => outln!("拓扑排序（执行顺序）: {:?}", graph.values(order)),
+        Err(err) => outln!("有环: {:?}", graph.values(err.cycle)),
+    }
+
+    // 加一条反向边就成环了，拓扑排序失败并指出环在哪
+    graph.add_edge(release, compile);
+    outln!("\n加上 release → compile 之后:");
+    match graph.topo_sort() {
+        Ok(order) => outln!("拓扑排序: {:?}", graph.values(order)),
+        Err(err) => outln!("拓扑排序失败，有环: {:?}", graph.values(err.cycle)),
+    }
+    graph.remove_edge(release, compile);
+
+    // 删除顶点会顺带删掉所有相连的边，留下的 id 不会悬空
+    graph.remove_vertex(docs);
+    outln!("\n删除 docs 之后（{} 条边）: {:?}", graph.edge_count(), graph.values(graph.topo_sort().unwrap()));
+    outln!("docs 的旧 id {:?} 还有效: {}，查询: {:?}", docs, graph.contains(docs), graph.get(docs));
+
+    outln!("\n💡 Rc 版本里每条边都要想清楚用 Rc 还是 Weak；Arena 里边就是 id，成环也能正常释放");
+}
+
+/// Arena 和 Rc 的性能对比
+pub fn arena_vs_rc() {
+    outln!("\n=== 18. Arena 和 Rc 的性能对比 ===");
+
+    // TreeNode 的句柄和 tree.rs 用同一个 Rc：加上 `--cfg my_rc` 时是手写的 MyRc
+    use crate::my_rc::rc::Rc;
+
+    // 同样形状的完全二叉树：节点 i 的父节点是 (i - 1) / 2
+    let n: usize = 20_000;
+
+    let build_rc = || {
+        let nodes: Vec<_> = (0..n).map(|i| TreeNode::new(i as u64)).collect();
+        for i in 1..n {
+            TreeNode::add_child(&nodes[(i - 1) / 2], Rc::clone(&nodes[i]));
+        }
+        Rc::clone(&nodes[0])
+    };
+    let build_arena = || {
+        let mut tree = ArenaTree::new();
+        let ids: Vec<_> = (0..n).map(|i| tree.add_node(i as u64)).collect();
+        for i in 1..n {
+            tree.add_child(ids[(i - 1) / 2], ids[i]);
+        }
+        (tree, ids[0])
+    };
+
+    outln!("建树（{} 个节点）:", n);
+    bench::report(&[
+        bench::measure("Rc<TreeNode> + Weak 父指针", 5, n as u64, build_rc),
+        bench::measure("ArenaTree", 5, n as u64, build_arena),
+    ]);
+
+    let rc_root = build_rc();
+    let (tree, arena_root) = build_arena();
+    outln!("\n先序遍历求和:");
+    bench::report(&[
+        bench::measure("Rc：每步克隆子节点的 Rc", 20, n as u64, || {
+            TreeNode::pre_order(&rc_root).map(|node| node.value).sum::<u64>()
+        }),
+        bench::measure("Arena：直接借出子节点 id", 20, n as u64, || {
+            tree.pre_order(arena_root).map(|id| tree[id]).sum::<u64>()
+        }),
+    ]);
+
+    outln!("\n💡 Arena 的节点连续存放，没有引用计数的增减和 RefCell 的借用检查；");
+    outln!("   代价是删除要显式进行，过期的 id 要靠代数来发现");
+}
+
+/// 双向链表：安全版和 unsafe 版
+pub fn doubly_linked_list() {
+    outln!("\n=== 19. 双向链表：Rc<RefCell> 版和裸指针版 ===");
+
+    // 两个版本的接口一样，区别在于拿到的是什么
+    let mut safe: RcList<i32> = (1..=5).collect();
+    let mut raw: RawList<i32> = (1..=5).collect();
+    outln!("RcList  = {:?}", safe);
+    outln!("RawList = {:?}", raw);
+    outln!("长度: {} / {}，是空的: {} / {}", safe.len(), raw.len(), safe.is_empty(), raw.is_empty());
+
+    // 两端的 push / pop
+    safe.push_front(0);
+    raw.push_front(0);
+    outln!(
+        "\npush_front(0) 后 pop_back: {:?} / {:?}",
+        safe.pop_back(),
+        raw.pop_back()
+    );
+
+    // 读：RawList 直接给 &T；RcList 只能给 Ref 守卫或者节点句柄，每次都要 borrow()
+    outln!(
+        "\n第一个元素: RcList 给 Ref<i32> = {}，RawList 给 &i32 = {}",
+        *safe.front().unwrap(),
+        raw.front().unwrap()
+    );
+    outln!("最后一个元素: {} / {}", *safe.back().unwrap(), raw.back().unwrap());
+    let safe_sum: i32 = safe.iter().map(|elem| *elem.borrow()).sum();
+    let raw_sum: i32 = raw.iter().sum();
+    outln!("求和: {} / {}", safe_sum, raw_sum);
+    let backwards: Vec<i32> = safe.iter().rev().map(|elem| *elem.borrow()).collect();
+    outln!("倒着遍历: {:?} / {:?}", backwards, raw.iter().rev().collect::<Vec<_>>());
+
+    // 写：RawList 的 iter_mut 给 &mut T；RcList 的句柄要 borrow_mut()
+    for elem in safe.iter_mut() {
+        // 句柄也能 borrow() 读，但读守卫要先释放，才能 borrow_mut()
+        let value = *elem.borrow();
+        *elem.borrow_mut() = value * 10;
+    }
+    for value in raw.iter_mut() {
+        *value *= 10;
+    }
+    outln!("每个元素乘 10: {:?} / {:?}", safe, raw);
+
+    // 游标：走到第 2 个元素，删掉它，再在原位置前后各插一个
+    {
+        let mut cursor = safe.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        outln!("\n游标在下标 {:?}，当前元素: {:?}", cursor.index(), cursor.current().map(|value| *value));
+        let removed = cursor.remove_current();
+        cursor.insert_before(25);
+        cursor.insert_after(35);
+        cursor.move_prev();
+        outln!("删除 {:?}，前后各插一个，再退一步回到: {:?}", removed, cursor.current().map(|value| *value));
+    }
+    {
+        let mut cursor = raw.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.remove_current();
+        cursor.insert_before(25);
+        cursor.insert_after(35);
+        cursor.move_prev();
+        outln!("RawList 的游标: 下标 {:?}，当前元素 {:?}", cursor.index(), cursor.current());
+    }
+    outln!("  {:?} / {:?}", safe, raw);
+
+    // split_off / append：只改断点两侧的指针，O(1)（找到断点要 O(n)）
+    let mut safe_back = safe.split_off(3);
+    let mut raw_back = raw.split_off(3);
+    outln!("\nsplit_off(3): {:?} + {:?}", safe, safe_back);
+    outln!("              {:?} + {:?}", raw, raw_back);
+    safe_back.append(&mut safe);
+    raw_back.append(&mut raw);
+    outln!("后半段 append 前半段: {:?} / {:?}", safe_back, raw_back);
+
+    outln!("\n💡 安全版的麻烦：");
+    outln!("   - 值在 RefCell 里，iter() 给不出 &T，只能给句柄，用的时候再 borrow()");
+    outln!("   - 句柄攥着节点的 Rc，活着的时候链表没法 pop（try_unwrap 会失败），");
+    outln!("     所以句柄类型要实现 Drop，让编译器把借用延长到它们被 drop");
+    outln!("   - 每个节点多出强弱计数和借用标记，每走一步都要 clone 一次 Rc");
+    outln!("   unsafe 版没有这些开销，但每个指针操作的正确性都得自己证明，所以要用 Miri 跑测试");
+}
+
+/// Weak 的用途之一：缓存
+pub fn weak_cache() {
+    outln!("\n=== 20. Weak 缓存：淘汰之后还能找回来 ===");
+
+    // 假装加载很贵：每次加载都记一笔
+    let loads = RefCell::new(Vec::new());
+    let load = |name: &str| {
+        loads.borrow_mut().push(name.to_string());
+        format!("<{} 的内容>", name)
+    };
+
+    let mut cache = LruCache::new(2);
+    let a = cache.get_or_insert_with("a.txt", || load("a.txt"));
+    cache.get_or_insert_with("b.txt", || load("b.txt"));
+    cache.get_or_insert_with("c.txt", || load("c.txt"));
+    outln!(
+        "容量 {}，依次加载 a、b、c，缓存里剩下 {} 个（最近使用在前）: {:?}",
+        cache.capacity(),
+        cache.len(),
+        cache.keys()
+    );
+    outln!("a 被淘汰了，但还拿在手里: a = {}", a);
+    outln!("被淘汰、但别处还在用的条目: {}", cache.evicted_in_use());
+
+    // a 通过 Weak 找回来，不用重新加载，而且和手里的是同一份
+    let again = cache.get_or_insert_with("a.txt", || load("a.txt"));
+    outln!("\n再要 a: 同一份? {}，缓存: {:?}", Rc::ptr_eq(&a, &again), cache.keys());
+    // 找回 a 又把 b 挤了出去；b 被淘汰时没人拿着，已经释放了，只能重新加载
+    cache.get_or_insert_with("b.txt", || load("b.txt"));
+    outln!("再要 b: 重新加载，缓存: {:?}", cache.keys());
+    // remove 连同 Weak 一起删掉：手里还拿着 a，也不会再被找回
+    cache.remove(&"a.txt");
+    let fresh = cache.get_or_insert_with("a.txt", || load("a.txt"));
+    outln!("remove(a) 之后再要 a: 同一份? {}", Rc::ptr_eq(&a, &fresh));
+    outln!("一共加载了: {:?}", loads.borrow());
+    outln!("统计: {}", cache.stats());
+
+    // TTL：用手动时钟，不用真的等
+    let clock = Arc::new(ManualClock::new());
+    let mut sessions =
+        LruCache::with_ttl(10, Duration::from_secs(30)).with_clock(clock.clone());
+    sessions.insert("alice", "token-1");
+    clock.advance(Duration::from_secs(20));
+    sessions.insert("bob", "token-2");
+    clock.advance(Duration::from_secs(15));
+    outln!("\nTTL 30 秒，35 秒后:");
+    outln!("  alice（20+15 秒前插入）: {:?}", sessions.get(&"alice").as_deref());
+    outln!("  bob（15 秒前插入）: {:?}", sessions.get(&"bob").as_deref());
+    outln!("  统计: {}", sessions.stats());
+    clock.advance(Duration::from_secs(20));
+    outln!("  再过 20 秒 purge_expired 删掉 {} 个，缓存空了: {}", sessions.purge_expired(), sessions.is_empty());
+
+    // 线程安全版：Arc + Mutex，多个线程共用一个缓存
+    let shared = Arc::new(SyncLruCache::new(16));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || {
+                for n in 0..10u64 {
+                    shared.get_or_insert_with(n, || (1..=n).product::<u64>());
+                }
             })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    outln!("\n4 个线程各查 10 个阶乘: {}", shared.stats());
+    outln!("9! = {}", shared.get(&9).unwrap());
+
+    // 接口和 LruCache 一样，TTL、手动时钟、remove、purge_expired 都有
+    let tokens = SyncLruCache::with_ttl(2, Duration::from_secs(30)).with_clock(clock.clone());
+    tokens.insert("alice", "token-1");
+    tokens.insert("bob", "token-2");
+    outln!("\nSyncLruCache 加上 TTL: {:?}（{}/{}）", tokens.keys(), tokens.len(), tokens.capacity());
+    outln!("remove(bob): {:?}", tokens.remove(&"bob").as_deref());
+    clock.advance(Duration::from_secs(30));
+    outln!(
+        "30 秒后 purge_expired 删掉 {} 个，缓存空了: {}，被淘汰但还在用的: {}",
+        tokens.purge_expired(),
+        tokens.is_empty(),
+        tokens.evicted_in_use()
+    );
+
+    outln!("\n💡 缓存只对 capacity 个条目负责；被淘汰的值用 Weak 记着，");
+    outln!("   还有人在用就找回来，没人用了就让它释放，不会在内存里多出一份");
+}
+
+/// 观察者模式：一对多的事件总线
+pub fn event_bus() {
+    outln!("\n=== 21. 事件总线：用 Weak 登记的订阅者 ===");
+
+    struct UserJoined(&'static str);
+    struct ChatMessage {
+        from: &'static str,
+        text: String,
+    }
+
+    // 一个订阅者可以关心好几类事件
+    struct ChatLog {
+        lines: RefCell<Vec<String>>,
+    }
+
+    impl Subscriber<UserJoined> for ChatLog {
+        fn on_event(&self, event: &UserJoined, _: &EventBus) {
+            self.lines.borrow_mut().push(format!("→ {} 加入了", event.0));
         }
+    }
 
-        fn add_child(parent: &Rc<TreeNode>, child: Rc<TreeNode>) {
-            *child.parent.borrow_mut() = Rc::downgrade(parent);
-            parent.children.borrow_mut().push(child);
+    impl Subscriber<ChatMessage> for ChatLog {
+        fn on_event(&self, event: &ChatMessage, _: &EventBus) {
+            self.lines
+                .borrow_mut()
+                .push(format!("{}: {}", event.from, event.text));
         }
     }
 
-    let root = TreeNode::new(1);
-    let child1 = TreeNode::new(2);
-    let child2 = TreeNode::new(3);
+    let bus = EventBus::new();
+    let log = Rc::new(ChatLog {
+        lines: RefCell::new(Vec::new()),
+    });
+    bus.subscribe::<UserJoined, _>(&log);
+    bus.subscribe::<ChatMessage, _>(&log);
+
+    // 处理函数里再发布事件：有人加入时机器人发一条欢迎消息
+    let greeter = Rc::new(|event: &UserJoined, bus: &EventBus| {
+        bus.publish(&ChatMessage {
+            from: "bot",
+            text: format!("欢迎 {}！", event.0),
+        });
+    });
+    bus.subscribe(&greeter);
+
+    // 处理函数里退订自己：只奖励第一个加入的人
+    let first_id = Rc::new(Cell::new(None));
+    let first_prize = {
+        let first_id = Rc::clone(&first_id);
+        Rc::new(move |event: &UserJoined, bus: &EventBus| {
+            bus.publish(&ChatMessage {
+                from: "bot",
+                text: format!("{} 是第一个，送一枚徽章", event.0),
+            });
+            bus.unsubscribe(first_id.get().unwrap());
+        })
+    };
+    first_id.set(Some(bus.subscribe(&first_prize)));
+
+    bus.publish(&UserJoined("alice"));
+    bus.publish(&UserJoined("bob"));
+    bus.publish(&ChatMessage {
+        from: "alice",
+        text: "大家好".to_string(),
+    });
+    outln!("聊天记录:");
+    for line in log.lines.borrow().iter() {
+        outln!("  {}", line);
+    }
+
+    // 总线只拿 Weak：订阅者 drop 之后自动从订阅表里消失
+    outln!("\nUserJoined 的订阅者: {} 个", bus.subscriber_count::<UserJoined>());
+    drop(greeter);
+    outln!("greeter drop 之后: {} 个", bus.subscriber_count::<UserJoined>());
+    outln!("Rc::strong_count(&log) = {}（总线没有增加强引用）", Rc::strong_count(&log));
+
+    // 对比：一边借用订阅表一边调用处理函数，处理函数里一订阅就 panic
+    use std::panic::{self, AssertUnwindSafe};
+    struct NaiveBus {
+        handlers: RefCell<Vec<fn(&NaiveBus)>>,
+    }
+    let naive = NaiveBus {
+        handlers: RefCell::new(Vec::new()),
+    };
+    naive
+        .handlers
+        .borrow_mut()
+        .push(|bus| bus.handlers.borrow_mut().push(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        for handler in naive.handlers.borrow().iter() {
+            handler(&naive);
+        }
+    }));
+    outln!("\n天真的写法：遍历时处理函数里订阅 → panic 了? {}", result.is_err());
+    outln!("EventBus 先复制一份订阅者快照、松开借用再调用，所以不会撞上");
 
-    TreeNode::add_child(&root, child1);
-    TreeNode::add_child(&root, child2);
+    outln!("\n💡 和 Messenger 相比：发布者不知道有谁在听，订阅者的生死也不归总线管");
+}
+
+/// 实用示例：配额追踪
+pub fn quota_tracker() {
+    outln!("\n=== 22. 实用示例：配额追踪 ===");
+
+    // 用量在阈值附近来回变动
+    let samples = [70, 76, 80, 85, 91, 89, 93, 100, 96, 94, 84, 60];
+    outln!("用量变化: {:?}（上限 100）", samples);
 
-    println!("根节点: {:?}", root.value);
-    println!("子节点数量: {}", root.children.borrow().len());
+    // 默认配置和 LimitTracker 一样是 75% / 90% / 100%，另外带 5% 的回差
+    outln!("\nQuotaTracker 只在级别变化时通知:");
+    let mut tracker = QuotaTracker::new(StdoutMessenger);
+    tracker.add_quota("api", 100).unwrap();
+    for used in samples {
+        tracker.set_used("api", used).unwrap();
+    }
+    // 原来的 LimitTracker 只要在 75% 以上，每次 set_value 都会发消息
+    let old_messages = samples.iter().filter(|&&used| used >= 75).count();
+    outln!("\n同样的用量变化，原来的 LimitTracker 会发 {} 条消息", old_messages);
+    outln!("89% 和 96% 没有触发降级：回差 5%，要低于 85% / 95% 才算回落");
+
+    // 多个配额，各用各的阈值；通知记到 MockMessenger 里
+    let mock = MockMessenger::new();
+    let mut tracker = QuotaTracker::new(&mock);
+    tracker.add_quota("cpu", 8).unwrap();
+    tracker.add_quota("memory", 4096).unwrap();
+    let strict = QuotaConfig::new([("注意", 0.5), ("满了", 1.0)])
+        .and_then(|config| config.with_hysteresis(0.25))
+        .unwrap();
+    tracker.add_quota_with("connections", 4, strict).unwrap();
+
+    tracker.consume("cpu", 7).unwrap();
+    tracker.consume("memory", 1024).unwrap();
+    for _ in 0..4 {
+        tracker.consume("connections", 1).unwrap();
+    }
+    tracker.release("connections", 1).unwrap();
+    tracker.release("cpu", 5).unwrap();
+
+    outln!("\n多个配额的通知:");
+    for message in mock.messages() {
+        outln!("  {}", message);
+    }
+    outln!("当前状态:");
+    for name in tracker.names() {
+        let usage = tracker.usage(name).unwrap();
+        outln!(
+            "  {:<12} {:>4}/{:<4} {}",
+            name,
+            usage.used,
+            usage.limit,
+            usage.level.unwrap_or("正常")
+        );
+    }
+    outln!("\n出错时返回 QuotaError: {}", tracker.consume("disk", 1).unwrap_err());
+
+    // sent() 给出结构化的通知，clear() 清空之前的记录
+    tracker.remove_quota("memory").unwrap();
+    mock.clear();
+    tracker.consume("cpu", 6).unwrap();
+    outln!("移除 memory 之后还在追踪: {:?}", tracker.names().collect::<Vec<_>>());
+    for notification in mock.sent() {
+        outln!("cpu 用满: {:?} → {}", notification.change, notification.level.as_deref().unwrap_or("正常"));
+    }
+
+    // FileMessenger：每条通知追加一行到日志文件
+    let path = std::env::temp_dir().join(format!("quota-{}.log", std::process::id()));
+    let mut tracker = QuotaTracker::new(FileMessenger::open(&path).unwrap());
+    tracker.add_quota("uploads", 100).unwrap();
+    tracker.set_used("uploads", 95).unwrap();
+    // 上限调大，比例跟着变，级别按新的比例重新计算
+    tracker.set_limit("uploads", 200).unwrap();
+    if let Some(error) = tracker.messenger().take_error() {
+        outln!("写日志失败: {}", error);
+    }
+    let log = std::fs::read_to_string(&path).unwrap_or_default();
+    let _ = std::fs::remove_file(&path);
+    outln!("\n日志文件里的内容:");
+    for line in log.lines() {
+        outln!("  {}", line);
+    }
+
+    outln!("\n💡 边沿触发 + 回差：跨过阈值才通知，回落时多让出一段，避免在阈值附近反复横跳");
+    outln!("   Messenger 可以换成 StdoutMessenger、FileMessenger 或测试用的 MockMessenger");
+}
+
+/// 登记本模块的示例
+pub fn register(registry: &mut Registry) {
+    let mut topic = registry.topic("smart_pointer", "智能指针", run_all_smart_pointer_examples);
+
+    topic
+        .lesson("box_basics", "Box<T> 基础", box_basics)
+        .minutes(5)
+        .tags(&["box", "heap"])
+        .requires(&["ownership::basic_ownership"]);
+    topic
+        .lesson("deref_trait", "Deref Trait", deref_trait)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["deref", "trait"])
+        .requires(&["smart_pointer::box_basics", "trait_generic::associated_types"]);
+    topic
+        .lesson("deref_coercion", "Deref 强制转换", deref_coercion)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["deref"])
+        .requires(&["smart_pointer::deref_trait"]);
+    topic
+        .lesson("drop_trait", "Drop Trait", drop_trait)
+        .minutes(5)
+        .tags(&["drop", "raii"])
+        .requires(&["smart_pointer::box_basics"]);
+    topic
+        .lesson("rc_basics", "Rc<T> 引用计数智能指针", rc_basics)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["rc"])
+        .requires(&["smart_pointer::box_basics"]);
+    topic
+        .lesson("refcell_basics", "RefCell<T> 内部可变性", refcell_basics)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["refcell", "interior-mutability"])
+        .requires(&["ownership::borrowing_rules"]);
+    topic
+        .lesson("rc_refcell_combination", "Rc<RefCell<T>> 组合使用", rc_refcell_combination)
+        .level(Difficulty::Intermediate)
+        .minutes(10)
+        .tags(&["rc", "refcell"])
+        .requires(&["smart_pointer::rc_basics", "smart_pointer::refcell_basics"]);
+    topic
+        .lesson("interior_mutability_pattern", "内部可变性模式", interior_mutability_pattern)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["refcell", "mock", "trait"])
+        .requires(&["smart_pointer::refcell_basics", "lifetime::struct_lifetimes"]);
+    topic
+        .lesson("reference_cycles", "引用循环", reference_cycles)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["rc", "refcell", "leak"])
+        .requires(&["smart_pointer::rc_refcell_combination"]);
+    topic
+        .lesson("weak_references", "Weak<T> 弱引用", weak_references)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["weak", "rc"])
+        .requires(&["smart_pointer::reference_cycles"]);
+    topic
+        .lesson("smart_pointer_guide", "智能指针选择指南", smart_pointer_guide)
+        .minutes(3)
+        .tags(&["guide"]);
+    topic
+        .lesson("tree_example", "树形结构", tree_example)
+        .level(Difficulty::Advanced)
+        .minutes(15)
+        .tags(&["weak", "rc", "refcell", "tree", "iterator"])
+        .requires(&["smart_pointer::weak_references"]);
+    topic
+        .lesson("persistent_list", "持久化链表", persistent_list)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["rc", "list", "drop", "iterator"])
+        .requires(&["smart_pointer::rc_basics", "smart_pointer::drop_trait"]);
+    topic
+        .lesson("my_box", "手写 Box：std::alloc", my_box)
+        .level(Difficulty::Advanced)
+        .minutes(15)
+        .tags(&["box", "alloc", "unsafe", "deref", "drop"])
+        .requires(&["smart_pointer::deref_trait", "smart_pointer::drop_trait"]);
+    topic
+        .lesson("my_rc", "手写 Rc、Weak 和 Arc", my_rc)
+        .level(Difficulty::Advanced)
+        .minutes(15)
+        .tags(&["rc", "weak", "arc", "unsafe", "atomic"])
+        .requires(&["smart_pointer::weak_references", "smart_pointer::my_box"]);
+    topic
+        .lesson("arena_tree", "Arena 树：用下标代替 Rc/Weak", arena_tree)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["arena", "tree", "iterator"])
+        .requires(&["smart_pointer::tree_example"]);
+    topic
+        .lesson("arena_graph", "Arena 有向图：BFS、DFS、找环、拓扑排序", arena_graph)
+        .level(Difficulty::Advanced)
+        .minutes(15)
+        .tags(&["arena", "graph", "leak"])
+        .requires(&["smart_pointer::arena_tree", "smart_pointer::reference_cycles"]);
+    topic
+        .lesson("arena_vs_rc", "Arena 和 Rc 的性能对比", arena_vs_rc)
+        .level(Difficulty::Advanced)
+        .minutes(5)
+        .tags(&["arena", "rc", "bench", SLOW, NONDETERMINISTIC])
+        .requires(&["smart_pointer::arena_tree"]);
+    topic
+        .lesson("doubly_linked_list", "双向链表：Rc<RefCell> 版和裸指针版", doubly_linked_list)
+        .level(Difficulty::Advanced)
+        .minutes(15)
+        .tags(&["list", "rc", "weak", "refcell", "unsafe", "iterator"])
+        .requires(&["smart_pointer::weak_references", "smart_pointer::my_box"]);
+    topic
+        .lesson("weak_cache", "Weak 缓存：淘汰之后还能找回来", weak_cache)
+        .level(Difficulty::Intermediate)
+        .minutes(10)
+        .tags(&["weak", "rc", "arc", "cache", "lru"])
+        .requires(&["smart_pointer::weak_references"]);
+    topic
+        .lesson("event_bus", "事件总线：用 Weak 登记的订阅者", event_bus)
+        .level(Difficulty::Intermediate)
+        .minutes(10)
+        .tags(&["weak", "rc", "refcell", "observer", "trait-object"])
+        .requires(&[
+            "smart_pointer::interior_mutability_pattern",
+            "smart_pointer::weak_references",
+        ]);
+    topic
+        .lesson("quota_tracker", "实用示例：配额追踪", quota_tracker)
+        .level(Difficulty::Intermediate)
+        .minutes(10)
+        .tags(&["refcell", "trait", "mock", "testing"])
+        .requires(&["smart_pointer::interior_mutability_pattern"]);
 }
 
 /// 运行所有示例
 pub fn run_all_smart_pointer_examples() {
-    println!("\n");
-    println!("╔════════════════════════════════════════╗");
-    println!("║   Rust 智能指针 - 完整示例            ║");
-    println!("╚════════════════════════════════════════╝");
-
-    box_basics();
-    deref_trait();
-    deref_coercion();
-    drop_trait();
-    rc_basics();
-    refcell_basics();
-    rc_refcell_combination();
-    interior_mutability_pattern();
-    reference_cycles();
-    weak_references();
-    smart_pointer_guide();
-    tree_example();
-
-    println!("\n=== 智能指针示例完成 ===\n");
+    outln!("\n");
+    outln!("╔════════════════════════════════════════╗");
+    outln!("║   Rust 智能指针 - 完整示例            ║");
+    outln!("╚════════════════════════════════════════╝");
+
+    lesson::registry().run_topic("smart_pointer");
+
+    outln!("\n=== 智能指针示例完成 ===\n");
 }
@@ -0,0 +1,431 @@
+// ============================================
+// 用 Mutex + Condvar 实现的同步原语
+// ============================================
+//
+// 每个原语都是同一个套路：
+// 1. 用 `Mutex` 保护一份状态（计数、队列……）
+// 2. 条件不满足时 `condvar.wait(guard)`：原子地释放锁并睡眠，被唤醒时重新拿到锁
+// 3. 改变状态的一方调用 `notify_one` / `notify_all`
+//
+// 关于虚假唤醒（spurious wakeup）：`wait` 可能在没有任何人 notify 的情况下返回，
+// 也可能在 notify 之后、本线程拿回锁之前，条件又被别的线程改掉了。
+// 所以 `wait` 必须放在 `while 条件不满足 { ... }` 循环里，醒来后重新检查；
+// 写成 `if` 的话，醒来就直接往下走，可能在条件不成立时继续执行。
+// `Condvar::wait_while` 就是把这个循环封装好的版本。
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// 计数信号量：最多允许 `permits` 个线程同时进入
+pub struct Semaphore {
+    permits: Mutex<usize>,
+    released: Condvar,
+}
+
+/// 信号量的许可，drop 时自动归还
+pub struct Permit<'a> {
+    semaphore: &'a Semaphore,
+}
+
+impl Semaphore {
+    pub fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            permits: Mutex::new(permits),
+            released: Condvar::new(),
+        }
+    }
+
+    /// 获取一个许可，没有空余时阻塞
+    pub fn acquire(&self) -> Permit<'_> {
+        let mut permits = self.permits.lock().unwrap();
+        // while 而不是 if：醒来时许可可能已经被别的线程抢走了
+        while *permits == 0 {
+            permits = self.released.wait(permits).unwrap();
+        }
+        *permits -= 1;
+        Permit { semaphore: self }
+    }
+
+    /// 有空余许可就拿走，没有就立即返回 `None`
+    pub fn try_acquire(&self) -> Option<Permit<'_>> {
+        let mut permits = self.permits.lock().unwrap();
+        if *permits == 0 {
+            return None;
+        }
+        *permits -= 1;
+        Some(Permit { semaphore: self })
+    }
+
+    /// 当前空余的许可数
+    pub fn available(&self) -> usize {
+        *self.permits.lock().unwrap()
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        *self.semaphore.permits.lock().unwrap() += 1;
+        // 只多出一个许可，唤醒一个等待者就够了
+        self.semaphore.released.notify_one();
+    }
+}
+
+/// 倒计时门闩：计数减到 0 之前，`wait` 的线程都会阻塞；一次性使用
+pub struct CountDownLatch {
+    count: Mutex<usize>,
+    zero: Condvar,
+}
+
+impl CountDownLatch {
+    pub fn new(count: usize) -> CountDownLatch {
+        CountDownLatch {
+            count: Mutex::new(count),
+            zero: Condvar::new(),
+        }
+    }
+
+    /// 计数减一，减到 0 时唤醒所有等待的线程
+    pub fn count_down(&self) {
+        let mut count = self.count.lock().unwrap();
+        if *count > 0 {
+            *count -= 1;
+            if *count == 0 {
+                self.zero.notify_all();
+            }
+        }
+    }
+
+    /// 阻塞直到计数为 0
+    pub fn wait(&self) {
+        let mut count = self.count.lock().unwrap();
+        while *count > 0 {
+            count = self.zero.wait(count).unwrap();
+        }
+    }
+
+    /// 最多等待 `timeout`，返回计数是否已经归零
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut count = self.count.lock().unwrap();
+        while *count > 0 {
+            // 虚假唤醒会让 wait_timeout 提前返回，所以按截止时间算剩余时间
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            count = self.zero.wait_timeout(count, deadline - now).unwrap().0;
+        }
+        true
+    }
+
+    pub fn count(&self) -> usize {
+        *self.count.lock().unwrap()
+    }
+}
+
+struct QueueState<T> {
+    items: VecDeque<T>,
+    closed: bool,
+}
+
+/// 有界阻塞队列：满了 `push` 等待，空了 `pop` 等待
+pub struct BlockingQueue<T> {
+    state: Mutex<QueueState<T>>,
+    capacity: usize,
+    /// 队列从空变为非空（或者关闭）时通知消费者
+    not_empty: Condvar,
+    /// 队列从满变为不满（或者关闭）时通知生产者
+    not_full: Condvar,
+}
+
+impl<T> BlockingQueue<T> {
+    /// # Panics
+    ///
+    /// `capacity` 为 0 时 panic。
+    pub fn new(capacity: usize) -> BlockingQueue<T> {
+        assert!(capacity > 0, "队列容量至少为 1");
+        BlockingQueue {
+            state: Mutex::new(QueueState {
+                items: VecDeque::with_capacity(capacity),
+                closed: false,
+            }),
+            capacity,
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+        }
+    }
+
+    /// 放入一个元素，队列满时阻塞；队列已关闭时把元素原样退回
+    pub fn push(&self, item: T) -> Result<(), T> {
+        let mut state = self.state.lock().unwrap();
+        while state.items.len() == self.capacity && !state.closed {
+            state = self.not_full.wait(state).unwrap();
+        }
+        if state.closed {
+            return Err(item);
+        }
+        state.items.push_back(item);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// 取出一个元素，队列空时阻塞；队列已关闭且取空后返回 `None`
+    pub fn pop(&self) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+        while state.items.is_empty() && !state.closed {
+            state = self.not_empty.wait(state).unwrap();
+        }
+        let item = state.items.pop_front();
+        if item.is_some() {
+            self.not_full.notify_one();
+        }
+        item
+    }
+
+    /// 不阻塞地放入，队列满或已关闭时退回元素
+    pub fn try_push(&self, item: T) -> Result<(), T> {
+        let mut state = self.state.lock().unwrap();
+        if state.closed || state.items.len() == self.capacity {
+            return Err(item);
+        }
+        state.items.push_back(item);
+        self.not_empty.notify_one();
+        Ok(())
+    }
+
+    /// 不阻塞地取出
+    pub fn try_pop(&self) -> Option<T> {
+        let item = self.state.lock().unwrap().items.pop_front();
+        if item.is_some() {
+            self.not_full.notify_one();
+        }
+        item
+    }
+
+    /// 关闭队列：之后的 `push` 都会失败，消费者取完剩余元素后得到 `None`
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        // 所有在等的线程都要醒来看到"已关闭"，所以用 notify_all
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+struct BarrierState {
+    arrived: usize,
+    /// 第几轮：所有线程到齐后加一
+    generation: u64,
+}
+
+/// 可重复使用的屏障，和 `std::sync::Barrier` 行为相同
+pub struct ReusableBarrier {
+    parties: usize,
+    state: Mutex<BarrierState>,
+    all_arrived: Condvar,
+}
+
+impl ReusableBarrier {
+    /// # Panics
+    ///
+    /// `parties` 为 0 时 panic。
+    pub fn new(parties: usize) -> ReusableBarrier {
+        assert!(parties > 0, "屏障至少需要一个参与者");
+        ReusableBarrier {
+            parties,
+            state: Mutex::new(BarrierState {
+                arrived: 0,
+                generation: 0,
+            }),
+            all_arrived: Condvar::new(),
+        }
+    }
+
+    /// 等待所有参与者到达；最后到达的线程返回 `true`（"领头者"）
+    pub fn wait(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let generation = state.generation;
+        state.arrived += 1;
+
+        if state.arrived == self.parties {
+            // 开启下一轮：计数清零，轮次加一
+            state.arrived = 0;
+            state.generation += 1;
+            self.all_arrived.notify_all();
+            return true;
+        }
+
+        // 等的是"轮次变了"，而不是"arrived == parties"：
+        // 被唤醒时下一轮可能已经有线程到达，arrived 又不为 0 了
+        while state.generation == generation {
+            state = self.all_arrived.wait(state).unwrap();
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_semaphore_limits_concurrency() {
+        let semaphore = Semaphore::new(3);
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..16 {
+                scope.spawn(|| {
+                    for _ in 0..20 {
+                        let _permit = semaphore.acquire();
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_running.fetch_max(now, Ordering::SeqCst);
+                        thread::yield_now();
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    }
+                });
+            }
+        });
+
+        assert!(max_running.load(Ordering::SeqCst) <= 3);
+        assert_eq!(semaphore.available(), 3);
+    }
+
+    #[test]
+    fn test_semaphore_try_acquire() {
+        let semaphore = Semaphore::new(1);
+        let permit = semaphore.try_acquire();
+        assert!(permit.is_some());
+        assert!(semaphore.try_acquire().is_none());
+        drop(permit);
+        assert!(semaphore.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_latch_releases_all_waiters() {
+        let latch = CountDownLatch::new(8);
+        let released = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    latch.wait();
+                    released.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+            assert!(!latch.wait_timeout(Duration::from_millis(10)));
+            assert_eq!(released.load(Ordering::SeqCst), 0);
+            for _ in 0..8 {
+                scope.spawn(|| latch.count_down());
+            }
+        });
+
+        assert_eq!(released.load(Ordering::SeqCst), 4);
+        assert_eq!(latch.count(), 0);
+        assert!(latch.wait_timeout(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_blocking_queue_many_producers_and_consumers() {
+        let queue = BlockingQueue::new(4);
+        let consumed = AtomicUsize::new(0);
+        let sum = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            let consumers: Vec<_> = (0..4)
+                .map(|_| {
+                    scope.spawn(|| {
+                        while let Some(item) = queue.pop() {
+                            assert!(queue.len() <= 4);
+                            consumed.fetch_add(1, Ordering::SeqCst);
+                            sum.fetch_add(item, Ordering::SeqCst);
+                        }
+                    })
+                })
+                .collect();
+            let producers: Vec<_> = (0..8)
+                .map(|p| {
+                    let queue = &queue;
+                    scope.spawn(move || {
+                        for i in 0..250 {
+                            queue.push(p * 250 + i).unwrap();
+                        }
+                    })
+                })
+                .collect();
+
+            for producer in producers {
+                producer.join().unwrap();
+            }
+            queue.close();
+            for consumer in consumers {
+                consumer.join().unwrap();
+            }
+        });
+
+        assert_eq!(consumed.load(Ordering::SeqCst), 2000);
+        assert_eq!(sum.load(Ordering::SeqCst), (0..2000).sum());
+    }
+
+    #[test]
+    fn test_blocking_queue_close_and_try_ops() {
+        let queue = BlockingQueue::new(1);
+        assert_eq!(queue.try_push(1), Ok(()));
+        assert_eq!(queue.try_push(2), Err(2));
+        queue.close();
+        assert_eq!(queue.push(3), Err(3));
+        assert_eq!(queue.pop(), Some(1)); // 关闭前放入的还能取出
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.try_pop(), None);
+    }
+
+    #[test]
+    fn test_barrier_is_reusable_and_has_one_leader_per_round() {
+        const THREADS: usize = 8;
+        const ROUNDS: usize = 50;
+        let barrier = ReusableBarrier::new(THREADS);
+        let leaders = AtomicUsize::new(0);
+        // 每一轮都检查：没有线程能在别人到达之前进入下一轮
+        let arrivals: Vec<AtomicUsize> = (0..ROUNDS).map(|_| AtomicUsize::new(0)).collect();
+
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|| {
+                    for arrived in &arrivals {
+                        arrived.fetch_add(1, Ordering::SeqCst);
+                        if barrier.wait() {
+                            leaders.fetch_add(1, Ordering::SeqCst);
+                        }
+                        assert_eq!(arrived.load(Ordering::SeqCst), THREADS);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(leaders.load(Ordering::SeqCst), ROUNDS);
+    }
+
+    #[test]
+    fn test_waiter_wakes_when_state_changes_from_another_thread() {
+        let latch = Arc::new(CountDownLatch::new(1));
+        let other = Arc::clone(&latch);
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            other.count_down();
+        });
+        assert!(latch.wait_timeout(Duration::from_secs(5)));
+        handle.join().unwrap();
+    }
+}
@@ -0,0 +1,295 @@
+// ============================================
+// 线程池 - 可复用的工作线程
+// ============================================
+//
+// 结构和 `thread_pool_concept` 里的草图一样：所有 worker 共享一个
+// `Arc<Mutex<Receiver<Job>>>` 任务队列。在此基础上补齐了草图缺少的部分：
+// - 优雅关闭：`Drop` 时关闭队列，等每个 worker 处理完剩余任务后 join
+// - 有界队列：`ThreadPool::bounded` 的队列满时 `execute` 阻塞（背压），
+//   `try_execute` 立即返回错误
+// - panic 隔离：单个任务 panic 只会被记录下来，worker 继续处理后面的任务
+
+use std::cell::Cell;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+thread_local! {
+    static WORKER_ID: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// 在线程池的任务里调用时，返回执行这个任务的 worker 编号
+pub fn current_worker() -> Option<usize> {
+    WORKER_ID.with(Cell::get)
+}
+
+/// 线程池关闭时的任务统计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoolStats {
+    /// 正常执行完的任务数
+    pub completed: usize,
+    /// 执行时 panic 的任务数
+    pub panicked: usize,
+}
+
+/// `try_execute` 在有界队列已满时返回的错误
+#[derive(Debug, PartialEq)]
+pub struct QueueFull;
+
+impl fmt::Display for QueueFull {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("线程池任务队列已满")
+    }
+}
+
+enum Sender {
+    Unbounded(mpsc::Sender<Job>),
+    Bounded(SyncSender<Job>),
+}
+
+/// worker 之间共享的统计数据
+#[derive(Default)]
+struct Counters {
+    completed: AtomicUsize,
+    panicked: AtomicUsize,
+}
+
+struct Worker {
+    id: usize,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Worker {
+    fn new(id: usize, receiver: Arc<Mutex<Receiver<Job>>>, counters: Arc<Counters>) -> Worker {
+        let thread = thread::Builder::new()
+            .name(format!("worker-{}", id))
+            .spawn(move || {
+                WORKER_ID.with(|worker| worker.set(Some(id)));
+                loop {
+                    // 锁只在取任务时持有，执行任务前就已经释放
+                    let message = receiver.lock().unwrap().recv();
+                    let job = match message {
+                        Ok(job) => job,
+                        Err(_) => break, // 发送端已关闭：线程池正在关闭
+                    };
+                    match panic::catch_unwind(AssertUnwindSafe(job)) {
+                        Ok(()) => counters.completed.fetch_add(1, Ordering::SeqCst),
+                        Err(_) => counters.panicked.fetch_add(1, Ordering::SeqCst),
+                    };
+                }
+            })
+            .expect("创建 worker 线程失败");
+
+        Worker {
+            id,
+            thread: Some(thread),
+        }
+    }
+}
+
+/// 固定数量工作线程的线程池
+pub struct ThreadPool {
+    workers: Vec<Worker>,
+    sender: Option<Sender>,
+    counters: Arc<Counters>,
+}
+
+impl ThreadPool {
+    /// 创建有 `size` 个 worker、任务队列不限长度的线程池
+    ///
+    /// # Panics
+    ///
+    /// `size` 为 0 时 panic。
+    pub fn new(size: usize) -> ThreadPool {
+        let (sender, receiver) = mpsc::channel();
+        ThreadPool::with_sender(size, Sender::Unbounded(sender), receiver)
+    }
+
+    /// 创建任务队列最多容纳 `capacity` 个待执行任务的线程池
+    ///
+    /// `capacity` 为 0 时，提交任务要等到有空闲 worker 接手才返回。
+    ///
+    /// # Panics
+    ///
+    /// `size` 为 0 时 panic。
+    pub fn bounded(size: usize, capacity: usize) -> ThreadPool {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        ThreadPool::with_sender(size, Sender::Bounded(sender), receiver)
+    }
+
+    fn with_sender(size: usize, sender: Sender, receiver: Receiver<Job>) -> ThreadPool {
+        assert!(size > 0, "线程池至少需要一个 worker");
+
+        let receiver = Arc::new(Mutex::new(receiver));
+        let counters = Arc::new(Counters::default());
+        let workers = (0..size)
+            .map(|id| Worker::new(id, Arc::clone(&receiver), Arc::clone(&counters)))
+            .collect();
+
+        ThreadPool {
+            workers,
+            sender: Some(sender),
+            counters,
+        }
+    }
+
+    /// worker 数量
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// 提交一个任务；有界队列已满时阻塞，直到有空位
+    pub fn execute<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let job: Job = Box::new(f);
+        // worker 捕获了任务的 panic 不会退出，接收端在 drop 之前一直存在
+        let sent = match self.sender.as_ref().unwrap() {
+            Sender::Unbounded(sender) => sender.send(job).is_ok(),
+            Sender::Bounded(sender) => sender.send(job).is_ok(),
+        };
+        assert!(sent, "线程池的 worker 已全部退出");
+    }
+
+    /// 提交一个任务；有界队列已满时立即返回 [`QueueFull`]
+    pub fn try_execute<F>(&self, f: F) -> Result<(), QueueFull>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let job: Job = Box::new(f);
+        match self.sender.as_ref().unwrap() {
+            Sender::Unbounded(sender) => {
+                sender.send(job).expect("线程池的 worker 已全部退出");
+                Ok(())
+            }
+            Sender::Bounded(sender) => match sender.try_send(job) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => Err(QueueFull),
+                Err(TrySendError::Disconnected(_)) => panic!("线程池的 worker 已全部退出"),
+            },
+        }
+    }
+
+    /// 关闭线程池：等待队列里的任务全部处理完，返回最终统计
+    pub fn shutdown(mut self) -> PoolStats {
+        self.join_workers();
+        PoolStats {
+            completed: self.completed_count(),
+            panicked: self.panicked_count(),
+        }
+    }
+
+    fn join_workers(&mut self) {
+        // 先关闭发送端：队列里剩下的任务处理完后，worker 的 recv 会返回 Err
+        drop(self.sender.take());
+
+        for worker in &mut self.workers {
+            if let Some(thread) = worker.thread.take() {
+                thread
+                    .join()
+                    .unwrap_or_else(|_| panic!("worker {} 异常退出", worker.id));
+            }
+        }
+    }
+
+    /// 已经正常执行完的任务数
+    pub fn completed_count(&self) -> usize {
+        self.counters.completed.load(Ordering::SeqCst)
+    }
+
+    /// 执行时 panic 的任务数
+    pub fn panicked_count(&self) -> usize {
+        self.counters.panicked.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        self.join_workers();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Barrier;
+    use std::time::Duration;
+
+    #[test]
+    fn test_drop_waits_for_all_jobs() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        {
+            let pool = ThreadPool::new(4);
+            for _ in 0..100 {
+                let counter = Arc::clone(&counter);
+                pool.execute(move || {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 100);
+    }
+
+    #[test]
+    fn test_jobs_run_on_worker_threads() {
+        let pool = ThreadPool::new(2);
+        let (tx, rx) = mpsc::channel();
+        for _ in 0..10 {
+            let tx = tx.clone();
+            pool.execute(move || tx.send(current_worker()).unwrap());
+        }
+        drop(tx);
+        assert!(rx.iter().all(|id| matches!(id, Some(0) | Some(1))));
+        assert_eq!(current_worker(), None);
+    }
+
+    #[test]
+    fn test_panicking_job_does_not_kill_worker() {
+        let pool = ThreadPool::new(1);
+        let (tx, rx) = mpsc::channel();
+        pool.execute(|| panic!("任务失败"));
+        pool.execute(move || tx.send("still alive").unwrap());
+
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)), Ok("still alive"));
+        assert_eq!(
+            pool.shutdown(),
+            PoolStats {
+                completed: 1,
+                panicked: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_bounded_queue_applies_backpressure() {
+        let pool = ThreadPool::bounded(1, 1);
+        let started = Arc::new(Barrier::new(2));
+        let release = Arc::new(Barrier::new(2));
+
+        // 第一个任务占住唯一的 worker，直到测试放行
+        let (s, r) = (Arc::clone(&started), Arc::clone(&release));
+        pool.execute(move || {
+            s.wait();
+            r.wait();
+        });
+        started.wait();
+
+        assert_eq!(pool.try_execute(|| {}), Ok(())); // 占满队列
+        assert_eq!(pool.try_execute(|| {}), Err(QueueFull));
+
+        release.wait();
+        drop(pool);
+    }
+
+    #[test]
+    #[should_panic(expected = "至少需要一个 worker")]
+    fn test_zero_workers_panics() {
+        ThreadPool::new(0);
+    }
+}
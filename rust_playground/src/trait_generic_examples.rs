@@ -4,9 +4,11 @@
 
 use std::fmt::{Debug, Display};
 
+use crate::lesson::{self, Difficulty, Registry};
+
 /// Trait 基础
 pub fn trait_basics() {
-    println!("\n=== 1. Trait 基础 ===");
+    outln!("\n=== 1. Trait 基础 ===");
 
     // 定义 trait
     trait Summary {
@@ -47,7 +49,7 @@ pub fn trait_basics() {
         retweet: false,
     };
 
-    println!("1 new tweet: {}", tweet.summarize());
+    outln!("1 new tweet: {}", tweet.summarize());
 
     let article = NewsArticle {
         headline: String::from("Penguins win the Stanley Cup Championship!"),
@@ -56,12 +58,12 @@ pub fn trait_basics() {
         content: String::from("The Pittsburgh Penguins once again are the best hockey team in the NHL."),
     };
 
-    println!("New article available! {}", article.summarize());
+    outln!("New article available! {}", article.summarize());
 }
 
 /// Trait 默认实现
 pub fn trait_default_implementation() {
-    println!("\n=== 2. Trait 默认实现 ===");
+    outln!("\n=== 2. Trait 默认实现 ===");
 
     trait Summary {
         fn summarize_author(&self) -> String;
@@ -89,12 +91,12 @@ pub fn trait_default_implementation() {
         content: String::from("of course, as you probably already know, people"),
     };
 
-    println!("1 new tweet: {}", tweet.summarize());
+    outln!("1 new tweet: {}", tweet.summarize());
 }
 
 /// Trait 作为参数
 pub fn trait_as_parameter() {
-    println!("\n=== 3. Trait 作为参数 ===");
+    outln!("\n=== 3. Trait 作为参数 ===");
 
     trait Summary {
         fn summarize(&self) -> String;
@@ -112,22 +114,22 @@ pub fn trait_as_parameter() {
 
     // 方式1: impl Trait 语法
     fn notify1(item: &impl Summary) {
-        println!("Breaking news! {}", item.summarize());
+        outln!("Breaking news! {}", item.summarize());
     }
 
     // 方式2: Trait bound 语法（更灵活）
     fn notify2<T: Summary>(item: &T) {
-        println!("Breaking news! {}", item.summarize());
+        outln!("Breaking news! {}", item.summarize());
     }
 
     // 多个参数
     fn notify3(item1: &impl Summary, item2: &impl Summary) {
-        println!("{} and {}", item1.summarize(), item2.summarize());
+        outln!("{} and {}", item1.summarize(), item2.summarize());
     }
 
     // 强制两个参数是同一类型
     fn notify4<T: Summary>(item1: &T, item2: &T) {
-        println!("{} and {}", item1.summarize(), item2.summarize());
+        outln!("{} and {}", item1.summarize(), item2.summarize());
     }
 
     let article = Article {
@@ -140,16 +142,16 @@ pub fn trait_as_parameter() {
 
 /// 多个 Trait Bound
 pub fn multiple_trait_bounds() {
-    println!("\n=== 4. 多个 Trait Bound ===");
+    outln!("\n=== 4. 多个 Trait Bound ===");
 
     // 使用 + 语法
     fn notify1(item: &(impl Summary + Display)) {
-        println!("{}", item);
+        outln!("{}", item);
     }
 
     // 泛型版本
     fn notify2<T: Summary + Display>(item: &T) {
-        println!("{}", item);
+        outln!("{}", item);
     }
 
     // where 子句让代码更清晰
@@ -158,8 +160,8 @@ pub fn multiple_trait_bounds() {
         T: Display + Clone,
         U: Clone + Debug,
     {
-        println!("t: {}", t);
-        println!("u: {:?}", u);
+        outln!("t: {}", t);
+        outln!("u: {:?}", u);
         0
     }
 
@@ -174,7 +176,7 @@ pub fn multiple_trait_bounds() {
 
 /// 返回实现了 Trait 的类型
 pub fn return_trait() {
-    println!("\n=== 5. 返回实现了 Trait 的类型 ===");
+    outln!("\n=== 5. 返回实现了 Trait 的类型 ===");
 
     trait Summary {
         fn summarize(&self) -> String;
@@ -198,7 +200,7 @@ pub fn return_trait() {
     }
 
     let tweet = returns_summarizable();
-    println!("Summary: {}", tweet.summarize());
+    outln!("Summary: {}", tweet.summarize());
 
     // 注意：不能根据条件返回不同类型
     // fn returns_summarizable_conditional(switch: bool) -> impl Summary {
@@ -212,7 +214,7 @@ pub fn return_trait() {
 
 /// 使用 Trait Bound 有条件地实现方法
 pub fn conditional_trait_implementation() {
-    println!("\n=== 6. 条件实现 ===");
+    outln!("\n=== 6. 条件实现 ===");
 
     struct Pair<T> {
         x: T,
@@ -229,9 +231,9 @@ pub fn conditional_trait_implementation() {
     impl<T: Display + PartialOrd> Pair<T> {
         fn cmp_display(&self) {
             if self.x >= self.y {
-                println!("最大的是 x = {}", self.x);
+                outln!("最大的是 x = {}", self.x);
             } else {
-                println!("最大的是 y = {}", self.y);
+                outln!("最大的是 y = {}", self.y);
             }
         }
     }
@@ -242,7 +244,7 @@ pub fn conditional_trait_implementation() {
 
 /// 泛型基础
 pub fn generic_basics() {
-    println!("\n=== 7. 泛型基础 ===");
+    outln!("\n=== 7. 泛型基础 ===");
 
     // 泛型函数
     fn largest<T: PartialOrd>(list: &[T]) -> &T {
@@ -257,16 +259,16 @@ pub fn generic_basics() {
 
     let number_list = vec![34, 50, 25, 100, 65];
     let result = largest(&number_list);
-    println!("最大的数字是 {}", result);
+    outln!("最大的数字是 {}", result);
 
     let char_list = vec!['y', 'm', 'a', 'q'];
     let result = largest(&char_list);
-    println!("最大的字符是 {}", result);
+    outln!("最大的字符是 {}", result);
 }
 
 /// 泛型结构体
 pub fn generic_structs() {
-    println!("\n=== 8. 泛型结构体 ===");
+    outln!("\n=== 8. 泛型结构体 ===");
 
     // 单个泛型参数
     #[derive(Debug)]
@@ -278,8 +280,8 @@ pub fn generic_structs() {
     let integer = Point { x: 5, y: 10 };
     let float = Point { x: 1.0, y: 4.0 };
 
-    println!("integer point: {:?}", integer);
-    println!("float point: {:?}", float);
+    outln!("integer point: {:?}", integer);
+    outln!("float point: {:?}", float);
 
     // 多个泛型参数
     #[derive(Debug)]
@@ -292,12 +294,12 @@ pub fn generic_structs() {
     let both_float = Point2 { x: 1.0, y: 4.0 };
     let integer_and_float = Point2 { x: 5, y: 4.0 };
 
-    println!("mixed point: {:?}", integer_and_float);
+    outln!("mixed point: {:?}", integer_and_float);
 }
 
 /// 泛型枚举
 pub fn generic_enums() {
-    println!("\n=== 9. 泛型枚举 ===");
+    outln!("\n=== 9. 泛型枚举 ===");
 
     // Option 的定义
     // enum Option<T> {
@@ -309,9 +311,9 @@ pub fn generic_enums() {
     let some_string = Some("a string");
     let absent_number: Option<i32> = None;
 
-    println!("some_number: {:?}", some_number);
-    println!("some_string: {:?}", some_string);
-    println!("absent_number: {:?}", absent_number);
+    outln!("some_number: {:?}", some_number);
+    outln!("some_string: {:?}", some_string);
+    outln!("absent_number: {:?}", absent_number);
 
     // Result 的定义
     // enum Result<T, E> {
@@ -322,13 +324,13 @@ pub fn generic_enums() {
     let success: Result<i32, String> = Ok(10);
     let failure: Result<i32, String> = Err(String::from("error"));
 
-    println!("success: {:?}", success);
-    println!("failure: {:?}", failure);
+    outln!("success: {:?}", success);
+    outln!("failure: {:?}", failure);
 }
 
 /// 泛型方法
 pub fn generic_methods() {
-    println!("\n=== 10. 泛型方法 ===");
+    outln!("\n=== 10. 泛型方法 ===");
 
     struct Point<T> {
         x: T,
@@ -350,15 +352,15 @@ pub fn generic_methods() {
     }
 
     let p = Point { x: 5, y: 10 };
-    println!("p.x = {}", p.x());
+    outln!("p.x = {}", p.x());
 
     let p = Point { x: 3.0, y: 4.0 };
-    println!("distance: {}", p.distance_from_origin());
+    outln!("distance: {}", p.distance_from_origin());
 }
 
 /// 关联类型
 pub fn associated_types() {
-    println!("\n=== 11. 关联类型 ===");
+    outln!("\n=== 11. 关联类型 ===");
 
     trait Iterator {
         type Item; // 关联类型
@@ -390,13 +392,13 @@ pub fn associated_types() {
     }
 
     let mut counter = Counter::new();
-    println!("counter.next(): {:?}", counter.next());
-    println!("counter.next(): {:?}", counter.next());
+    outln!("counter.next(): {:?}", counter.next());
+    outln!("counter.next(): {:?}", counter.next());
 }
 
 /// 运算符重载
 pub fn operator_overloading() {
-    println!("\n=== 12. 运算符重载 ===");
+    outln!("\n=== 12. 运算符重载 ===");
 
     use std::ops::Add;
 
@@ -421,12 +423,12 @@ pub fn operator_overloading() {
     let p2 = Point { x: 2, y: 3 };
     let p3 = p1 + p2;
 
-    println!("{:?} + {:?} = {:?}", p1, p2, p3);
+    outln!("{:?} + {:?} = {:?}", p1, p2, p3);
 }
 
 /// 完全限定语法
 pub fn fully_qualified_syntax() {
-    println!("\n=== 13. 完全限定语法 ===");
+    outln!("\n=== 13. 完全限定语法 ===");
 
     trait Pilot {
         fn fly(&self);
@@ -440,19 +442,19 @@ pub fn fully_qualified_syntax() {
 
     impl Pilot for Human {
         fn fly(&self) {
-            println!("This is your captain speaking.");
+            outln!("This is your captain speaking.");
         }
     }
 
     impl Wizard for Human {
         fn fly(&self) {
-            println!("Up!");
+            outln!("Up!");
         }
     }
 
     impl Human {
         fn fly(&self) {
-            println!("*waving arms furiously*");
+            outln!("*waving arms furiously*");
         }
     }
 
@@ -464,7 +466,7 @@ pub fn fully_qualified_syntax() {
 
 /// Supertrait（父 Trait）
 pub fn supertraits() {
-    println!("\n=== 14. Supertrait ===");
+    outln!("\n=== 14. Supertrait ===");
 
     use std::fmt;
 
@@ -473,11 +475,11 @@ pub fn supertraits() {
         fn outline_print(&self) {
             let output = self.to_string();
             let len = output.len();
-            println!("{}", "*".repeat(len + 4));
-            println!("*{}*", " ".repeat(len + 2));
-            println!("* {} *", output);
-            println!("*{}*", " ".repeat(len + 2));
-            println!("{}", "*".repeat(len + 4));
+            outln!("{}", "*".repeat(len + 4));
+            outln!("*{}*", " ".repeat(len + 2));
+            outln!("* {} *", output);
+            outln!("*{}*", " ".repeat(len + 2));
+            outln!("{}", "*".repeat(len + 4));
         }
     }
 
@@ -500,7 +502,7 @@ pub fn supertraits() {
 
 /// Newtype 模式
 pub fn newtype_pattern() {
-    println!("\n=== 15. Newtype 模式 ===");
+    outln!("\n=== 15. Newtype 模式 ===");
 
     use std::fmt;
 
@@ -514,31 +516,106 @@ pub fn newtype_pattern() {
     }
 
     let w = Wrapper(vec![String::from("hello"), String::from("world")]);
-    println!("w = {}", w);
+    outln!("w = {}", w);
+}
+
+/// 登记本模块的示例
+pub fn register(registry: &mut Registry) {
+    let mut topic = registry.topic("trait_generic", "Trait 和泛型", run_all_trait_generic_examples);
+
+    topic
+        .lesson("trait_basics", "Trait 基础", trait_basics)
+        .minutes(5)
+        .tags(&["trait"]);
+    topic
+        .lesson("trait_default_implementation", "Trait 默认实现", trait_default_implementation)
+        .minutes(5)
+        .tags(&["trait"])
+        .requires(&["trait_generic::trait_basics"]);
+    topic
+        .lesson("trait_as_parameter", "Trait 作为参数", trait_as_parameter)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["trait", "generics"])
+        .requires(&["trait_generic::trait_basics"]);
+    topic
+        .lesson("multiple_trait_bounds", "多个 Trait Bound", multiple_trait_bounds)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["trait", "generics", "where"])
+        .requires(&["trait_generic::trait_as_parameter"]);
+    topic
+        .lesson("return_trait", "返回实现了 Trait 的类型", return_trait)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["trait", "impl-trait"])
+        .requires(&["trait_generic::trait_as_parameter"]);
+    topic
+        .lesson("conditional_trait_implementation", "条件实现", conditional_trait_implementation)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["trait", "generics"])
+        .requires(&["trait_generic::generic_methods", "trait_generic::multiple_trait_bounds"]);
+    topic
+        .lesson("generic_basics", "泛型基础", generic_basics)
+        .minutes(5)
+        .tags(&["generics"]);
+    topic
+        .lesson("generic_structs", "泛型结构体", generic_structs)
+        .minutes(5)
+        .tags(&["generics", "struct"])
+        .requires(&["trait_generic::generic_basics"]);
+    topic
+        .lesson("generic_enums", "泛型枚举", generic_enums)
+        .minutes(5)
+        .tags(&["generics", "enum"])
+        .requires(&["trait_generic::generic_basics"]);
+    topic
+        .lesson("generic_methods", "泛型方法", generic_methods)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["generics", "impl"])
+        .requires(&["trait_generic::generic_structs"]);
+    topic
+        .lesson("associated_types", "关联类型", associated_types)
+        .level(Difficulty::Advanced)
+        .minutes(10)
+        .tags(&["trait", "associated-type"])
+        .requires(&["trait_generic::trait_basics", "trait_generic::generic_basics"]);
+    topic
+        .lesson("operator_overloading", "运算符重载", operator_overloading)
+        .level(Difficulty::Intermediate)
+        .minutes(8)
+        .tags(&["trait", "ops"])
+        .requires(&["trait_generic::associated_types"]);
+    topic
+        .lesson("fully_qualified_syntax", "完全限定语法", fully_qualified_syntax)
+        .level(Difficulty::Advanced)
+        .minutes(8)
+        .tags(&["trait"])
+        .requires(&["trait_generic::trait_basics"]);
+    topic
+        .lesson("supertraits", "Supertrait", supertraits)
+        .level(Difficulty::Advanced)
+        .minutes(8)
+        .tags(&["trait"])
+        .requires(&["trait_generic::trait_default_implementation"]);
+    topic
+        .lesson("newtype_pattern", "Newtype 模式", newtype_pattern)
+        .level(Difficulty::Intermediate)
+        .minutes(5)
+        .tags(&["trait", "pattern"])
+        .requires(&["trait_generic::trait_basics"]);
 }
 
 /// 运行所有示例
 pub fn run_all_trait_generic_examples() {
-    println!("\n");
-    println!("╔════════════════════════════════════════╗");
-    println!("║   Rust Trait 和泛型 - 完整示例        ║");
-    println!("╚════════════════════════════════════════╝");
-
-    trait_basics();
-    trait_default_implementation();
-    trait_as_parameter();
-    multiple_trait_bounds();
-    return_trait();
-    conditional_trait_implementation();
-    generic_basics();
-    generic_structs();
-    generic_enums();
-    generic_methods();
-    associated_types();
-    operator_overloading();
-    fully_qualified_syntax();
-    supertraits();
-    newtype_pattern();
-
-    println!("\n=== Trait 和泛型示例完成 ===\n");
+    outln!("\n");
+    outln!("╔════════════════════════════════════════╗");
+    outln!("║   Rust Trait 和泛型 - 完整示例        ║");
+    outln!("╚════════════════════════════════════════╝");
+
+    lesson::registry().run_topic("trait_generic");
+
+    outln!("\n=== Trait 和泛型示例完成 ===\n");
 }
@@ -0,0 +1,378 @@
+// ============================================
+// Rc/Weak 树 - 父节点用 Weak，子节点用 Rc
+// ============================================
+//
+// 所有权只从上往下走：父节点用 `Rc` 拥有子节点，子节点用 `Weak` 指回父节点。
+// 这样不会形成引用循环，根节点 drop 后整棵树都会被释放；
+// 把一棵子树摘下来（`detach`）后，只要外面没人再持有它，它也会被释放。
+//
+// 遍历器每走一步都把当前节点的子节点列表克隆一份（只是克隆 `Rc`），
+// 所以遍历过程中不会一直借着 `RefCell`，边遍历边修改树也不会 panic。
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::my_rc::rc::{Rc, Weak};
+
+/// 树节点：总是放在 `Rc` 里使用
+#[derive(Debug)]
+pub struct TreeNode<T> {
+    pub value: T,
+    parent: RefCell<Weak<TreeNode<T>>>,
+    children: RefCell<Vec<Rc<TreeNode<T>>>>,
+}
+
+impl<T> TreeNode<T> {
+    pub fn new(value: T) -> Rc<TreeNode<T>> {
+        Rc::new(TreeNode {
+            value,
+            parent: RefCell::new(Weak::new()),
+            children: RefCell::new(vec![]),
+        })
+    }
+
+    /// 把 `child` 挂到 `parent` 下面（放在最后）
+    ///
+    /// `child` 如果已经有父节点，会先从原来的父节点上摘下来，也就是"换父节点"。
+    ///
+    /// # Panics
+    ///
+    /// `child` 是 `parent` 自己或者它的祖先时 panic：那样会形成引用循环。
+    pub fn add_child(parent: &Rc<TreeNode<T>>, child: Rc<TreeNode<T>>) {
+        assert!(
+            !TreeNode::is_ancestor_of(&child, parent),
+            "不能把节点挂到它自己的子树下面"
+        );
+        TreeNode::detach(&child);
+        *child.parent.borrow_mut() = Rc::downgrade(parent);
+        parent.children.borrow_mut().push(child);
+    }
+
+    /// 从父节点上摘下来，成为一棵独立的树；原来没有父节点时返回 `false`
+    pub fn detach(node: &Rc<TreeNode<T>>) -> bool {
+        let parent = node.parent.replace(Weak::new()).upgrade();
+        match parent {
+            Some(parent) => {
+                parent
+                    .children
+                    .borrow_mut()
+                    .retain(|child| !Rc::ptr_eq(child, node));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 按位置摘下一个子节点
+    pub fn remove_child(parent: &Rc<TreeNode<T>>, index: usize) -> Option<Rc<TreeNode<T>>> {
+        let mut children = parent.children.borrow_mut();
+        if index >= children.len() {
+            return None;
+        }
+        let child = children.remove(index);
+        *child.parent.borrow_mut() = Weak::new();
+        Some(child)
+    }
+
+    /// 父节点；根节点（或者父节点已经被释放）返回 `None`
+    pub fn parent(&self) -> Option<Rc<TreeNode<T>>> {
+        self.parent.borrow().upgrade()
+    }
+
+    /// 子节点列表的一份拷贝
+    pub fn children(&self) -> Vec<Rc<TreeNode<T>>> {
+        self.children.borrow().clone()
+    }
+
+    pub fn child_count(&self) -> usize {
+        self.children.borrow().len()
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.parent().is_none()
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        self.children.borrow().is_empty()
+    }
+
+    /// 到根节点的边数，根节点是 0
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut current = self.parent();
+        while let Some(node) = current {
+            depth += 1;
+            current = node.parent();
+        }
+        depth
+    }
+
+    /// 子树的高度：叶子是 0
+    pub fn height(&self) -> usize {
+        self.children
+            .borrow()
+            .iter()
+            .map(|child| child.height() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 从 `node` 一路往上到根节点，第一个元素是 `node` 自己
+    pub fn path_to_root(node: &Rc<TreeNode<T>>) -> Vec<Rc<TreeNode<T>>> {
+        let mut path = vec![Rc::clone(node)];
+        while let Some(parent) = path.last().unwrap().parent() {
+            path.push(parent);
+        }
+        path
+    }
+
+    /// 所在树的根节点
+    pub fn root(node: &Rc<TreeNode<T>>) -> Rc<TreeNode<T>> {
+        TreeNode::path_to_root(node).pop().unwrap()
+    }
+
+    /// `ancestor` 是不是 `node` 自己或者它的祖先
+    pub fn is_ancestor_of(ancestor: &Rc<TreeNode<T>>, node: &Rc<TreeNode<T>>) -> bool {
+        TreeNode::path_to_root(node)
+            .iter()
+            .any(|n| Rc::ptr_eq(n, ancestor))
+    }
+
+    /// 子树里的节点总数（包括自己）
+    pub fn size(node: &Rc<TreeNode<T>>) -> usize {
+        TreeNode::pre_order(node).count()
+    }
+
+    /// 先序遍历：先自己，再从左到右访问子树
+    pub fn pre_order(node: &Rc<TreeNode<T>>) -> PreOrder<T> {
+        PreOrder {
+            stack: vec![Rc::clone(node)],
+        }
+    }
+
+    /// 后序遍历：先从左到右访问子树，最后才是自己
+    pub fn post_order(node: &Rc<TreeNode<T>>) -> PostOrder<T> {
+        PostOrder {
+            stack: vec![(Rc::clone(node), false)],
+        }
+    }
+
+    /// 层序遍历：按深度一层一层地访问
+    pub fn level_order(node: &Rc<TreeNode<T>>) -> LevelOrder<T> {
+        LevelOrder {
+            queue: VecDeque::from([Rc::clone(node)]),
+        }
+    }
+}
+
+/// 先序遍历器，见 [`TreeNode::pre_order`]
+pub struct PreOrder<T> {
+    stack: Vec<Rc<TreeNode<T>>>,
+}
+
+impl<T> Iterator for PreOrder<T> {
+    type Item = Rc<TreeNode<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // 倒着压栈，弹出时才是从左到右
+        self.stack.extend(node.children().into_iter().rev());
+        Some(node)
+    }
+}
+
+/// 后序遍历器，见 [`TreeNode::post_order`]
+pub struct PostOrder<T> {
+    /// 第二个字段表示子节点是否已经压过栈
+    stack: Vec<(Rc<TreeNode<T>>, bool)>,
+}
+
+impl<T> Iterator for PostOrder<T> {
+    type Item = Rc<TreeNode<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(node);
+            }
+            let children = node.children();
+            self.stack.push((node, true));
+            self.stack
+                .extend(children.into_iter().rev().map(|child| (child, false)));
+        }
+    }
+}
+
+/// 层序遍历器，见 [`TreeNode::level_order`]
+pub struct LevelOrder<T> {
+    queue: VecDeque<Rc<TreeNode<T>>>,
+}
+
+impl<T> Iterator for LevelOrder<T> {
+    type Item = Rc<TreeNode<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.children());
+        Some(node)
+    }
+}
+
+/// 以当前节点为根，画出整棵子树：
+///
+/// ```text
+/// 1
+/// ├── 2
+/// │   └── 4
+/// └── 3
+/// ```
+impl<T: fmt::Display> fmt::Display for TreeNode<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.value)?;
+        write_children(self, "", f)
+    }
+}
+
+fn write_children<T: fmt::Display>(
+    node: &TreeNode<T>,
+    prefix: &str,
+    f: &mut fmt::Formatter,
+) -> fmt::Result {
+    let children = node.children.borrow();
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        writeln!(f, "{}{}{}", prefix, branch, child.value)?;
+        write_children(child, &format!("{}{}", prefix, indent), f)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// ```text
+    /// 1
+    /// ├── 2
+    /// │   ├── 4
+    /// │   └── 5
+    /// └── 3
+    ///     └── 6
+    /// ```
+    fn sample() -> (Rc<TreeNode<i32>>, Vec<Rc<TreeNode<i32>>>) {
+        let nodes: Vec<_> = (1..=6).map(TreeNode::new).collect();
+        TreeNode::add_child(&nodes[0], Rc::clone(&nodes[1]));
+        TreeNode::add_child(&nodes[0], Rc::clone(&nodes[2]));
+        TreeNode::add_child(&nodes[1], Rc::clone(&nodes[3]));
+        TreeNode::add_child(&nodes[1], Rc::clone(&nodes[4]));
+        TreeNode::add_child(&nodes[2], Rc::clone(&nodes[5]));
+        (Rc::clone(&nodes[0]), nodes)
+    }
+
+    fn values(nodes: impl IntoIterator<Item = Rc<TreeNode<i32>>>) -> Vec<i32> {
+        nodes.into_iter().map(|node| node.value).collect()
+    }
+
+    #[test]
+    fn test_traversal_orders() {
+        let (root, _nodes) = sample();
+        assert_eq!(values(TreeNode::pre_order(&root)), [1, 2, 4, 5, 3, 6]);
+        assert_eq!(values(TreeNode::post_order(&root)), [4, 5, 2, 6, 3, 1]);
+        assert_eq!(values(TreeNode::level_order(&root)), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(TreeNode::size(&root), 6);
+    }
+
+    #[test]
+    fn test_depth_height_and_path_to_root() {
+        let (root, nodes) = sample();
+        assert_eq!(root.depth(), 0);
+        assert_eq!(nodes[4].depth(), 2);
+        assert_eq!(root.height(), 2);
+        assert_eq!(nodes[2].height(), 1);
+        assert_eq!(values(TreeNode::path_to_root(&nodes[5])), [6, 3, 1]);
+        assert!(Rc::ptr_eq(&TreeNode::root(&nodes[3]), &root));
+        assert!(TreeNode::is_ancestor_of(&nodes[1], &nodes[4]));
+        assert!(!TreeNode::is_ancestor_of(&nodes[2], &nodes[4]));
+    }
+
+    #[test]
+    fn test_reparent_moves_whole_subtree() {
+        let (root, nodes) = sample();
+        TreeNode::add_child(&nodes[5], Rc::clone(&nodes[1]));
+
+        assert_eq!(root.child_count(), 1);
+        assert!(Rc::ptr_eq(&nodes[1].parent().unwrap(), &nodes[5]));
+        assert_eq!(nodes[4].depth(), 4);
+        assert_eq!(values(TreeNode::pre_order(&root)), [1, 3, 6, 2, 4, 5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "不能把节点挂到它自己的子树下面")]
+    fn test_adding_ancestor_as_child_panics() {
+        let (root, nodes) = sample();
+        TreeNode::add_child(&nodes[3], root);
+    }
+
+    #[test]
+    fn test_pretty_print() {
+        let (root, _nodes) = sample();
+        let expected = "\
+1
+├── 2
+│   ├── 4
+│   └── 5
+└── 3
+    └── 6
+";
+        assert_eq!(root.to_string(), expected);
+    }
+
+    #[test]
+    fn test_detached_subtree_is_freed() {
+        let (root, nodes) = sample();
+        let branch = Rc::downgrade(&nodes[1]);
+        let leaf = Rc::downgrade(&nodes[3]);
+        drop(nodes);
+
+        // 父节点的 Rc 是唯一的强引用；子节点指回来的 Weak 不算
+        assert_eq!(branch.strong_count(), 1);
+        assert_eq!(branch.weak_count(), 3); // 两个子节点的 parent + 我们手里这个
+
+        let subtree = TreeNode::remove_child(&root, 0).unwrap();
+        assert!(subtree.is_root());
+        assert_eq!(root.child_count(), 1);
+        assert_eq!(branch.strong_count(), 1);
+        drop(subtree);
+
+        assert_eq!(branch.strong_count(), 0);
+        assert_eq!(leaf.strong_count(), 0);
+        assert!(branch.upgrade().is_none());
+        assert_eq!(TreeNode::size(&root), 3);
+    }
+
+    #[test]
+    fn test_dropping_root_frees_every_node() {
+        let (root, nodes) = sample();
+        let weaks: Vec<_> = nodes.iter().map(Rc::downgrade).collect();
+        drop(nodes);
+
+        assert!(TreeNode::detach(&weaks[2].upgrade().unwrap()));
+        assert!(!TreeNode::detach(&root));
+        // 摘下来的子树没有人持有，立刻就被释放了
+        assert_eq!(weaks[2].strong_count(), 0);
+        assert_eq!(weaks[5].strong_count(), 0);
+
+        drop(root);
+        for weak in &weaks {
+            assert_eq!(weak.strong_count(), 0);
+            assert_eq!(weak.weak_count(), 0);
+        }
+    }
+}
@@ -3,6 +3,8 @@
 // 从 example 模块导入函数（演示循环导入的替代方案）
 // 注意：Rust 不允许真正的循环依赖，但我们可以通过公共接口来实现协作
 
+use crate::lesson::Registry;
+
 /// 加法函数
 pub fn add(a: i32, b: i32) -> i32 {
     a + b
@@ -105,33 +107,42 @@ pub mod math_utils {
 
 /// 演示如何使用这些工具函数
 pub fn demo_utils() {
-    println!("\n=== Utils 模块演示 ===");
+    outln!("\n=== Utils 模块演示 ===");
 
-    println!("10 + 5 = {}", add(10, 5));
-    println!("10 * 5 = {}", multiply(10, 5));
-    println!("10 - 5 = {}", subtract(10, 5));
+    outln!("10 + 5 = {}", add(10, 5));
+    outln!("10 * 5 = {}", multiply(10, 5));
+    outln!("10 - 5 = {}", subtract(10, 5));
 
     match divide(10, 5) {
-        Some(result) => println!("10 / 5 = {}", result),
-        None => println!("除数不能为零"),
+        Some(result) => outln!("10 / 5 = {}", result),
+        None => outln!("除数不能为零"),
     }
 
-    println!("{}", greet("学习者"));
-    println!("5! = {}", factorial(5));
-    println!("17 是质数吗? {}", is_prime(17));
+    outln!("{}", greet("学习者"));
+    outln!("5! = {}", factorial(5));
+    outln!("17 是质数吗? {}", is_prime(17));
 
     // 字符串工具
     let text = "Hello Rust";
-    println!("反转 '{}': {}", text, string_utils::reverse(text));
-    println!("大写: {}", string_utils::to_uppercase(text));
-    println!("'{}' 有 {} 个单词", text, string_utils::word_count(text));
+    outln!("反转 '{}': {}", text, string_utils::reverse(text));
+    outln!("大写: {}", string_utils::to_uppercase(text));
+    outln!("'{}' 有 {} 个单词", text, string_utils::word_count(text));
 
     // 数学工具
     let numbers = vec![1, 5, 3, 9, 2, 7];
-    println!("数组 {:?}", numbers);
-    println!("平均值: {}", math_utils::average(&numbers));
-    println!("最大值: {:?}", math_utils::max(&numbers));
-    println!("最小值: {:?}", math_utils::min(&numbers));
+    outln!("数组 {:?}", numbers);
+    outln!("平均值: {}", math_utils::average(&numbers));
+    outln!("最大值: {:?}", math_utils::max(&numbers));
+    outln!("最小值: {:?}", math_utils::min(&numbers));
+}
+
+/// 登记本模块的示例
+pub fn register(registry: &mut Registry) {
+    registry
+        .topic("utils", "模块导入与工具函数", demo_utils)
+        .lesson("demo_utils", "Utils 模块演示", demo_utils)
+        .minutes(3)
+        .tags(&["modules"]);
 }
 
 #[cfg(test)]
@@ -0,0 +1,706 @@
+// ============================================
+// Weak 缓存 - 淘汰之后还能找回来的 LRU / TTL 缓存
+// ============================================
+//
+// 缓存把值放在 `Rc<V>` 里交给调用方。普通的 LRU 缓存淘汰一个条目时直接丢掉它，
+// 但调用方手里可能还拿着这个 `Rc`：值明明还在内存里，下次 `get` 却要重新加载一份，
+// 同一个东西在内存里有了两份。
+//
+// 这里淘汰时不直接丢，而是把强引用降级成 `Weak` 存着：
+// - 别处还在用：`upgrade()` 成功，下次 `get` 直接把它拿回缓存（记一次 revived）
+// - 没人用了：值已经释放，`upgrade()` 失败，当作未命中
+// 缓存本身只对 `capacity` 个条目负责，被淘汰的值活多久由使用者决定。
+// 记着这些 `Weak` 的表按翻倍的阈值清理失效条目，大小有上界（见 `Core::evicted`）。
+//
+// TTL 从插入时算起，过期的条目不会降级成 `Weak`——过期的数据不该再被找回来。
+//
+// `LruCache` 用 `Rc`，只能在单线程里用；`SyncLruCache` 换成 `Arc` 再加一把 `Mutex`，
+// 两者共用同一份逻辑（`Core`），区别只在指针类型上，由 `Shared` trait 抽象。
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::rc::{self, Rc};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{self as stdsync, Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// 缓存用来判断过期的时钟，测试里可以换成手动拨动的 `ManualClock`
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+/// 真实时间
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// 只有调用 `advance` 才会走的时钟
+pub struct ManualClock {
+    start: Instant,
+    elapsed_nanos: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock {
+            start: Instant::now(),
+            elapsed_nanos: AtomicU64::new(0),
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + Duration::from_nanos(self.elapsed_nanos.load(Ordering::Relaxed))
+    }
+}
+
+/// 命中、未命中、淘汰的次数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// 在缓存里直接找到
+    pub hits: u64,
+    /// 已经被淘汰，但别处还在用，通过 `Weak` 找了回来
+    pub revived: u64,
+    pub misses: u64,
+    /// 因为超出容量被挤出去
+    pub evictions: u64,
+    /// 因为超过 TTL 被删掉
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    /// 命中率，找回来的也算命中；还没有查询过时是 0
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.revived + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            (self.hits + self.revived) as f64 / lookups as f64
+        }
+    }
+}
+
+impl fmt::Display for CacheStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "命中 {}，找回 {}，未命中 {}，淘汰 {}，过期 {}（命中率 {:.0}%）",
+            self.hits,
+            self.revived,
+            self.misses,
+            self.evictions,
+            self.expirations,
+            self.hit_rate() * 100.0
+        )
+    }
+}
+
+/// `Rc` 和 `Arc` 的共同点：能降级成弱引用，也能从弱引用升级回来
+pub trait Shared: Clone {
+    type Weak;
+
+    fn downgrade(this: &Self) -> Self::Weak;
+    fn upgrade(weak: &Self::Weak) -> Option<Self>;
+    fn strong_count(this: &Self) -> usize;
+}
+
+impl<V> Shared for Rc<V> {
+    type Weak = rc::Weak<V>;
+
+    fn downgrade(this: &Self) -> Self::Weak {
+        Rc::downgrade(this)
+    }
+
+    fn upgrade(weak: &Self::Weak) -> Option<Self> {
+        weak.upgrade()
+    }
+
+    fn strong_count(this: &Self) -> usize {
+        Rc::strong_count(this)
+    }
+}
+
+impl<V> Shared for Arc<V> {
+    type Weak = stdsync::Weak<V>;
+
+    fn downgrade(this: &Self) -> Self::Weak {
+        Arc::downgrade(this)
+    }
+
+    fn upgrade(weak: &Self::Weak) -> Option<Self> {
+        weak.upgrade()
+    }
+
+    fn strong_count(this: &Self) -> usize {
+        Arc::strong_count(this)
+    }
+}
+
+struct Entry<P> {
+    value: P,
+    inserted: Instant,
+    /// 最近一次使用的序号，也是它在 `order` 里的键
+    last_used: u64,
+}
+
+struct Evicted<W> {
+    value: W,
+    inserted: Instant,
+}
+
+/// `LruCache` 和 `SyncLruCache` 共用的实现
+struct Core<K, P: Shared> {
+    capacity: usize,
+    ttl: Option<Duration>,
+    clock: Arc<dyn Clock>,
+    entries: HashMap<K, Entry<P>>,
+    /// 使用序号 → 键，第一个就是最久没用的
+    order: BTreeMap<u64, K>,
+    next_tick: u64,
+    /// 被淘汰时别处还在用的值
+    ///
+    /// 这些值之后随时可能被释放，表里就留下了失效的 `Weak`（它还占着值原来的那块内存）。
+    /// 表长到 `prune_at` 时清理一次，`prune_at` 定为清理后大小的两倍、至少 `2 * capacity`：
+    /// - 表的大小不超过 `2 * max(capacity, 上次清理后还活着的条目数)`
+    /// - 两次清理之间至少隔着 `max(capacity, 存活数)` 次淘汰，均摊到每次淘汰是 O(1)
+    evicted: HashMap<K, Evicted<P::Weak>>,
+    prune_at: usize,
+    stats: CacheStats,
+}
+
+impl<K: Eq + Hash + Clone, P: Shared> Core<K, P> {
+    fn new(capacity: usize, ttl: Option<Duration>) -> Core<K, P> {
+        assert!(capacity > 0, "缓存容量至少为 1");
+        Core {
+            capacity,
+            ttl,
+            clock: Arc::new(SystemClock),
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            next_tick: 0,
+            evicted: HashMap::new(),
+            prune_at: 2 * capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    fn is_expired(&self, inserted: Instant, now: Instant) -> bool {
+        self.ttl
+            .is_some_and(|ttl| now.saturating_duration_since(inserted) >= ttl)
+    }
+
+    fn tick(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+
+    fn get(&mut self, key: &K) -> Option<P> {
+        let now = self.clock.now();
+
+        if let Some(entry) = self.entries.get(key) {
+            if self.is_expired(entry.inserted, now) {
+                self.remove_entry(key);
+                self.stats.expirations += 1;
+                self.stats.misses += 1;
+                return None;
+            }
+            let tick = self.tick();
+            let entry = self.entries.get_mut(key).unwrap();
+            let key = self.order.remove(&entry.last_used).unwrap();
+            entry.last_used = tick;
+            self.order.insert(tick, key);
+            self.stats.hits += 1;
+            return Some(entry.value.clone());
+        }
+
+        if let Some(evicted) = self.evicted.remove(key) {
+            if !self.is_expired(evicted.inserted, now) {
+                if let Some(value) = P::upgrade(&evicted.value) {
+                    self.stats.revived += 1;
+                    self.put(key.clone(), value.clone(), evicted.inserted);
+                    return Some(value);
+                }
+            }
+        }
+        self.stats.misses += 1;
+        None
+    }
+
+    fn insert(&mut self, key: K, value: P) {
+        let now = self.clock.now();
+        self.remove_entry(&key);
+        self.evicted.remove(&key);
+        self.put(key, value, now);
+    }
+
+    /// 放进缓存并标记为最近使用，超出容量就淘汰最久没用的
+    fn put(&mut self, key: K, value: P, inserted: Instant) {
+        let tick = self.tick();
+        self.order.insert(tick, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                inserted,
+                last_used: tick,
+            },
+        );
+        while self.entries.len() > self.capacity {
+            self.evict_oldest();
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        let (_, key) = self.order.pop_first().unwrap();
+        let entry = self.entries.remove(&key).unwrap();
+        self.stats.evictions += 1;
+        // 只有缓存自己拿着的值，降级以后马上就会释放，不用记
+        if P::strong_count(&entry.value) > 1 {
+            self.evicted.insert(
+                key,
+                Evicted {
+                    value: P::downgrade(&entry.value),
+                    inserted: entry.inserted,
+                },
+            );
+        }
+        if self.evicted.len() >= self.prune_at {
+            self.prune_evicted();
+        }
+    }
+
+    /// 删掉已经失效的弱引用，并定下次清理的阈值
+    fn prune_evicted(&mut self) {
+        self.evicted
+            .retain(|_, evicted| P::upgrade(&evicted.value).is_some());
+        self.prune_at = 2 * self.evicted.len().max(self.capacity);
+    }
+
+    fn remove_entry(&mut self, key: &K) -> Option<P> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.last_used);
+        Some(entry.value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<P> {
+        self.evicted.remove(key);
+        self.remove_entry(key)
+    }
+
+    /// 删掉所有过期的条目，返回删掉了几个
+    fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let expired: Vec<K> = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| self.is_expired(entry.inserted, now))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &expired {
+            self.remove_entry(key);
+        }
+        let ttl = self.ttl;
+        self.evicted.retain(|_, evicted| {
+            ttl.is_none_or(|ttl| now.saturating_duration_since(evicted.inserted) < ttl)
+        });
+        self.stats.expirations += expired.len() as u64;
+        expired.len()
+    }
+
+    /// 已经被淘汰、但别处还在用的条目数
+    fn evicted_in_use(&mut self) -> usize {
+        self.prune_evicted();
+        self.evicted.len()
+    }
+
+    /// 从最近使用到最久没用的键
+    fn keys(&self) -> Vec<K> {
+        self.order.values().rev().cloned().collect()
+    }
+}
+
+/// 单线程的 LRU / TTL 缓存，值以 `Rc<V>` 的形式交出去
+pub struct LruCache<K, V> {
+    core: Core<K, Rc<V>>,
+}
+
+impl<K: Eq + Hash + Clone, V> LruCache<K, V> {
+    /// 最多保存 `capacity` 个条目，永不过期
+    ///
+    /// # Panics
+    ///
+    /// `capacity` 为 0 时 panic。
+    pub fn new(capacity: usize) -> LruCache<K, V> {
+        LruCache {
+            core: Core::new(capacity, None),
+        }
+    }
+
+    /// 最多保存 `capacity` 个条目，插入 `ttl` 之后过期
+    pub fn with_ttl(capacity: usize, ttl: Duration) -> LruCache<K, V> {
+        LruCache {
+            core: Core::new(capacity, Some(ttl)),
+        }
+    }
+
+    /// 换一个时钟，测试 TTL 时用 `ManualClock`
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> LruCache<K, V> {
+        self.core.clock = clock;
+        self
+    }
+
+    /// 查找并标记为最近使用
+    pub fn get(&mut self, key: &K) -> Option<Rc<V>> {
+        self.core.get(key)
+    }
+
+    /// 插入或替换，返回缓存里的那份
+    pub fn insert(&mut self, key: K, value: V) -> Rc<V> {
+        let value = Rc::new(value);
+        self.core.insert(key, Rc::clone(&value));
+        value
+    }
+
+    /// 找不到时调用 `load` 加载并放进缓存
+    pub fn get_or_insert_with(&mut self, key: K, load: impl FnOnce() -> V) -> Rc<V> {
+        match self.core.get(&key) {
+            Some(value) => value,
+            None => self.insert(key, load()),
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<Rc<V>> {
+        self.core.remove(key)
+    }
+
+    /// 删掉所有过期的条目，返回删掉了几个
+    pub fn purge_expired(&mut self) -> usize {
+        self.core.purge_expired()
+    }
+
+    /// 已经被淘汰、但别处还拿着 `Rc` 的条目数
+    pub fn evicted_in_use(&mut self) -> usize {
+        self.core.evicted_in_use()
+    }
+
+    /// 从最近使用到最久没用的键
+    pub fn keys(&self) -> Vec<K> {
+        self.core.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.core.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.core.entries.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.core.capacity
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.core.stats
+    }
+}
+
+/// 线程安全的版本：值是 `Arc<V>`，所有操作都在一把锁里完成
+pub struct SyncLruCache<K, V> {
+    core: Mutex<Core<K, Arc<V>>>,
+}
+
+impl<K: Eq + Hash + Clone, V> SyncLruCache<K, V> {
+    /// 加锁，忽略毒化标记
+    ///
+    /// 持有锁时会调用的外部代码里，会 panic 的只有 `get_or_insert_with` 的 `load`
+    /// （键的 `Hash` / `Eq` 和时钟按约定不会 panic）；它在查找之后、插入之前运行，
+    /// 那时 `Core` 没有改到一半的状态。
+    fn lock(&self) -> MutexGuard<'_, Core<K, Arc<V>>> {
+        self.core.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// # Panics
+    ///
+    /// `capacity` 为 0 时 panic。
+    pub fn new(capacity: usize) -> SyncLruCache<K, V> {
+        SyncLruCache {
+            core: Mutex::new(Core::new(capacity, None)),
+        }
+    }
+
+    pub fn with_ttl(capacity: usize, ttl: Duration) -> SyncLruCache<K, V> {
+        SyncLruCache {
+            core: Mutex::new(Core::new(capacity, Some(ttl))),
+        }
+    }
+
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> SyncLruCache<K, V> {
+        let mut core = self.core.into_inner().unwrap_or_else(PoisonError::into_inner);
+        core.clock = clock;
+        SyncLruCache {
+            core: Mutex::new(core),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<Arc<V>> {
+        self.lock().get(key)
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Arc<V> {
+        let value = Arc::new(value);
+        self.lock().insert(key, Arc::clone(&value));
+        value
+    }
+
+    /// 找不到时调用 `load` 加载
+    ///
+    /// `load` 在持有锁的时候调用：同一时刻只有一个线程在加载，同一个键不会被加载两次，
+    /// 代价是加载期间其他线程的查询都要等着。`load` panic 时缓存保持原样，
+    /// 锁虽然被毒化了，之后的调用照常可用（见 [`SyncLruCache::lock`]）。
+    pub fn get_or_insert_with(&self, key: K, load: impl FnOnce() -> V) -> Arc<V> {
+        let mut core = self.lock();
+        if let Some(value) = core.get(&key) {
+            return value;
+        }
+        let value = Arc::new(load());
+        core.insert(key, Arc::clone(&value));
+        value
+    }
+
+    pub fn remove(&self, key: &K) -> Option<Arc<V>> {
+        self.lock().remove(key)
+    }
+
+    pub fn purge_expired(&self) -> usize {
+        self.lock().purge_expired()
+    }
+
+    pub fn evicted_in_use(&self) -> usize {
+        self.lock().evicted_in_use()
+    }
+
+    pub fn keys(&self) -> Vec<K> {
+        self.lock().keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.lock().capacity
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::thread;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = LruCache::new(3);
+        for key in ["a", "b", "c"] {
+            cache.insert(key, key.to_uppercase());
+        }
+        // 用一下 a，最久没用的就变成了 b
+        assert_eq!(cache.get(&"a").as_deref().map(String::as_str), Some("A"));
+        cache.insert("d", "D".to_string());
+
+        assert_eq!(cache.keys(), ["d", "a", "c"]);
+        assert!(cache.get(&"b").is_none());
+        cache.insert("e", "E".to_string());
+        assert_eq!(cache.keys(), ["e", "d", "a"]);
+        assert_eq!(cache.stats().evictions, 2);
+    }
+
+    #[test]
+    fn test_never_exceeds_capacity() {
+        let mut cache = LruCache::new(4);
+        for i in 0..100 {
+            cache.insert(i, i * i);
+            assert!(cache.len() <= cache.capacity());
+        }
+        assert_eq!(cache.len(), 4);
+        assert_eq!(cache.keys(), [99, 98, 97, 96]);
+        assert_eq!(cache.stats().evictions, 96);
+        // 没人拿着被淘汰的值，弱引用表里什么都不会留下
+        assert_eq!(cache.evicted_in_use(), 0);
+    }
+
+    #[test]
+    fn test_replacing_a_key_does_not_evict() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        cache.insert("a", 10);
+        assert_eq!(cache.keys(), ["a", "b"]);
+        assert_eq!(cache.get(&"a").map(|v| *v), Some(10));
+        assert_eq!(cache.stats().evictions, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "缓存容量至少为 1")]
+    fn test_zero_capacity_panics() {
+        let _ = LruCache::<i32, i32>::new(0);
+    }
+
+    #[test]
+    fn test_evicted_value_in_use_is_revived() {
+        let mut cache = LruCache::new(1);
+        let a = cache.insert("a", 1);
+        cache.insert("b", 2);
+        assert_eq!(cache.evicted_in_use(), 1);
+
+        // a 已经被挤出去了，但我们还拿着它，所以找得回来，而且是同一份
+        let revived = cache.get(&"a").unwrap();
+        assert!(Rc::ptr_eq(&a, &revived));
+        assert_eq!(cache.keys(), ["a"]);
+
+        // b 被挤出去时没人拿着，就真的没了
+        assert!(cache.get(&"b").is_none());
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.revived, stats.misses), (0, 1, 1));
+        assert_eq!(stats.evictions, 2);
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let clock = Arc::new(ManualClock::new());
+        let mut cache = LruCache::with_ttl(2, Duration::from_secs(10)).with_clock(clock.clone());
+        let a = cache.insert("a", 1);
+        clock.advance(Duration::from_secs(6));
+        cache.insert("b", 2);
+        cache.insert("c", 3); // 挤掉 a，但 a 还拿在手里
+
+        clock.advance(Duration::from_secs(5));
+        // 已经过期的 a 即使还活着也不会被找回来
+        assert!(cache.get(&"a").is_none());
+        assert_eq!(*a, 1);
+        assert_eq!(cache.get(&"b").map(|v| *v), Some(2));
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(cache.purge_expired(), 2);
+        assert!(cache.is_empty());
+        assert_eq!(cache.stats().expirations, 2);
+    }
+
+    #[test]
+    fn test_get_or_insert_with_loads_once() {
+        let mut cache = LruCache::new(2);
+        let mut loads = 0;
+        for _ in 0..3 {
+            cache.get_or_insert_with("k", || {
+                loads += 1;
+                "v"
+            });
+        }
+        assert_eq!(loads, 1);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert_eq!(format!("{:.2}", stats.hit_rate()), "0.67");
+        assert_eq!(cache.remove(&"k").map(|v| *v), Some("v"));
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_sync_cache_shared_between_threads() {
+        let cache = Arc::new(SyncLruCache::new(8));
+        let loads = Arc::new(AtomicU64::new(0));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let cache = Arc::clone(&cache);
+                let loads = Arc::clone(&loads);
+                thread::spawn(move || {
+                    for key in 0..8 {
+                        let value = cache.get_or_insert_with(key, || {
+                            loads.fetch_add(1, Ordering::Relaxed);
+                            key * 2
+                        });
+                        assert_eq!(*value, key * 2);
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // 容量够用，每个键只加载一次
+        assert_eq!(loads.load(Ordering::Relaxed), 8);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (24, 8));
+        assert_eq!(cache.len(), 8);
+    }
+
+    #[test]
+    fn test_sync_cache_revives_and_expires() {
+        let clock = Arc::new(ManualClock::new());
+        let cache = SyncLruCache::with_ttl(1, Duration::from_secs(1)).with_clock(clock.clone());
+        let kept = cache.insert(1, "one");
+        cache.insert(2, "two");
+        assert!(Arc::ptr_eq(&kept, &cache.get(&1).unwrap()));
+
+        clock.advance(Duration::from_secs(1));
+        assert!(cache.get(&1).is_none());
+        assert_eq!(cache.stats().expirations, 1);
+    }
+
+    #[test]
+    fn test_evicted_table_stays_bounded() {
+        // 每个值被淘汰时都还有人拿着，之后才释放：表里会不断出现失效的 Weak
+        let mut cache = LruCache::new(2);
+        let mut held = VecDeque::new();
+        for key in 0..1000 {
+            held.push_back(cache.insert(key, key));
+            if held.len() > 4 {
+                held.pop_front();
+            }
+            // 活着的被淘汰条目不超过 4 个，按文档的上界表最多 2 * max(2, 4) 个
+            assert!(cache.core.evicted.len() <= 8);
+        }
+        assert_eq!(cache.evicted_in_use(), 2);
+    }
+
+    #[test]
+    fn test_sync_cache_survives_panicking_loader() {
+        let cache = SyncLruCache::new(2);
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            cache.get_or_insert_with("k", || panic!("加载失败"));
+        }));
+        assert!(result.is_err());
+
+        // 锁被毒化了，但缓存照常可用，也没有留下加载了一半的条目
+        assert!(cache.is_empty());
+        assert_eq!(*cache.get_or_insert_with("k", || "v"), "v");
+        assert_eq!(cache.stats().misses, 2);
+    }
+}
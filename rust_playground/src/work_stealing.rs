@@ -0,0 +1,345 @@
+// ============================================
+// 工作窃取（work stealing）调度器
+// ============================================
+//
+// `ThreadPool` 的所有 worker 共享一个任务队列，每取一个任务都要抢同一把锁。
+// 工作窃取让每个 worker 拥有自己的双端队列：
+// - 自己产生的子任务压到队尾，也从队尾取（LIFO，刚拆出来的数据还在缓存里）
+// - 自己的队列空了，才去别人队列的队头"偷"一个（FIFO，偷到的往往是大块任务）
+// 大部分时间每个 worker 只碰自己的队列，锁几乎没有竞争。
+//
+// 为了对比，`Mode::SharedQueue` 用同样的接口跑单个共享队列。
+// worker 是 `thread::scope` 里的作用域线程，任务可以借用调用方的数据
+// （例如快速排序里拆开的 `&mut [T]`），不需要 `'static`。
+// 真实的实现（rayon、tokio）用无锁的 Chase-Lev 双端队列，这里用
+// `Mutex<VecDeque>` 代替，结构相同但更容易读懂。
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, TryLockError};
+use std::thread;
+
+type Job<'env> = Box<dyn for<'w> FnOnce(&Worker<'w, 'env>) + Send + 'env>;
+
+/// 任务队列的组织方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    /// 每个 worker 一个双端队列，空闲时去别人那里偷
+    WorkStealing,
+    /// 所有 worker 共享一个队列（`ThreadPool` 的做法）
+    SharedQueue,
+}
+
+/// 每个 worker 的统计
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkerStats {
+    /// 执行过的任务数
+    pub executed: usize,
+    /// 其中从别的 worker 偷来的任务数
+    pub stolen: usize,
+    /// 加锁时发现锁已被占用的次数
+    pub contended: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    executed: AtomicUsize,
+    stolen: AtomicUsize,
+    contended: AtomicUsize,
+}
+
+struct Shared<'env> {
+    mode: Mode,
+    /// `WorkStealing` 时每个 worker 一个；`SharedQueue` 时只有一个
+    queues: Vec<Mutex<VecDeque<Job<'env>>>>,
+    /// 已提交但还没执行完的任务数，降到 0 说明所有工作都做完了
+    pending: AtomicUsize,
+    counters: Vec<Counters>,
+}
+
+impl<'env> Shared<'env> {
+    /// 加锁并统计竞争：先 `try_lock`，失败说明有别的线程正持有这把锁
+    fn lock(&self, worker: usize, queue: usize) -> MutexGuard<'_, VecDeque<Job<'env>>> {
+        match self.queues[queue].try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) => {
+                self.counters[worker]
+                    .contended
+                    .fetch_add(1, Ordering::Relaxed);
+                self.queues[queue].lock().unwrap()
+            }
+            Err(TryLockError::Poisoned(_)) => panic!("任务队列被 panic 毒化"),
+        }
+    }
+
+    fn own_queue(&self, worker: usize) -> usize {
+        match self.mode {
+            Mode::WorkStealing => worker,
+            Mode::SharedQueue => 0,
+        }
+    }
+
+    fn find_job(&self, worker: usize) -> Option<Job<'env>> {
+        let own = self.own_queue(worker);
+        if let Some(job) = self.lock(worker, own).pop_back() {
+            return Some(job);
+        }
+        if self.mode == Mode::SharedQueue {
+            return None;
+        }
+
+        // 从下一个 worker 开始依次尝试偷，避免所有空闲 worker 都盯着同一个
+        let count = self.queues.len();
+        for offset in 1..count {
+            let victim = (worker + offset) % count;
+            if let Some(job) = self.lock(worker, victim).pop_front() {
+                self.counters[worker].stolen.fetch_add(1, Ordering::Relaxed);
+                return Some(job);
+            }
+        }
+        None
+    }
+}
+
+/// 任务运行时拿到的上下文，用来继续提交子任务
+pub struct Worker<'w, 'env> {
+    index: usize,
+    shared: &'w Shared<'env>,
+}
+
+impl<'env> Worker<'_, 'env> {
+    /// 提交一个子任务：放进当前 worker 自己的队列
+    pub fn spawn<F>(&self, job: F)
+    where
+        F: for<'w> FnOnce(&Worker<'w, 'env>) + Send + 'env,
+    {
+        self.shared.pending.fetch_add(1, Ordering::SeqCst);
+        let queue = self.shared.own_queue(self.index);
+        self.shared.lock(self.index, queue).push_back(Box::new(job));
+    }
+}
+
+/// 离开作用域时把待完成任务数减一
+struct Finished<'a>(&'a AtomicUsize);
+
+impl Drop for Finished<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn worker_loop(index: usize, shared: &Shared<'_>) {
+    let worker = Worker { index, shared };
+    let mut idle_rounds = 0u32;
+    loop {
+        match shared.find_job(index) {
+            Some(job) => {
+                idle_rounds = 0;
+                // 子任务在 job 里已经计入 pending，这里减掉的只是 job 自己；
+                // 用 drop 守卫来减，job panic 时其他 worker 也不会一直空等
+                let _finished = Finished(&shared.pending);
+                job(&worker);
+                shared.counters[index]
+                    .executed
+                    .fetch_add(1, Ordering::Relaxed);
+            }
+            None if shared.pending.load(Ordering::SeqCst) == 0 => break,
+            None => {
+                // 暂时没活干，但别的 worker 还可能产生新任务
+                idle_rounds += 1;
+                if idle_rounds < 64 {
+                    std::hint::spin_loop();
+                } else {
+                    thread::yield_now();
+                }
+            }
+        }
+    }
+}
+
+/// 用 `workers` 个线程运行 `root` 以及它递归提交的所有任务，全部完成后返回统计
+///
+/// # Panics
+///
+/// `workers` 为 0 时 panic；有任务 panic 时，其余 worker 做完剩下的任务后 `run` 也会 panic。
+pub fn run<'env, F>(mode: Mode, workers: usize, root: F) -> Vec<WorkerStats>
+where
+    F: for<'w> FnOnce(&Worker<'w, 'env>) + Send + 'env,
+{
+    assert!(workers > 0, "至少需要一个 worker");
+    let queue_count = match mode {
+        Mode::WorkStealing => workers,
+        Mode::SharedQueue => 1,
+    };
+    let queues: Vec<Mutex<VecDeque<Job<'env>>>> = (0..queue_count)
+        .map(|_| Mutex::new(VecDeque::new()))
+        .collect();
+    // 根任务放进第一个队列，其他 worker 一开始只能去偷
+    queues[0].lock().unwrap().push_back(Box::new(root));
+    let shared = Shared {
+        mode,
+        queues,
+        pending: AtomicUsize::new(1),
+        counters: (0..workers).map(|_| Counters::default()).collect(),
+    };
+
+    thread::scope(|scope| {
+        for index in 0..workers {
+            let shared = &shared;
+            scope.spawn(move || worker_loop(index, shared));
+        }
+    });
+
+    shared
+        .counters
+        .iter()
+        .map(|c| WorkerStats {
+            executed: c.executed.load(Ordering::Relaxed),
+            stolen: c.stolen.load(Ordering::Relaxed),
+            contended: c.contended.load(Ordering::Relaxed),
+        })
+        .collect()
+}
+
+// ---------- 递归任务示例 ----------
+
+/// 小于这个规模就直接顺序计算，拆得太细反而浪费在调度上
+const FIB_CUTOFF: u32 = 12;
+const SORT_CUTOFF: usize = 256;
+
+fn fib_sequential(n: u32) -> u64 {
+    if n < 2 {
+        n as u64
+    } else {
+        fib_sequential(n - 1) + fib_sequential(n - 2)
+    }
+}
+
+/// 每一层递归都拆成两个子任务，叶子把结果加到 `total`
+fn fib_task<'env>(worker: &Worker<'_, 'env>, n: u32, total: &'env AtomicU64) {
+    if n < FIB_CUTOFF {
+        total.fetch_add(fib_sequential(n), Ordering::Relaxed);
+        return;
+    }
+    worker.spawn(move |w| fib_task(w, n - 1, total));
+    worker.spawn(move |w| fib_task(w, n - 2, total));
+}
+
+/// 并行计算斐波那契数，返回结果和每个 worker 的统计
+pub fn parallel_fib(mode: Mode, workers: usize, n: u32) -> (u64, Vec<WorkerStats>) {
+    let total = AtomicU64::new(0);
+    let stats = run(mode, workers, |w| fib_task(w, n, &total));
+    (total.into_inner(), stats)
+}
+
+/// 以中间元素为基准分区，返回基准最终的位置
+fn partition<T: Ord>(data: &mut [T]) -> usize {
+    let last = data.len() - 1;
+    data.swap(data.len() / 2, last);
+    let mut store = 0;
+    for i in 0..last {
+        if data[i] < data[last] {
+            data.swap(i, store);
+            store += 1;
+        }
+    }
+    data.swap(store, last);
+    store
+}
+
+/// 分区后左右两半互不重叠，`split_at_mut` 把它们交给两个子任务
+fn quicksort_task<'env, T: Ord + Send + 'env>(worker: &Worker<'_, 'env>, data: &'env mut [T]) {
+    if data.len() <= SORT_CUTOFF {
+        data.sort_unstable();
+        return;
+    }
+    let pivot = partition(data);
+    let (left, right) = data.split_at_mut(pivot);
+    worker.spawn(move |w| quicksort_task(w, left));
+    worker.spawn(move |w| quicksort_task(w, &mut right[1..]));
+}
+
+/// 并行快速排序
+pub fn parallel_quicksort<T: Ord + Send>(
+    mode: Mode,
+    workers: usize,
+    data: &mut [T],
+) -> Vec<WorkerStats> {
+    run(mode, workers, |w| quicksort_task(w, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler::Rng;
+
+    fn random_data(len: usize, seed: u64) -> Vec<u32> {
+        let mut rng = Rng::new(seed);
+        (0..len).map(|_| rng.below(1_000_000) as u32).collect()
+    }
+
+    #[test]
+    fn test_fib_matches_sequential_in_both_modes() {
+        for mode in [Mode::WorkStealing, Mode::SharedQueue] {
+            for workers in [1, 4] {
+                let (result, stats) = parallel_fib(mode, workers, 22);
+                assert_eq!(result, fib_sequential(22), "{:?} × {}", mode, workers);
+                assert_eq!(stats.len(), workers);
+            }
+        }
+    }
+
+    #[test]
+    fn test_quicksort_sorts_in_both_modes() {
+        for mode in [Mode::WorkStealing, Mode::SharedQueue] {
+            let mut data = random_data(20_000, 7);
+            let mut expected = data.clone();
+            expected.sort_unstable();
+            parallel_quicksort(mode, 4, &mut data);
+            assert_eq!(data, expected, "{:?}", mode);
+        }
+
+        let mut few = vec![3, 1, 2];
+        parallel_quicksort(Mode::WorkStealing, 2, &mut few);
+        assert_eq!(few, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_stats_count_every_job() {
+        // 每个 n >= FIB_CUTOFF 的任务拆成两个子任务，总任务数可以直接数出来
+        fn jobs(n: u32) -> usize {
+            if n < FIB_CUTOFF {
+                1
+            } else {
+                1 + jobs(n - 1) + jobs(n - 2)
+            }
+        }
+        let (_, stats) = parallel_fib(Mode::WorkStealing, 3, 18);
+        let executed: usize = stats.iter().map(|s| s.executed).sum();
+        assert_eq!(executed, jobs(18));
+        assert!(stats.iter().all(|s| s.stolen <= s.executed));
+    }
+
+    #[test]
+    fn test_single_worker_never_steals() {
+        let (_, stats) = parallel_fib(Mode::WorkStealing, 1, 18);
+        assert_eq!(stats[0].stolen, 0);
+        assert_eq!(stats[0].contended, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "a scoped thread panicked")]
+    fn test_panicking_job_propagates_instead_of_hanging() {
+        run(Mode::WorkStealing, 3, |w| {
+            for i in 0..10 {
+                w.spawn(move |_| assert!(i != 5, "任务失败"));
+            }
+        });
+    }
+
+    #[test]
+    fn test_shared_queue_never_steals() {
+        let (_, stats) = parallel_fib(Mode::SharedQueue, 4, 18);
+        assert!(stats.iter().all(|s| s.stolen == 0));
+    }
+}
@@ -0,0 +1,197 @@
+// ============================================
+// 示例输出回归测试（golden output）
+// ============================================
+//
+// 对每一节课运行 `rust_playground run <id>`，把输出和
+// `tests/snapshots/<主题>/<函数名>.txt` 比较：
+// - 默认：逐字比较
+// - 标记为 unordered 的课程（多线程交错打印）：只比较行的集合，忽略顺序
+// - 标记为 nondeterministic 的课程：输出本身不稳定，跳过
+// - 标记为 seeded 的课程：额外用 `--seed 1` 在确定性调度下运行一次，
+//   和 `<函数名>.seed1.txt` 逐字比较
+//
+// 示例输出有意修改后，用下面的命令重新生成快照：
+//   UPDATE_SNAPSHOTS=1 cargo test --test lessons
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+
+const BIN: &str = env!("CARGO_BIN_EXE_rust_playground");
+
+/// 确定性调度模式下快照使用的种子
+const SEED: &str = "1";
+
+fn snapshot_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("snapshots")
+}
+
+/// `variant` 非空时对应 `<函数名>.<variant>.txt`
+fn snapshot_path(id: &str, variant: Option<&str>) -> PathBuf {
+    let name = id.replace("::", "/");
+    match variant {
+        Some(variant) => snapshot_dir().join(format!("{}.{}.txt", name, variant)),
+        None => snapshot_dir().join(format!("{}.txt", name)),
+    }
+}
+
+fn updating() -> bool {
+    std::env::var_os("UPDATE_SNAPSHOTS").is_some()
+}
+
+/// 运行程序并返回 stdout，失败时带上 stderr
+fn run(args: &[&str]) -> String {
+    let output = Command::new(BIN).args(args).output().unwrap();
+    assert!(
+        output.status.success(),
+        "`rust_playground {}` 失败:\n{}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn lesson_ids(tag: Option<&str>) -> Vec<String> {
+    let mut args = vec!["list", "--ids"];
+    if let Some(tag) = tag {
+        args.extend(["--tag", tag]);
+    }
+    run(&args).lines().map(String::from).collect()
+}
+
+fn sorted_lines(text: &str) -> Vec<&str> {
+    let mut lines: Vec<&str> = text.lines().collect();
+    lines.sort_unstable();
+    lines
+}
+
+/// 比较一节课的输出，返回不一致时的说明
+fn check(path: PathBuf, id: &str, actual: &str, unordered: bool) -> Result<(), String> {
+    if updating() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, actual).unwrap();
+        return Ok(());
+    }
+
+    let expected = fs::read_to_string(&path).map_err(|_| {
+        format!(
+            "{}: 快照 {} 不存在，运行 `UPDATE_SNAPSHOTS=1 cargo test --test lessons` 生成",
+            id,
+            path.display()
+        )
+    })?;
+
+    let same = if unordered {
+        sorted_lines(&expected) == sorted_lines(actual)
+    } else {
+        expected == actual
+    };
+    if same {
+        return Ok(());
+    }
+
+    let mut report = format!("{}: 输出与快照 {} 不一致", id, path.display());
+    if unordered {
+        report.push_str("（忽略行顺序）");
+    }
+    let expected_lines: Vec<&str> = expected.lines().collect();
+    let actual_lines: Vec<&str> = actual.lines().collect();
+    for line in expected_lines
+        .iter()
+        .filter(|line| !actual_lines.contains(line))
+    {
+        report.push_str(&format!("\n  - {}", line));
+    }
+    for line in actual_lines
+        .iter()
+        .filter(|line| !expected_lines.contains(line))
+    {
+        report.push_str(&format!("\n  + {}", line));
+    }
+    Err(report)
+}
+
+#[test]
+fn lesson_output_matches_snapshots() {
+    let unordered: HashSet<String> = lesson_ids(Some("unordered")).into_iter().collect();
+    let skipped: HashSet<String> = lesson_ids(Some("nondeterministic")).into_iter().collect();
+    let ids: Vec<String> = lesson_ids(None)
+        .into_iter()
+        .filter(|id| !skipped.contains(id))
+        .collect();
+    assert!(!ids.is_empty());
+
+    // 每节课是独立进程，并行跑可以省下并发示例里 sleep 的时间
+    let failures: Vec<String> = thread::scope(|scope| {
+        let handles: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                let unordered = unordered.contains(id);
+                let path = snapshot_path(id, None);
+                scope.spawn(move || check(path, id, &run(&["run", id]), unordered))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap().err())
+            .collect()
+    });
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
+
+#[test]
+fn seeded_lessons_are_reproducible() {
+    let ids = lesson_ids(Some("seeded"));
+    assert!(!ids.is_empty());
+
+    let variant = format!("seed{}", SEED);
+    let failures: Vec<String> = thread::scope(|scope| {
+        let handles: Vec<_> = ids
+            .iter()
+            .map(|id| {
+                let path = snapshot_path(id, Some(&variant));
+                scope.spawn(move || {
+                    let first = run(&["run", id, "--seed", SEED]);
+                    let second = run(&["run", id, "--seed", SEED]);
+                    if first != second {
+                        return Err(format!("{}: 同一个种子两次运行的输出不同", id));
+                    }
+                    check(path, id, &first, false)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().unwrap().err())
+            .collect()
+    });
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
+
+#[test]
+fn every_snapshot_belongs_to_a_lesson() {
+    let ids: HashSet<String> = lesson_ids(None).into_iter().collect();
+    let mut orphans = Vec::new();
+
+    for topic in fs::read_dir(snapshot_dir()).unwrap() {
+        let topic = topic.unwrap().path();
+        for file in fs::read_dir(&topic).unwrap() {
+            let file = file.unwrap().path();
+            // `barrier_example.seed1.txt` 也属于 `barrier_example`
+            let stem = file.file_stem().unwrap().to_string_lossy();
+            let name = stem.split('.').next().unwrap();
+            let id = format!("{}::{}", topic.file_name().unwrap().to_string_lossy(), name);
+            if !ids.contains(&id) {
+                orphans.push(file.display().to_string());
+            }
+        }
+    }
+
+    assert!(orphans.is_empty(), "没有对应课程的快照文件: {:?}", orphans);
+}